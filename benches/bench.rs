@@ -12,7 +12,7 @@ extern crate nom;
 #[macro_use]
 extern crate rusticata_macros;
 
-use der_parser::ber::{BerObjectHeader, BerTag};
+use der_parser::ber::{BerObjectHeader, BerSize, BerTag};
 use der_parser::der::{
     der_read_element_header, parse_der, parse_der_integer, parse_der_u32, DerObject,
 };
@@ -31,7 +31,7 @@ fn bench_der_read_element_header(b: &mut Bencher) {
                         class: 0,
                         structured: 0,
                         tag: BerTag(12),
-                        len: 10
+                        len: BerSize::Definite(10),
                     }
                 );
             }
@@ -89,7 +89,7 @@ fn bench_parse_der_seq(b: &mut Bencher) {
 
 #[bench]
 fn bench_parse_der_seq_macros(b: &mut Bencher) {
-    fn localparse_seq(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn localparse_seq(i: &[u8]) -> DerResult {
         parse_der_sequence_defined_m!(i, parse_der_integer >> parse_der_integer)
     }
     let bytes = hex!("30 0a 02 03 01 00 01 02 03 01 00 00");
@@ -1,10 +1,14 @@
-use crate::ber::bytes_to_u64;
+use crate::ber::{bytes_to_i128, bytes_to_unsigned, is_minimal_integer, BerDate, BerDateTime, BerDuration, BerTime, BerTimeOfDay};
+use crate::ber::{BerCharacterString, BerEmbeddedPdv, BerExternal};
+use crate::ber::string::{decode_ascii, decode_bmp, decode_t61, decode_ucs4, decode_utf8};
+use crate::ber::serialize::{encode_signed, encode_unsigned};
 use crate::error::BerError;
+use std::borrow::Cow;
 use std::vec::Vec;
 use std::ops::Index;
 use std::convert::From;
 use std::convert::AsRef;
-use oid::Oid;
+use oid::{Oid, RelativeOid};
 
 /// Defined in X.680 section 8.4
 /// X.690 doesn't specify the maxmimum tag size so we're assuming that people
@@ -28,21 +32,32 @@ impl debug BerTag {
     EmbeddedPdv = 0xb,
     Utf8String = 0xc,
     RelativeOid = 0xd,
+    Time = 0xe,
 
     Sequence = 0x10,
     Set = 0x11,
     NumericString = 0x12,
     PrintableString = 0x13,
     T61String = 0x14,
+    VideotexString = 0x15,
 
     Ia5String = 0x16,
     UtcTime = 0x17,
     GeneralizedTime = 0x18,
 
+    GraphicString = 0x19,
+    VisibleString = 0x1a,
     GeneralString = 27, // 0x1b
 
+    UniversalString = 0x1c,
+    CharacterString = 0x1d,
     BmpString = 0x1e,
 
+    Date = 0x1f,
+    TimeOfDay = 0x20,
+    DateTime = 0x21,
+    Duration = 0x22,
+
     Invalid = 0xff,
 }
 }
@@ -62,7 +77,16 @@ pub struct BerObjectHeader {
     pub class: u8,
     pub structured: u8,
     pub tag: BerTag,
-    pub len: u64,
+    pub len: BerSize,
+}
+
+/// Length of the content of a BER object (X.690 section 8.1.3)
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum BerSize {
+    /// Definite form: number of content octets
+    Definite(usize),
+    /// Indefinite form: content is terminated by end-of-content octets
+    Indefinite,
 }
 
 
@@ -70,31 +94,45 @@ pub struct BerObjectHeader {
 pub enum BerObjectContent<'a> {
     EndOfContent,
     Boolean(bool),
-    Integer(&'a[u8]),
+    Integer(Cow<'a, [u8]>),
     BitString(u8, BitStringObject<'a>),
-    OctetString(&'a [u8]),
+    OctetString(Cow<'a, [u8]>),
     Null,
-    Enum(u64),
-    OID(Oid),
-    RelativeOID(Oid),
-    NumericString(&'a[u8]),
-    PrintableString(&'a[u8]),
-    IA5String(&'a[u8]),
-    UTF8String(&'a[u8]),
-    T61String(&'a[u8]),
-
-    BmpString(&'a[u8]),
+    Enum(i64),
+    Real(f64),
+    OID(Oid<'a>),
+    RelativeOID(RelativeOid<'a>),
+    NumericString(Cow<'a, [u8]>),
+    PrintableString(Cow<'a, [u8]>),
+    IA5String(Cow<'a, [u8]>),
+    UTF8String(Cow<'a, [u8]>),
+    T61String(Cow<'a, [u8]>),
+    VideotexString(Cow<'a, [u8]>),
+    GraphicString(Cow<'a, [u8]>),
+    VisibleString(Cow<'a, [u8]>),
+    UniversalString(Cow<'a, [u8]>),
+    ObjectDescriptor(Cow<'a, [u8]>),
+    CharacterString(BerCharacterString<'a>),
+    External(BerExternal<'a>),
+    EmbeddedPdv(BerEmbeddedPdv<'a>),
+
+    BmpString(Cow<'a, [u8]>),
 
     Sequence(Vec<BerObject<'a> >),
     Set(Vec<BerObject<'a> >),
 
-    UTCTime(&'a [u8]),
-    GeneralizedTime(&'a [u8]),
+    UTCTime(Cow<'a, [u8]>),
+    GeneralizedTime(Cow<'a, [u8]>),
+    Time(Cow<'a, [u8]>),
+    Date(Cow<'a, [u8]>),
+    TimeOfDay(Cow<'a, [u8]>),
+    DateTime(Cow<'a, [u8]>),
+    Duration(Cow<'a, [u8]>),
 
-    GeneralString(&'a [u8]),
+    GeneralString(Cow<'a, [u8]>),
 
     ContextSpecific(BerTag, Option<Box<BerObject<'a>>>),
-    Unknown(BerTag, &'a[u8]),
+    Unknown(BerTag, Cow<'a, [u8]>),
 }
 
 impl BerObjectHeader {
@@ -119,6 +157,22 @@ impl BerObjectHeader {
     pub fn is_constructed(&self) -> bool { self.structured == 1 }
 }
 
+impl BerSize {
+    /// Test if length is encoded using the indefinite form
+    #[inline]
+    pub fn is_indefinite(&self) -> bool { *self == BerSize::Indefinite }
+
+    /// Get the length of a definite form, or fail with `InvalidLength` for the indefinite form
+    ///
+    /// Primitive objects (and all DER objects) always use the definite form.
+    pub fn primitive(&self) -> Result<usize,BerError> {
+        match *self {
+            BerSize::Definite(l) => Ok(l),
+            BerSize::Indefinite  => Err(BerError::InvalidLength),
+        }
+    }
+}
+
 impl<'a> BerObject<'a> {
     /// Build a BerObject from a header and content.
     /// Note: values are not checked, so the tag can be different from the real content, or flags
@@ -132,13 +186,16 @@ impl<'a> BerObject<'a> {
         }
     }
     /// Build a BerObject from its content, using default flags (no class, correct tag,
-    /// and structured flag set only for Set and Sequence)
+    /// and structured flag set only for Set, Sequence and types encoded as a Sequence)
     pub fn from_obj(c: BerObjectContent) -> BerObject {
         let class = 0;
         let tag = c.tag();
         let structured = match tag {
             BerTag::Sequence |
-            BerTag::Set      => 1,
+            BerTag::Set      |
+            BerTag::External |
+            BerTag::EmbeddedPdv |
+            BerTag::CharacterString => 1,
             _                => 0,
         };
         BerObject{
@@ -155,7 +212,7 @@ impl<'a> BerObject<'a> {
             class:      0,
             structured: 0,
             tag:        BerTag::Integer,
-            content:    BerObjectContent::Integer(i),
+            content:    BerObjectContent::Integer(Cow::Borrowed(i)),
         }
     }
 
@@ -169,8 +226,110 @@ impl<'a> BerObject<'a> {
         BerObject::from_obj( BerObjectContent::Set(l) )
     }
 
+    /// Build a DER integer object from an unsigned value
+    ///
+    /// The integer is encoded using the minimal number of octets.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::BerObject;
+    /// # fn main() {
+    /// let der_int = BerObject::from_u64(0x10001);
+    /// assert_eq!(der_int, BerObject::from_int_slice(b"\x01\x00\x01"));
+    /// # }
+    /// ```
+    pub fn from_u64(i: u64) -> BerObject<'a> {
+        BerObject::from_obj(BerObjectContent::Integer(Cow::Owned(encode_unsigned(i))))
+    }
+
+    /// Build a DER integer object from a signed value
+    ///
+    /// The integer is encoded using the minimal number of octets.
+    pub fn from_i64(i: i64) -> BerObject<'a> {
+        BerObject::from_obj(BerObjectContent::Integer(Cow::Owned(encode_signed(i))))
+    }
+
+    /// Build a DER real object
+    ///
+    /// When serialized, finite values use the binary encoding with base 2 (X.690 section 11.3).
+    pub fn from_f64(f: f64) -> BerObject<'a> {
+        BerObject::from_obj(BerObjectContent::Real(f))
+    }
+
+    /// Build a DER boolean object
+    pub fn from_bool(b: bool) -> BerObject<'a> {
+        BerObject::from_obj(BerObjectContent::Boolean(b))
+    }
+
+    /// Build a DER UTF-8 string object from a borrowed or owned string
+    pub fn from_str_utf8<S: Into<Cow<'a, str>>>(s: S) -> BerObject<'a> {
+        BerObject::from_obj(BerObjectContent::UTF8String(str_into_bytes(s.into())))
+    }
+
+    /// Build a DER printable string object from a borrowed or owned string
+    ///
+    /// Note: the string is not checked, the caller is responsible for using only characters
+    /// allowed in a PrintableString.
+    pub fn from_printable<S: Into<Cow<'a, str>>>(s: S) -> BerObject<'a> {
+        BerObject::from_obj(BerObjectContent::PrintableString(str_into_bytes(s.into())))
+    }
+
+    /// Build a DER octet string object from borrowed or owned bytes
+    pub fn from_octetstring<B: Into<Cow<'a, [u8]>>>(b: B) -> BerObject<'a> {
+        BerObject::from_obj(BerObjectContent::OctetString(b.into()))
+    }
+
+    /// Build a DER bitstring object from the bits (first bit is the most significant bit of the
+    /// first octet), and the number of unused bits in the last octet
+    pub fn from_bitstring<B: Into<Cow<'a, [u8]>>>(bits: B, unused_bits: u8) -> BerObject<'a> {
+        BerObject::from_obj(BerObjectContent::BitString(unused_bits, BitStringObject{ data: bits.into() }))
+    }
+
+    /// Build an EXPLICIT context-specific tagged object (`[tag] EXPLICIT`) wrapping `obj`
+    ///
+    /// The class can be changed afterwards (for ex. to build an application tagged object).
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::{BerObject,BerTag};
+    /// # fn main() {
+    /// let obj = BerObject::from_explicit(BerTag(2), BerObject::from_u64(1));
+    /// assert_eq!(obj.to_der().unwrap(), vec![0xa2, 0x03, 0x02, 0x01, 0x01]);
+    /// # }
+    /// ```
+    pub fn from_explicit(tag: BerTag, obj: BerObject<'a>) -> BerObject<'a> {
+        BerObject{
+            class:      0b10,
+            structured: 1,
+            tag,
+            content:    BerObjectContent::ContextSpecific(tag, Some(Box::new(obj))),
+        }
+    }
+
+    /// Build an IMPLICIT context-specific tagged object (`[tag] IMPLICIT`) from `obj`
+    ///
+    /// The identifier of `obj` is replaced, and its content is kept.
+    /// The class can be changed afterwards (for ex. to build an application tagged object).
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::{BerObject,BerTag};
+    /// # fn main() {
+    /// let obj = BerObject::from_implicit(BerTag(2), BerObject::from_u64(1));
+    /// assert_eq!(obj.to_der().unwrap(), vec![0x82, 0x01, 0x01]);
+    /// # }
+    /// ```
+    pub fn from_implicit(tag: BerTag, obj: BerObject<'a>) -> BerObject<'a> {
+        BerObject{
+            class:      0b10,
+            structured: obj.structured,
+            tag,
+            content:    obj.content,
+        }
+    }
+
     /// Attempt to read integer value from DER object.
-    /// This can fail if the object is not an integer, or if it is too large.
+    /// This can fail if the object is not an integer, if it is too large, or if it is negative.
     ///
     /// ```rust
     /// # extern crate der_parser;
@@ -186,13 +345,14 @@ impl<'a> BerObject<'a> {
     pub fn as_u64(&self) -> Result<u64,BerError> { self.content.as_u64() }
 
     /// Attempt to read integer value from DER object.
-    /// This can fail if the object is not an integer, or if it is too large.
+    /// This can fail if the object is not an integer, if it is too large, or if it is negative.
     ///
     /// ```rust
     /// # extern crate der_parser;
     /// # use der_parser::ber::{BerObject,BerObjectContent};
+    /// # use std::borrow::Cow;
     /// # fn main() {
-    /// let der_int  = BerObject::from_obj(BerObjectContent::Integer(b"\x01\x00\x01"));
+    /// let der_int  = BerObject::from_obj(BerObjectContent::Integer(Cow::Borrowed(b"\x01\x00\x01")));
     /// assert_eq!(
     ///     der_int.as_u32(),
     ///     Ok(0x10001)
@@ -201,20 +361,149 @@ impl<'a> BerObject<'a> {
     /// ```
     pub fn as_u32(&self) -> Result<u32,BerError> { self.content.as_u32() }
 
+    /// Attempt to read a signed integer value from DER object.
+    /// This can fail if the object is not an integer, or if it is too large.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::BerObject;
+    /// # fn main() {
+    /// let der_int  = BerObject::from_int_slice(b"\xff\x7f");
+    /// assert_eq!(
+    ///     der_int.as_i64(),
+    ///     Ok(-129)
+    /// );
+    /// # }
+    /// ```
+    pub fn as_i64(&self) -> Result<i64,BerError> { self.content.as_i64() }
+
+    /// Attempt to read a signed integer value from DER object.
+    /// This can fail if the object is not an integer, or if it is too large.
+    pub fn as_i32(&self) -> Result<i32,BerError> { self.content.as_i32() }
+
+    /// Attempt to read a signed integer value from DER object.
+    /// This can fail if the object is not an integer, or if it is too large.
+    pub fn as_i128(&self) -> Result<i128,BerError> { self.content.as_i128() }
+
+    /// Test if an integer is encoded using the minimum number of octets.
+    /// This can fail if the object is not an integer.
+    ///
+    /// A minimal encoding is required by DER (X.690 section 8.3.2). Redundant leading octets
+    /// make an encoding malleable, so this should be checked before verifying a signature.
+    ///
+    /// ENUMERATED values are decoded as `i64` and their encoding is not kept, so this fails with
+    /// `BerTypeError`. Use [`parse_der_enum`](../der/fn.parse_der_enum.html) to reject
+    /// non-minimal encodings.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::BerObject;
+    /// # fn main() {
+    /// assert_eq!(BerObject::from_int_slice(b"\x00\x80").is_minimal(), Ok(true));
+    /// assert_eq!(BerObject::from_int_slice(b"\x00\x7f").is_minimal(), Ok(false));
+    /// # }
+    /// ```
+    pub fn is_minimal(&self) -> Result<bool,BerError> { self.content.is_minimal() }
+
     /// Attempt to read integer value from DER object.
     /// This can fail if the object is not a boolean.
     pub fn as_bool(&self) -> Result<bool,BerError> { self.content.as_bool() }
 
+    /// Attempt to read a real value from DER object.
+    /// This can fail if the object is not a real.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::parse_ber_real;
+    /// # fn main() {
+    /// // 3 * 2^-2, binary encoding
+    /// let bytes = [0x09, 0x03, 0x80, 0xfe, 0x03];
+    /// let (_, obj) = parse_ber_real(&bytes).expect("parsing failed");
+    /// assert_eq!(obj.as_f64(), Ok(0.75));
+    /// # }
+    /// ```
+    pub fn as_f64(&self) -> Result<f64,BerError> { self.content.as_f64() }
+
+    /// Attempt to decode a UTCTime object.
+    /// This can fail if the object is not a UTCTime, or if its content is invalid.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::{BerObject,BerObjectContent,BerTimeZone};
+    /// # use std::borrow::Cow;
+    /// # fn main() {
+    /// let obj = BerObject::from_obj(BerObjectContent::UTCTime(Cow::Borrowed(b"190102030405Z")));
+    /// let t = obj.as_utctime().expect("invalid time");
+    /// assert_eq!((t.year, t.month, t.day), (2019, 1, 2));
+    /// assert_eq!(t.tz, BerTimeZone::Z);
+    /// # }
+    /// ```
+    pub fn as_utctime(&self) -> Result<BerDateTime,BerError> { self.content.as_utctime() }
+
+    /// Attempt to decode a GeneralizedTime object.
+    /// This can fail if the object is not a GeneralizedTime, or if its content is invalid.
+    pub fn as_generalizedtime(&self) -> Result<BerDateTime,BerError> { self.content.as_generalizedtime() }
+
+    /// Attempt to decode a TIME object.
+    /// This can fail if the object is not a TIME, if its content is invalid, or if it is a time
+    /// interval or recurrence.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::{BerObject,BerObjectContent,BerTime};
+    /// # use std::borrow::Cow;
+    /// # fn main() {
+    /// let obj = BerObject::from_obj(BerObjectContent::Time(Cow::Borrowed(b"2019-01-02T03:04Z")));
+    /// match obj.as_time() {
+    ///     Ok(BerTime::DateTime(dt)) => assert_eq!((dt.day, dt.hour, dt.minute), (2, 3, 4)),
+    ///     _ => panic!("unexpected value"),
+    /// }
+    /// # }
+    /// ```
+    pub fn as_time(&self) -> Result<BerTime,BerError> { self.content.as_time() }
+
+    /// Attempt to decode a DATE object.
+    /// This can fail if the object is not a DATE, or if its content is invalid.
+    pub fn as_date(&self) -> Result<BerDate,BerError> { self.content.as_date() }
+
+    /// Attempt to decode a TIME-OF-DAY object.
+    /// This can fail if the object is not a TIME-OF-DAY, or if its content is invalid.
+    pub fn as_timeofday(&self) -> Result<BerTimeOfDay,BerError> { self.content.as_timeofday() }
+
+    /// Attempt to decode a DATE-TIME object.
+    /// This can fail if the object is not a DATE-TIME, or if its content is invalid.
+    pub fn as_datetime(&self) -> Result<BerDateTime,BerError> { self.content.as_datetime() }
+
+    /// Attempt to decode a DURATION object.
+    /// This can fail if the object is not a DURATION, or if its content is invalid.
+    pub fn as_duration(&self) -> Result<BerDuration,BerError> { self.content.as_duration() }
+
+    /// Attempt to get a reference on the decoded value of an EXTERNAL object.
+    /// This can fail if the object is not an EXTERNAL.
+    pub fn as_external(&self) -> Result<&BerExternal<'a>,BerError> { self.content.as_external() }
+
+    /// Attempt to get a reference on the decoded value of an EMBEDDED PDV object.
+    /// This can fail if the object is not an EMBEDDED PDV.
+    pub fn as_embedded_pdv(&self) -> Result<&BerEmbeddedPdv<'a>,BerError> { self.content.as_embedded_pdv() }
+
+    /// Attempt to get a reference on the decoded value of an unrestricted CHARACTER STRING object.
+    /// This can fail if the object is not a CHARACTER STRING.
+    pub fn as_character_string(&self) -> Result<&BerCharacterString<'a>,BerError> { self.content.as_character_string() }
+
     /// Attempt to read an OID value from DER object.
     /// This can fail if the object is not an OID.
     ///
     /// Note that this function returns a reference to the OID. To get an owned value,
     /// use [`as_oid_val`](struct.BerObject.html#method.as_oid_val)
-    pub fn as_oid(&self) -> Result<&Oid,BerError> { self.content.as_oid() }
+    pub fn as_oid(&self) -> Result<&Oid<'a>,BerError> { self.content.as_oid() }
 
     /// Attempt to read an OID value from DER object.
     /// This can fail if the object is not an OID.
-    pub fn as_oid_val(&self) -> Result<Oid,BerError> { self.content.as_oid_val() }
+    pub fn as_oid_val(&self) -> Result<Oid<'a>,BerError> { self.content.as_oid_val() }
+
+    /// Attempt to read a RELATIVE-OID value from DER object.
+    /// This can fail if the object is not a RELATIVE-OID.
+    pub fn as_relative_oid(&self) -> Result<&RelativeOid<'a>,BerError> { self.content.as_relative_oid() }
 
     /// Attempt to read the content from a context-specific DER object.
     /// This can fail if the object is not context-specific.
@@ -251,7 +540,30 @@ impl<'a> BerObject<'a> {
     /// This can fail if the object does not contain a type directly equivalent to a slice (e.g a
     /// sequence).
     /// This function mostly concerns string types, integers, or unknown DER objects.
-    pub fn as_slice(&self) -> Result<&'a [u8],BerError> { self.content.as_slice() }
+    pub fn as_slice(&self) -> Result<&[u8],BerError> { self.content.as_slice() }
+
+    /// Attempt to decode a string object.
+    /// This can fail if the object is not a string, or if it contains invalid characters.
+    ///
+    /// Each string type is decoded according to its character set: ASCII for IA5String,
+    /// NumericString, PrintableString and VisibleString, UCS-2 for BmpString, UCS-4 for
+    /// UniversalString, T.61 for T61String and VideotexString, and UTF-8 for UTF8String,
+    /// GraphicString, ObjectDescriptor and GeneralString.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::{BerObject,BerObjectContent};
+    /// # use std::borrow::Cow;
+    /// # fn main() {
+    /// let obj = BerObject::from_obj(BerObjectContent::BmpString(Cow::Borrowed(b"\x00a\x00b\x20\xac")));
+    /// assert_eq!(obj.as_str().as_ref().map(|s| s.as_ref()), Ok("ab€"));
+    /// # }
+    /// ```
+    pub fn as_str(&self) -> Result<Cow<str>,BerError> { self.content.as_str() }
+
+    /// Decode a string object, replacing invalid characters with `U+FFFD`.
+    /// This can fail if the object is not a string.
+    pub fn to_string_lossy(&self) -> Result<Cow<str>,BerError> { self.content.to_string_lossy() }
 
     /// Test if object class is Universal
     pub fn is_universal(&self) -> bool { self.class == 0 }
@@ -266,11 +578,44 @@ impl<'a> BerObject<'a> {
     pub fn is_primitive(&self) -> bool { self.structured == 0 }
     /// Test if object is constructed
     pub fn is_constructed(&self) -> bool { self.structured == 1 }
+
+    /// Convert the object to an owned value, copying all data borrowed from the input.
+    ///
+    /// The returned object does not depend on the lifetime of the parsed data, so it can be
+    /// stored or sent to another thread after the input buffer has been dropped.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::{parse_ber, BerObject};
+    /// # fn main() {
+    /// fn read_object() -> BerObject<'static> {
+    ///     let bytes = vec![0x02, 0x03, 0x01, 0x00, 0x01];
+    ///     let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+    ///     obj.into_owned()
+    /// }
+    /// assert_eq!(read_object().as_u64(), Ok(0x10001));
+    /// # }
+    /// ```
+    pub fn into_owned(self) -> BerObject<'static> {
+        BerObject{
+            class:      self.class,
+            structured: self.structured,
+            tag:        self.tag,
+            content:    self.content.into_owned(),
+        }
+    }
+}
+
+fn str_into_bytes(s: Cow<str>) -> Cow<[u8]> {
+    match s {
+        Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
+        Cow::Owned(s)    => Cow::Owned(s.into_bytes()),
+    }
 }
 
 /// Build a DER object from an OID.
-impl<'a> From<Oid> for BerObject<'a> {
-    fn from(oid: Oid) -> BerObject<'a> {
+impl<'a> From<Oid<'a>> for BerObject<'a> {
+    fn from(oid: Oid<'a>) -> BerObject<'a> {
         BerObject::from_obj(BerObjectContent::OID(oid))
     }
 }
@@ -283,9 +628,55 @@ impl<'a> From<BerObjectContent<'a>> for BerObject<'a> {
 }
 
 impl<'a> BerObjectContent<'a> {
+    /// Convert the content to an owned value, copying all data borrowed from the input
+    pub fn into_owned(self) -> BerObjectContent<'static> {
+        fn owned(s: Cow<[u8]>) -> Cow<'static, [u8]> {
+            Cow::Owned(s.into_owned())
+        }
+        match self {
+            BerObjectContent::EndOfContent          => BerObjectContent::EndOfContent,
+            BerObjectContent::Boolean(b)            => BerObjectContent::Boolean(b),
+            BerObjectContent::Integer(s)            => BerObjectContent::Integer(owned(s)),
+            BerObjectContent::BitString(u,b)        => BerObjectContent::BitString(u, b.into_owned()),
+            BerObjectContent::OctetString(s)        => BerObjectContent::OctetString(owned(s)),
+            BerObjectContent::Null                  => BerObjectContent::Null,
+            BerObjectContent::Enum(i)               => BerObjectContent::Enum(i),
+            BerObjectContent::Real(f)               => BerObjectContent::Real(f),
+            BerObjectContent::OID(o)                => BerObjectContent::OID(o.into_owned()),
+            BerObjectContent::RelativeOID(o)        => BerObjectContent::RelativeOID(o.into_owned()),
+            BerObjectContent::NumericString(s)      => BerObjectContent::NumericString(owned(s)),
+            BerObjectContent::PrintableString(s)    => BerObjectContent::PrintableString(owned(s)),
+            BerObjectContent::IA5String(s)          => BerObjectContent::IA5String(owned(s)),
+            BerObjectContent::UTF8String(s)         => BerObjectContent::UTF8String(owned(s)),
+            BerObjectContent::T61String(s)          => BerObjectContent::T61String(owned(s)),
+            BerObjectContent::VideotexString(s)     => BerObjectContent::VideotexString(owned(s)),
+            BerObjectContent::GraphicString(s)      => BerObjectContent::GraphicString(owned(s)),
+            BerObjectContent::VisibleString(s)      => BerObjectContent::VisibleString(owned(s)),
+            BerObjectContent::UniversalString(s)    => BerObjectContent::UniversalString(owned(s)),
+            BerObjectContent::ObjectDescriptor(s)   => BerObjectContent::ObjectDescriptor(owned(s)),
+            BerObjectContent::CharacterString(s)    => BerObjectContent::CharacterString(s.into_owned()),
+            BerObjectContent::External(e)           => BerObjectContent::External(e.into_owned()),
+            BerObjectContent::EmbeddedPdv(p)        => BerObjectContent::EmbeddedPdv(p.into_owned()),
+            BerObjectContent::BmpString(s)          => BerObjectContent::BmpString(owned(s)),
+            BerObjectContent::Sequence(l)           => BerObjectContent::Sequence(l.into_iter().map(BerObject::into_owned).collect()),
+            BerObjectContent::Set(l)                => BerObjectContent::Set(l.into_iter().map(BerObject::into_owned).collect()),
+            BerObjectContent::UTCTime(s)            => BerObjectContent::UTCTime(owned(s)),
+            BerObjectContent::GeneralizedTime(s)    => BerObjectContent::GeneralizedTime(owned(s)),
+            BerObjectContent::Time(s)               => BerObjectContent::Time(owned(s)),
+            BerObjectContent::Date(s)               => BerObjectContent::Date(owned(s)),
+            BerObjectContent::TimeOfDay(s)          => BerObjectContent::TimeOfDay(owned(s)),
+            BerObjectContent::DateTime(s)           => BerObjectContent::DateTime(owned(s)),
+            BerObjectContent::Duration(s)           => BerObjectContent::Duration(owned(s)),
+            BerObjectContent::GeneralString(s)      => BerObjectContent::GeneralString(owned(s)),
+            BerObjectContent::ContextSpecific(t,o)  => BerObjectContent::ContextSpecific(t, o.map(|o| Box::new(o.into_owned()))),
+            BerObjectContent::Unknown(t,s)          => BerObjectContent::Unknown(t, owned(s)),
+        }
+    }
+
     pub fn as_u64(&self) -> Result<u64,BerError> {
         match *self {
-            BerObjectContent::Integer(i) => bytes_to_u64(i),
+            BerObjectContent::Integer(ref i) => bytes_to_unsigned(i),
+            BerObjectContent::Enum(i) if i < 0 => Err(BerError::IntegerNegative),
             BerObjectContent::Enum(i)    => Ok(i as u64),
             _ => Err(BerError::BerTypeError),
         }
@@ -293,8 +684,8 @@ impl<'a> BerObjectContent<'a> {
 
     pub fn as_u32(&self) -> Result<u32,BerError> {
         match *self {
-            BerObjectContent::Integer(i) => {
-                bytes_to_u64(i).and_then(|x| {
+            BerObjectContent::Integer(ref i) => {
+                bytes_to_unsigned(i).and_then(|x| {
                     if x > std::u32::MAX as u64 {
                         Err(BerError::IntegerTooLarge)
                     } else {
@@ -302,8 +693,9 @@ impl<'a> BerObjectContent<'a> {
                     }
                 })
             },
+            BerObjectContent::Enum(i) if i < 0 => Err(BerError::IntegerNegative),
             BerObjectContent::Enum(i)    => {
-                if i > std::u32::MAX as u64 {
+                if i > std::u32::MAX as i64 {
                     Err(BerError::IntegerTooLarge)
                 } else {
                     Ok(i as u32)
@@ -313,6 +705,118 @@ impl<'a> BerObjectContent<'a> {
         }
     }
 
+    pub fn is_minimal(&self) -> Result<bool,BerError> {
+        match *self {
+            BerObjectContent::Integer(ref i) => Ok(is_minimal_integer(i)),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_i128(&self) -> Result<i128,BerError> {
+        match *self {
+            BerObjectContent::Integer(ref i) => bytes_to_i128(i),
+            BerObjectContent::Enum(i)    => Ok(i as i128),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_i64(&self) -> Result<i64,BerError> {
+        self.as_i128().and_then(|x| {
+            if x < i64::MIN as i128 || x > i64::MAX as i128 {
+                Err(BerError::IntegerTooLarge)
+            } else {
+                Ok(x as i64)
+            }
+        })
+    }
+
+    pub fn as_i32(&self) -> Result<i32,BerError> {
+        self.as_i128().and_then(|x| {
+            if x < i32::MIN as i128 || x > i32::MAX as i128 {
+                Err(BerError::IntegerTooLarge)
+            } else {
+                Ok(x as i32)
+            }
+        })
+    }
+
+    pub fn as_f64(&self) -> Result<f64,BerError> {
+        match *self {
+            BerObjectContent::Real(f) => Ok(f),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_utctime(&self) -> Result<BerDateTime,BerError> {
+        match *self {
+            BerObjectContent::UTCTime(ref s) => BerDateTime::from_utctime(s, false),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_generalizedtime(&self) -> Result<BerDateTime,BerError> {
+        match *self {
+            BerObjectContent::GeneralizedTime(ref s) => BerDateTime::from_generalizedtime(s, false),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_time(&self) -> Result<BerTime,BerError> {
+        match *self {
+            BerObjectContent::Time(ref s) => BerTime::from_bytes(s),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_date(&self) -> Result<BerDate,BerError> {
+        match *self {
+            BerObjectContent::Date(ref s) => BerDate::from_bytes(s, false),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_timeofday(&self) -> Result<BerTimeOfDay,BerError> {
+        match *self {
+            BerObjectContent::TimeOfDay(ref s) => BerTimeOfDay::from_bytes(s, false),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_datetime(&self) -> Result<BerDateTime,BerError> {
+        match *self {
+            BerObjectContent::DateTime(ref s) => BerDateTime::from_datetime(s, false),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_duration(&self) -> Result<BerDuration,BerError> {
+        match *self {
+            BerObjectContent::Duration(ref s) => BerDuration::from_bytes(s),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_external(&self) -> Result<&BerExternal<'a>,BerError> {
+        match *self {
+            BerObjectContent::External(ref e) => Ok(e),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_embedded_pdv(&self) -> Result<&BerEmbeddedPdv<'a>,BerError> {
+        match *self {
+            BerObjectContent::EmbeddedPdv(ref p) => Ok(p),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_character_string(&self) -> Result<&BerCharacterString<'a>,BerError> {
+        match *self {
+            BerObjectContent::CharacterString(ref s) => Ok(s),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
     pub fn as_bool(&self) -> Result<bool,BerError> {
         match *self {
             BerObjectContent::Boolean(b) => Ok(b),
@@ -320,18 +824,23 @@ impl<'a> BerObjectContent<'a> {
         }
     }
 
-    pub fn as_oid(&self) -> Result<&Oid,BerError> {
+    pub fn as_oid(&self) -> Result<&Oid<'a>,BerError> {
         match *self {
             BerObjectContent::OID(ref o) => Ok(o),
-            BerObjectContent::RelativeOID(ref o) => Ok(o),
             _ => Err(BerError::BerTypeError),
         }
     }
 
-    pub fn as_oid_val(&self) -> Result<Oid,BerError> {
+    pub fn as_oid_val(&self) -> Result<Oid<'a>,BerError> {
         match *self {
             BerObjectContent::OID(ref o) => Ok(o.to_owned()),
-            BerObjectContent::RelativeOID(ref o) => Ok(o.to_owned()),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_relative_oid(&self) -> Result<&RelativeOid<'a>,BerError> {
+        match *self {
+            BerObjectContent::RelativeOID(ref o) => Ok(o),
             _ => Err(BerError::BerTypeError),
         }
     }
@@ -371,19 +880,46 @@ impl<'a> BerObjectContent<'a> {
         }
     }
 
-    pub fn as_slice(&self) -> Result<&'a [u8],BerError> {
+    pub fn as_slice(&self) -> Result<&[u8],BerError> {
         match *self {
-            BerObjectContent::Integer(s) |
-            BerObjectContent::BitString(_,BitStringObject{data:s}) |
-            BerObjectContent::OctetString(s) |
-            BerObjectContent::NumericString(s) |
-            BerObjectContent::PrintableString(s) |
-            BerObjectContent::IA5String(s) |
-            BerObjectContent::UTF8String(s) |
-            BerObjectContent::T61String(s) |
-            BerObjectContent::BmpString(s) |
-            BerObjectContent::GeneralString(s) |
-            BerObjectContent::Unknown(_,s) => Ok(s),
+            BerObjectContent::Integer(ref s) |
+            BerObjectContent::BitString(_,BitStringObject{data:ref s}) |
+            BerObjectContent::OctetString(ref s) |
+            BerObjectContent::NumericString(ref s) |
+            BerObjectContent::PrintableString(ref s) |
+            BerObjectContent::IA5String(ref s) |
+            BerObjectContent::UTF8String(ref s) |
+            BerObjectContent::T61String(ref s) |
+            BerObjectContent::VideotexString(ref s) |
+            BerObjectContent::GraphicString(ref s) |
+            BerObjectContent::VisibleString(ref s) |
+            BerObjectContent::UniversalString(ref s) |
+            BerObjectContent::ObjectDescriptor(ref s) |
+            BerObjectContent::BmpString(ref s) |
+            BerObjectContent::GeneralString(ref s) |
+            BerObjectContent::Unknown(_,ref s) => Ok(s),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_str(&self) -> Result<Cow<str>,BerError> { self.decode_string(false) }
+
+    pub fn to_string_lossy(&self) -> Result<Cow<str>,BerError> { self.decode_string(true) }
+
+    fn decode_string(&self, lossy: bool) -> Result<Cow<str>,BerError> {
+        match *self {
+            BerObjectContent::NumericString(ref s) |
+            BerObjectContent::PrintableString(ref s) |
+            BerObjectContent::IA5String(ref s) |
+            BerObjectContent::VisibleString(ref s) => decode_ascii(s, lossy),
+            BerObjectContent::UTF8String(ref s) |
+            BerObjectContent::GraphicString(ref s) |
+            BerObjectContent::ObjectDescriptor(ref s) |
+            BerObjectContent::GeneralString(ref s) => decode_utf8(s, lossy),
+            BerObjectContent::T61String(ref s) |
+            BerObjectContent::VideotexString(ref s) => decode_t61(s, lossy),
+            BerObjectContent::BmpString(ref s) => decode_bmp(s, lossy),
+            BerObjectContent::UniversalString(ref s) => decode_ucs4(s, lossy),
             _ => Err(BerError::BerTypeError),
         }
     }
@@ -397,6 +933,7 @@ impl<'a> BerObjectContent<'a> {
             BerObjectContent::OctetString(_)       => BerTag::OctetString,
             BerObjectContent::Null                 => BerTag::Null,
             BerObjectContent::Enum(_)              => BerTag::Enumerated,
+            BerObjectContent::Real(_)              => BerTag::RealType,
             BerObjectContent::OID(_)               => BerTag::Oid,
             BerObjectContent::NumericString(_)     => BerTag::NumericString,
             BerObjectContent::PrintableString(_)   => BerTag::PrintableString,
@@ -404,11 +941,24 @@ impl<'a> BerObjectContent<'a> {
             BerObjectContent::UTF8String(_)        => BerTag::Utf8String,
             BerObjectContent::RelativeOID(_)       => BerTag::RelativeOid,
             BerObjectContent::T61String(_)         => BerTag::T61String,
+            BerObjectContent::VideotexString(_)    => BerTag::VideotexString,
+            BerObjectContent::GraphicString(_)     => BerTag::GraphicString,
+            BerObjectContent::VisibleString(_)     => BerTag::VisibleString,
+            BerObjectContent::UniversalString(_)   => BerTag::UniversalString,
+            BerObjectContent::ObjectDescriptor(_)  => BerTag::ObjDescriptor,
+            BerObjectContent::CharacterString(_)   => BerTag::CharacterString,
+            BerObjectContent::External(_)          => BerTag::External,
+            BerObjectContent::EmbeddedPdv(_)       => BerTag::EmbeddedPdv,
             BerObjectContent::BmpString(_)         => BerTag::BmpString,
             BerObjectContent::Sequence(_)          => BerTag::Sequence,
             BerObjectContent::Set(_)               => BerTag::Set,
             BerObjectContent::UTCTime(_)           => BerTag::UtcTime,
             BerObjectContent::GeneralizedTime(_)   => BerTag::GeneralizedTime,
+            BerObjectContent::Time(_)              => BerTag::Time,
+            BerObjectContent::Date(_)              => BerTag::Date,
+            BerObjectContent::TimeOfDay(_)         => BerTag::TimeOfDay,
+            BerObjectContent::DateTime(_)          => BerTag::DateTime,
+            BerObjectContent::Duration(_)          => BerTag::Duration,
             BerObjectContent::GeneralString(_)     => BerTag::GeneralString,
             BerObjectContent::ContextSpecific(x,_) |
             BerObjectContent::Unknown(x,_)         => x,
@@ -419,19 +969,25 @@ impl<'a> BerObjectContent<'a> {
 #[cfg(feature="bigint")]
 mod bigint {
     use super::{BerObject,BerObjectContent};
-    use num_bigint::{Sign,BigInt,BigUint};
+    use num_bigint::{BigInt,BigUint};
 
     impl<'a> BerObject<'a> {
         pub fn as_bigint(&self) -> Option<BigInt> {
             match self.content {
-                BerObjectContent::Integer(s)         => Some(BigInt::from_bytes_be(Sign::Plus, s)),
+                BerObjectContent::Integer(ref s)     => Some(BigInt::from_signed_bytes_be(s)),
                 _ => None,
             }
         }
 
         pub fn as_biguint(&self) -> Option<BigUint> {
             match self.content {
-                BerObjectContent::Integer(s)         => Some(BigUint::from_bytes_be(s)),
+                BerObjectContent::Integer(ref s)     => {
+                    // negative values cannot be represented
+                    match s.first() {
+                        Some(&b) if b & 0x80 != 0 => None,
+                        _ => Some(BigUint::from_bytes_be(s)),
+                    }
+                },
                 _ => None,
             }
         }
@@ -528,7 +1084,7 @@ impl<'a> Index<usize> for BerObject<'a> {
 /// BitString wrapper
 #[derive(Clone, Debug,PartialEq)]
 pub struct BitStringObject<'a> {
-    pub data: &'a[u8],
+    pub data: Cow<'a, [u8]>,
 }
 
 impl<'a> BitStringObject<'a> {
@@ -541,10 +1097,15 @@ impl<'a> BitStringObject<'a> {
         let b = 7 - (bitnum % 8);
         (self.data[byte_pos] & (1 << b)) != 0
     }
+
+    /// Convert the bitstring to an owned value, copying the data if it is borrowed
+    pub fn into_owned(self) -> BitStringObject<'static> {
+        BitStringObject{ data: Cow::Owned(self.data.into_owned()) }
+    }
 }
 
 impl<'a> AsRef<[u8]> for BitStringObject<'a> {
-    fn as_ref(&self) -> &[u8] { self.data }
+    fn as_ref(&self) -> &[u8] { &self.data }
 }
 
 
@@ -552,7 +1113,9 @@ impl<'a> AsRef<[u8]> for BitStringObject<'a> {
 #[cfg(test)]
 mod tests {
     use ber::*;
+    use error::BerError;
     use oid::*;
+    use std::borrow::Cow;
 
 #[test]
 fn test_der_as_u64() {
@@ -560,6 +1123,30 @@ fn test_der_as_u64() {
     assert_eq!(der_obj.as_u64(), Ok(0x10002));
 }
 
+#[test]
+fn test_der_as_signed() {
+    assert_eq!(BerObject::from_int_slice(b"\xff").as_i32(), Ok(-1));
+    assert_eq!(BerObject::from_int_slice(b"\x00\xff").as_i32(), Ok(255));
+    assert_eq!(BerObject::from_int_slice(b"\x80").as_i64(), Ok(-128));
+    assert_eq!(BerObject::from_int_slice(b"\xff\x7f").as_i64(), Ok(-129));
+    assert_eq!(BerObject::from_int_slice(b"\x80\x00\x00\x00").as_i32(), Ok(i32::MIN));
+    assert_eq!(BerObject::from_int_slice(b"\xff\x7f\xff\xff\xff").as_i32(), Err(BerError::IntegerTooLarge));
+    assert_eq!(BerObject::from_int_slice(b"\xff\x7f\xff\xff\xff").as_i64(), Ok(-0x8000_0001));
+    // redundant sign extension octets are accepted
+    assert_eq!(BerObject::from_int_slice(b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff").as_i64(), Ok(-1));
+    assert_eq!(BerObject::from_i64(i64::MIN).as_i64(), Ok(i64::MIN));
+    assert_eq!(BerObject::from_u64(u64::MAX).as_i128(), Ok(u64::MAX as i128));
+    assert_eq!(BerObject::from_bool(true).as_i32(), Err(BerError::BerTypeError));
+}
+
+#[test]
+fn test_der_as_unsigned_negative() {
+    assert_eq!(BerObject::from_int_slice(b"\xff").as_u32(), Err(BerError::IntegerNegative));
+    assert_eq!(BerObject::from_int_slice(b"\xff").as_u64(), Err(BerError::IntegerNegative));
+    assert_eq!(BerObject::from_int_slice(b"\x00\xff").as_u32(), Ok(255));
+    assert_eq!(BerObject::from_u64(u64::MAX).as_u64(), Ok(u64::MAX));
+}
+
 #[test]
 fn test_der_seq_iter() {
     let der_obj  = BerObject::from_obj(BerObjectContent::Sequence(vec![
@@ -586,15 +1173,15 @@ fn test_der_seq_iter() {
 
 #[test]
 fn test_der_from_oid() {
-    let obj : BerObject = Oid::from(&[1,2]).into();
-    let expected = BerObject::from_obj(BerObjectContent::OID(Oid::from(&[1,2])));
+    let obj : BerObject = Oid::from(&[1,2]).unwrap().into();
+    let expected = BerObject::from_obj(BerObjectContent::OID(Oid::from(&[1,2]).unwrap()));
 
     assert_eq!(obj, expected);
 }
 
 #[test]
 fn test_der_bistringobject() {
-    let obj = BitStringObject{ data: &[0x0f, 0x00, 0x40] };
+    let obj = BitStringObject{ data: Cow::Borrowed(&[0x0f, 0x00, 0x40]) };
     assert!(!obj.is_set(0));
     assert!(obj.is_set(7));
     assert!(!obj.is_set(9));
@@ -607,26 +1194,30 @@ fn test_der_bistringobject_asref() {
        assert_eq!(s.as_ref(), b);
     }
     let b: &[u8] = &[0x0f, 0x00, 0x40];
-    let obj = BitStringObject{data:b};
+    let obj = BitStringObject{data:Cow::Borrowed(b)};
     assert_equal(obj, b);
 }
 
 #[cfg(feature="bigint")]
 #[test]
 fn test_der_to_bigint() {
-    let obj  = BerObject::from_obj(BerObjectContent::Integer(b"\x01\x00\x01"));
+    let obj  = BerObject::from_int_slice(b"\x01\x00\x01");
     let expected = ::num_bigint::BigInt::from(0x10001);
 
     assert_eq!(obj.as_bigint(), Some(expected));
+    let obj  = BerObject::from_int_slice(b"\xff\x7f");
+    assert_eq!(obj.as_bigint(), Some(::num_bigint::BigInt::from(-129)));
 }
 
 #[cfg(feature="bigint")]
 #[test]
 fn test_der_to_biguint() {
-    let obj  = BerObject::from_obj(BerObjectContent::Integer(b"\x01\x00\x01"));
+    let obj  = BerObject::from_int_slice(b"\x01\x00\x01");
     let expected = ::num_bigint::BigUint::from(0x10001 as u32);
 
     assert_eq!(obj.as_biguint(), Some(expected));
+    let obj  = BerObject::from_int_slice(b"\xff");
+    assert_eq!(obj.as_biguint(), None);
 }
 
 }
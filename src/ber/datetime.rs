@@ -0,0 +1,382 @@
+//! Decoding of UTCTime and GeneralizedTime values (X.680 sections 46 and 47)
+
+use crate::error::BerError;
+use std::fmt;
+
+/// Time zone of a decoded time value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BerTimeZone {
+    /// No time zone: the value is a local time
+    Undefined,
+    /// Coordinated universal time (`Z` suffix)
+    Z,
+    /// Offset from UTC, in minutes (`+hhmm` or `-hhmm` suffix)
+    Offset(i16),
+}
+
+/// A decoded UTCTime or GeneralizedTime value
+///
+/// Fractions of hours or minutes (allowed in BER GeneralizedTime) are converted to minutes,
+/// seconds and nanoseconds. Digits beyond the nanosecond are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BerDateTime {
+    pub year: u32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+    pub tz: BerTimeZone,
+}
+
+impl BerDateTime {
+    /// Decode the content of a UTCTime object
+    ///
+    /// The format is `YYMMDDhhmm[ss](Z|+hhmm|-hhmm)`. Two-digit years are mapped to 1950-2049,
+    /// as in RFC 5280.
+    ///
+    /// If `der` is true, the DER rules are checked (X.690 section 11.8): seconds are present and
+    /// the time zone is `Z`.
+    pub fn from_utctime(s: &[u8], der: bool) -> Result<BerDateTime, BerError> {
+        let mut r = TimeReader(s);
+        let yy = r.digits(2)?;
+        let year = if yy >= 50 { 1900 + yy } else { 2000 + yy };
+        let month = r.digits(2)? as u8;
+        let day = r.digits(2)? as u8;
+        let hour = r.digits(2)? as u8;
+        let minute = r.digits(2)? as u8;
+        let second = if r.peek_digit() {
+            r.digits(2)? as u8
+        } else {
+            if der {
+                return Err(BerError::DerConstraintFailed);
+            }
+            0
+        };
+        let tz = r.timezone()?;
+        match tz {
+            BerTimeZone::Z => (),
+            BerTimeZone::Undefined => return Err(BerError::InvalidValue),
+            BerTimeZone::Offset(_) if der => return Err(BerError::DerConstraintFailed),
+            BerTimeZone::Offset(_) => (),
+        }
+        r.end()?;
+        let dt = BerDateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanosecond: 0,
+            tz,
+        };
+        dt.check()?;
+        Ok(dt)
+    }
+
+    /// Decode the content of a GeneralizedTime object
+    ///
+    /// The format is `YYYYMMDDhh[mm[ss]][(.|,)f+][Z|+hhmm|-hhmm]`, the fraction applying to the
+    /// last element present.
+    ///
+    /// If `der` is true, the DER rules are checked (X.690 section 11.7): minutes and seconds are
+    /// present, the time zone is `Z`, and the fraction (if any) uses a `.` and has no trailing
+    /// zeros.
+    pub fn from_generalizedtime(s: &[u8], der: bool) -> Result<BerDateTime, BerError> {
+        let mut r = TimeReader(s);
+        let year = r.digits(4)?;
+        let month = r.digits(2)? as u8;
+        let day = r.digits(2)? as u8;
+        let hour = r.digits(2)? as u8;
+        let mut minute = 0;
+        let mut second = 0;
+        // unit of the fraction, in nanoseconds
+        let mut unit = 3_600_000_000_000;
+        if r.peek_digit() {
+            minute = r.digits(2)? as u8;
+            unit = 60_000_000_000;
+            if r.peek_digit() {
+                second = r.digits(2)? as u8;
+                unit = 1_000_000_000;
+            }
+        }
+        if der && unit != 1_000_000_000 {
+            return Err(BerError::DerConstraintFailed);
+        }
+        let mut extra: u64 = 0;
+        if let Some(sep) = r.peek() {
+            if sep == b'.' || sep == b',' {
+                r.0 = &r.0[1..];
+                let fraction = r.fraction()?;
+                if der && (sep != b'.' || fraction.last() == Some(&b'0')) {
+                    return Err(BerError::DerConstraintFailed);
+                }
+                extra = fraction_to_nanos(fraction, unit);
+            }
+        }
+        let tz = r.timezone()?;
+        if der && tz != BerTimeZone::Z {
+            return Err(BerError::DerConstraintFailed);
+        }
+        r.end()?;
+        let extra_seconds = extra / 1_000_000_000;
+        let dt = BerDateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute: minute + (extra_seconds / 60) as u8,
+            second: second + (extra_seconds % 60) as u8,
+            nanosecond: (extra % 1_000_000_000) as u32,
+            tz,
+        };
+        dt.check()?;
+        Ok(dt)
+    }
+
+    /// Check the ranges of the date and time elements
+    pub(crate) fn check(&self) -> Result<(), BerError> {
+        let leap = (self.year % 4 == 0 && self.year % 100 != 0) || self.year % 400 == 0;
+        let days = match self.month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return Err(BerError::InvalidValue),
+        };
+        if self.day == 0 || self.day > days || self.hour > 23 || self.minute > 59 || self.second > 59 {
+            return Err(BerError::InvalidValue);
+        }
+        Ok(())
+    }
+
+    /// Offset from UTC, in seconds (a local time is interpreted as UTC)
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    fn offset_seconds(&self) -> i32 {
+        match self.tz {
+            BerTimeZone::Offset(m) => i32::from(m) * 60,
+            _ => 0,
+        }
+    }
+
+    /// Convert to a `chrono::DateTime`
+    ///
+    /// A local time (no time zone) is interpreted as UTC.
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(&self) -> Result<::chrono::DateTime<::chrono::FixedOffset>, BerError> {
+        use chrono::{FixedOffset, NaiveDate, TimeZone};
+        let offset = FixedOffset::east_opt(self.offset_seconds()).ok_or(BerError::InvalidValue)?;
+        let naive = NaiveDate::from_ymd_opt(self.year as i32, u32::from(self.month), u32::from(self.day))
+            .and_then(|d| {
+                d.and_hms_nano_opt(
+                    u32::from(self.hour),
+                    u32::from(self.minute),
+                    u32::from(self.second),
+                    self.nanosecond,
+                )
+            })
+            .ok_or(BerError::InvalidValue)?;
+        offset
+            .from_local_datetime(&naive)
+            .single()
+            .ok_or(BerError::InvalidValue)
+    }
+
+    /// Convert to a `time::OffsetDateTime`
+    ///
+    /// A local time (no time zone) is interpreted as UTC.
+    #[cfg(feature = "time")]
+    pub fn to_time(&self) -> Result<::time::OffsetDateTime, BerError> {
+        use std::convert::TryFrom;
+        use time::{Date, Month, PrimitiveDateTime, Time, UtcOffset};
+        let month = Month::try_from(self.month).map_err(|_| BerError::InvalidValue)?;
+        let date = Date::from_calendar_date(self.year as i32, month, self.day)
+            .map_err(|_| BerError::InvalidValue)?;
+        let time = Time::from_hms_nano(self.hour, self.minute, self.second, self.nanosecond)
+            .map_err(|_| BerError::InvalidValue)?;
+        let offset =
+            UtcOffset::from_whole_seconds(self.offset_seconds()).map_err(|_| BerError::InvalidValue)?;
+        Ok(PrimitiveDateTime::new(date, time).assume_offset(offset))
+    }
+}
+
+impl fmt::Display for BerDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+        if self.nanosecond != 0 {
+            write!(f, ".{:09}", self.nanosecond)?;
+        }
+        match self.tz {
+            BerTimeZone::Undefined => Ok(()),
+            tz => write!(f, " {}", tz),
+        }
+    }
+}
+
+impl fmt::Display for BerTimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BerTimeZone::Undefined => Ok(()),
+            BerTimeZone::Z => f.write_str("UTC"),
+            BerTimeZone::Offset(m) => {
+                let sign = if m < 0 { '-' } else { '+' };
+                write!(f, "{}{:02}{:02}", sign, m.abs() / 60, m.abs() % 60)
+            }
+        }
+    }
+}
+
+/// Convert the decimal fraction `digits` of `unit` nanoseconds to nanoseconds
+pub(crate) fn fraction_to_nanos(digits: &[u8], unit: u64) -> u64 {
+    // 18 digits are enough for the precision of a nanosecond
+    let mut num: u128 = 0;
+    let mut den: u128 = 1;
+    for &d in digits.iter().take(18) {
+        num = num * 10 + u128::from(d - b'0');
+        den *= 10;
+    }
+    (num * u128::from(unit) / den) as u64
+}
+
+/// Reader for the digits and separators of a time value
+pub(crate) struct TimeReader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> TimeReader<'a> {
+    pub(crate) fn peek(&self) -> Option<u8> {
+        self.0.first().cloned()
+    }
+
+    pub(crate) fn peek_digit(&self) -> bool {
+        self.peek().is_some_and(|c| c.is_ascii_digit())
+    }
+
+    pub(crate) fn digits(&mut self, n: usize) -> Result<u32, BerError> {
+        if self.0.len() < n || !self.0[..n].iter().all(u8::is_ascii_digit) {
+            return Err(BerError::InvalidValue);
+        }
+        let v = self.0[..n]
+            .iter()
+            .fold(0, |acc, &c| acc * 10 + u32::from(c - b'0'));
+        self.0 = &self.0[n..];
+        Ok(v)
+    }
+
+    pub(crate) fn fraction(&mut self) -> Result<&'a [u8], BerError> {
+        let n = self.0.iter().take_while(|c| c.is_ascii_digit()).count();
+        if n == 0 {
+            return Err(BerError::InvalidValue);
+        }
+        let (f, rem) = self.0.split_at(n);
+        self.0 = rem;
+        Ok(f)
+    }
+
+    pub(crate) fn timezone(&mut self) -> Result<BerTimeZone, BerError> {
+        match self.peek() {
+            None => Ok(BerTimeZone::Undefined),
+            Some(b'Z') => {
+                self.0 = &self.0[1..];
+                Ok(BerTimeZone::Z)
+            }
+            Some(sign @ b'+') | Some(sign @ b'-') => {
+                self.0 = &self.0[1..];
+                let hh = self.digits(2)?;
+                let mm = self.digits(2)?;
+                if hh > 23 || mm > 59 {
+                    return Err(BerError::InvalidValue);
+                }
+                let m = (hh * 60 + mm) as i16;
+                Ok(BerTimeZone::Offset(if sign == b'-' { -m } else { m }))
+            }
+            Some(_) => Err(BerError::InvalidValue),
+        }
+    }
+
+    /// Consume the next character if it is `c`
+    pub(crate) fn skip(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.0 = &self.0[1..];
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn end(&self) -> Result<(), BerError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(BerError::InvalidValue)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+#[test]
+fn test_utctime() {
+    let t = BerDateTime::from_utctime(b"190102030405Z", true).expect("decoding failed");
+    assert_eq!((t.year, t.month, t.day), (2019, 1, 2));
+    assert_eq!((t.hour, t.minute, t.second), (3, 4, 5));
+    assert_eq!(t.tz, BerTimeZone::Z);
+    assert_eq!(t.to_string(), "2019-01-02 03:04:05 UTC");
+    let t = BerDateTime::from_utctime(b"9912312359-0130", false).expect("decoding failed");
+    assert_eq!(t.year, 1999);
+    assert_eq!(t.second, 0);
+    assert_eq!(t.tz, BerTimeZone::Offset(-90));
+    assert_eq!(BerDateTime::from_utctime(b"9912312359-0130", true), Err(BerError::DerConstraintFailed));
+    assert_eq!(BerDateTime::from_utctime(b"9912312359Z", true), Err(BerError::DerConstraintFailed));
+    assert_eq!(BerDateTime::from_utctime(b"991231235959", false), Err(BerError::InvalidValue));
+    assert_eq!(BerDateTime::from_utctime(b"190229000000Z", false), Err(BerError::InvalidValue));
+    assert_eq!(BerDateTime::from_utctime(b"190102030405ZZ", false), Err(BerError::InvalidValue));
+}
+
+#[test]
+fn test_generalizedtime() {
+    let t = BerDateTime::from_generalizedtime(b"20000229235959.25Z", true).expect("decoding failed");
+    assert_eq!((t.year, t.month, t.day), (2000, 2, 29));
+    assert_eq!((t.hour, t.minute, t.second), (23, 59, 59));
+    assert_eq!(t.nanosecond, 250_000_000);
+    // BER: local time, fraction of hour
+    let t = BerDateTime::from_generalizedtime(b"2019010203,5", false).expect("decoding failed");
+    assert_eq!((t.hour, t.minute, t.second), (3, 30, 0));
+    assert_eq!(t.tz, BerTimeZone::Undefined);
+    let t = BerDateTime::from_generalizedtime(b"201901020304.5+0200", false).expect("decoding failed");
+    assert_eq!((t.minute, t.second), (4, 30));
+    assert_eq!(t.tz, BerTimeZone::Offset(120));
+    // DER constraints
+    assert_eq!(BerDateTime::from_generalizedtime(b"2019010203Z", true), Err(BerError::DerConstraintFailed));
+    assert_eq!(BerDateTime::from_generalizedtime(b"20190102030405,5Z", true), Err(BerError::DerConstraintFailed));
+    assert_eq!(BerDateTime::from_generalizedtime(b"20190102030405.50Z", true), Err(BerError::DerConstraintFailed));
+    assert_eq!(BerDateTime::from_generalizedtime(b"20190102030405", true), Err(BerError::DerConstraintFailed));
+    assert_eq!(BerDateTime::from_generalizedtime(b"20190102030405.Z", false), Err(BerError::InvalidValue));
+    assert_eq!(BerDateTime::from_generalizedtime(b"20191302030405Z", false), Err(BerError::InvalidValue));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_to_chrono() {
+    let t = BerDateTime::from_generalizedtime(b"20190102030405.5+0100", false).expect("decoding failed");
+    let dt = t.to_chrono().expect("conversion failed");
+    assert_eq!(dt.timestamp(), 1_546_394_645);
+    assert_eq!(dt.timestamp_subsec_nanos(), 500_000_000);
+    assert_eq!(dt.offset().local_minus_utc(), 3600);
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_to_time() {
+    let t = BerDateTime::from_utctime(b"190102030405Z", true).expect("decoding failed");
+    let dt = t.to_time().expect("conversion failed");
+    assert_eq!(dt.unix_timestamp(), 1_546_398_245);
+}
+
+}
@@ -0,0 +1,486 @@
+//! EXTERNAL, EMBEDDED PDV and CHARACTER STRING types (X.680 sections 36, 37 and 44)
+//!
+//! These types are encoded as their associated SEQUENCE types (X.690 sections 8.18 to 8.20).
+
+use crate::ber::parser::ber_read_content_sequence;
+use crate::ber::{
+    ber_read_element_content_as, parse_ber_recursive, BerObject, BerObjectContent, BerSize, BerTag,
+    BitStringObject,
+};
+use crate::error::{BerError, BerResult, ParseError};
+use crate::oid::Oid;
+use nom::Err;
+use std::borrow::Cow;
+
+/// Encoding of the data value of an EXTERNAL
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExternalEncoding<'a> {
+    /// `single-ASN1-type [0] ABSTRACT-SYNTAX.&Type`
+    SingleAsn1Type(Box<BerObject<'a>>),
+    /// `octet-aligned [1] IMPLICIT OCTET STRING`
+    OctetAligned(Cow<'a, [u8]>),
+    /// `arbitrary [2] IMPLICIT BIT STRING`, with the number of unused bits
+    Arbitrary(u8, BitStringObject<'a>),
+}
+
+/// A decoded EXTERNAL value (X.690 section 8.18)
+#[derive(Debug, Clone, PartialEq)]
+pub struct BerExternal<'a> {
+    pub direct_reference: Option<Oid<'a>>,
+    pub indirect_reference: Option<i64>,
+    /// Content of the ObjectDescriptor
+    pub data_value_descriptor: Option<Cow<'a, [u8]>>,
+    pub encoding: ExternalEncoding<'a>,
+}
+
+/// The `identification` of an EMBEDDED PDV or CHARACTER STRING value
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdvIdentification<'a> {
+    Syntaxes {
+        abstract_syntax: Oid<'a>,
+        transfer_syntax: Oid<'a>,
+    },
+    Syntax(Oid<'a>),
+    PresentationContextId(i64),
+    ContextNegotiation {
+        presentation_context_id: i64,
+        transfer_syntax: Oid<'a>,
+    },
+    TransferSyntax(Oid<'a>),
+    Fixed,
+}
+
+/// A decoded EMBEDDED PDV value (X.690 section 8.19)
+#[derive(Debug, Clone, PartialEq)]
+pub struct BerEmbeddedPdv<'a> {
+    pub identification: PdvIdentification<'a>,
+    /// Content of the ObjectDescriptor (absent in encodings following X.680)
+    pub data_value_descriptor: Option<Cow<'a, [u8]>>,
+    pub data_value: Cow<'a, [u8]>,
+}
+
+/// A decoded (unrestricted) CHARACTER STRING value (X.690 section 8.20)
+#[derive(Debug, Clone, PartialEq)]
+pub struct BerCharacterString<'a> {
+    pub identification: PdvIdentification<'a>,
+    /// Content of the ObjectDescriptor (absent in encodings following X.680)
+    pub data_value_descriptor: Option<Cow<'a, [u8]>>,
+    pub string_value: Cow<'a, [u8]>,
+}
+
+fn owned(s: Cow<[u8]>) -> Cow<'static, [u8]> {
+    Cow::Owned(s.into_owned())
+}
+
+impl<'a> BerExternal<'a> {
+    /// Convert to an owned value, copying all data borrowed from the input
+    pub fn into_owned(self) -> BerExternal<'static> {
+        BerExternal {
+            direct_reference: self.direct_reference.map(Oid::into_owned),
+            indirect_reference: self.indirect_reference,
+            data_value_descriptor: self.data_value_descriptor.map(owned),
+            encoding: match self.encoding {
+                ExternalEncoding::SingleAsn1Type(o) => {
+                    ExternalEncoding::SingleAsn1Type(Box::new(o.into_owned()))
+                }
+                ExternalEncoding::OctetAligned(s) => ExternalEncoding::OctetAligned(owned(s)),
+                ExternalEncoding::Arbitrary(u, b) => ExternalEncoding::Arbitrary(u, b.into_owned()),
+            },
+        }
+    }
+}
+
+impl<'a> PdvIdentification<'a> {
+    /// Convert to an owned value, copying all data borrowed from the input
+    pub fn into_owned(self) -> PdvIdentification<'static> {
+        match self {
+            PdvIdentification::Syntaxes {
+                abstract_syntax,
+                transfer_syntax,
+            } => PdvIdentification::Syntaxes {
+                abstract_syntax: abstract_syntax.into_owned(),
+                transfer_syntax: transfer_syntax.into_owned(),
+            },
+            PdvIdentification::Syntax(o) => PdvIdentification::Syntax(o.into_owned()),
+            PdvIdentification::PresentationContextId(i) => {
+                PdvIdentification::PresentationContextId(i)
+            }
+            PdvIdentification::ContextNegotiation {
+                presentation_context_id,
+                transfer_syntax,
+            } => PdvIdentification::ContextNegotiation {
+                presentation_context_id,
+                transfer_syntax: transfer_syntax.into_owned(),
+            },
+            PdvIdentification::TransferSyntax(o) => {
+                PdvIdentification::TransferSyntax(o.into_owned())
+            }
+            PdvIdentification::Fixed => PdvIdentification::Fixed,
+        }
+    }
+}
+
+impl<'a> BerEmbeddedPdv<'a> {
+    /// Convert to an owned value, copying all data borrowed from the input
+    pub fn into_owned(self) -> BerEmbeddedPdv<'static> {
+        BerEmbeddedPdv {
+            identification: self.identification.into_owned(),
+            data_value_descriptor: self.data_value_descriptor.map(owned),
+            data_value: owned(self.data_value),
+        }
+    }
+}
+
+impl<'a> BerCharacterString<'a> {
+    /// Convert to an owned value, copying all data borrowed from the input
+    pub fn into_owned(self) -> BerCharacterString<'static> {
+        BerCharacterString {
+            identification: self.identification.into_owned(),
+            data_value_descriptor: self.data_value_descriptor.map(owned),
+            string_value: owned(self.string_value),
+        }
+    }
+}
+
+/// Result of the decoding of a component: errors point into the input
+type ComponentResult<'a, T> = Result<T, Err<&'a [u8], ParseError>>;
+
+fn component_error<T>(i: &[u8], e: BerError) -> ComponentResult<T> {
+    Err(Err::Error(error_position!(i, e.into_error_kind())))
+}
+
+/// Read the components of a constructed object
+fn read_components(i: &[u8], len: BerSize, depth: usize) -> BerResult<Vec<BerObject>> {
+    match ber_read_content_sequence(i, len, depth)? {
+        (rem, BerObjectContent::Sequence(l)) => Ok((rem, l)),
+        _ => unreachable!(),
+    }
+}
+
+/// Return the raw content of a context-specific component
+fn context_content<'a>(obj: &BerObject<'a>) -> Option<&'a [u8]> {
+    match obj.content {
+        BerObjectContent::Unknown(_, Cow::Borrowed(s)) if obj.class == 0b10 => Some(s),
+        _ => None,
+    }
+}
+
+/// Decode the content of an IMPLICIT tagged component, as type `tag`
+fn read_implicit<'a>(
+    i: &'a [u8],
+    obj: &BerObject<'a>,
+    tag: BerTag,
+    depth: usize,
+) -> ComponentResult<'a, BerObjectContent<'a>> {
+    let s = match context_content(obj) {
+        Some(s) => s,
+        None => return component_error(i, BerError::InvalidValue),
+    };
+    let (_, content) = ber_read_element_content_as(
+        s,
+        tag,
+        BerSize::Definite(s.len()),
+        obj.is_constructed(),
+        depth,
+    )?;
+    Ok(content)
+}
+
+fn read_implicit_oid<'a>(
+    i: &'a [u8],
+    obj: &BerObject<'a>,
+    depth: usize,
+) -> ComponentResult<'a, Oid<'a>> {
+    match read_implicit(i, obj, BerTag::Oid, depth)? {
+        BerObjectContent::OID(oid) => Ok(oid),
+        _ => component_error(i, BerError::InvalidValue),
+    }
+}
+
+fn read_implicit_integer<'a>(
+    i: &'a [u8],
+    obj: &BerObject<'a>,
+    depth: usize,
+) -> ComponentResult<'a, i64> {
+    read_implicit(i, obj, BerTag::Integer, depth)?
+        .as_i64()
+        .or_else(|e| component_error(i, e))
+}
+
+/// Decode the identification CHOICE (`[0] EXPLICIT`)
+fn read_identification<'a>(
+    i: &'a [u8],
+    obj: &BerObject<'a>,
+    depth: usize,
+) -> ComponentResult<'a, PdvIdentification<'a>> {
+    let s = match context_content(obj) {
+        Some(s) if obj.tag == BerTag(0) && obj.is_constructed() => s,
+        _ => return component_error(i, BerError::InvalidValue),
+    };
+    let (rem, alt) = parse_ber_recursive(s, depth + 1)?;
+    if !rem.is_empty() || alt.class != 0b10 {
+        return component_error(s, BerError::InvalidValue);
+    }
+    // SEQUENCE of two IMPLICIT tagged components
+    let pair = |i: &'a [u8]| -> ComponentResult<'a, (BerObject<'a>, BerObject<'a>)> {
+        let s = match context_content(&alt) {
+            Some(s) if alt.is_constructed() => s,
+            _ => return component_error(i, BerError::InvalidValue),
+        };
+        let (_, mut l) = read_components(s, BerSize::Definite(s.len()), depth + 1)?;
+        if l.len() != 2 || l[0].tag != BerTag(0) || l[1].tag != BerTag(1) {
+            return component_error(s, BerError::InvalidValue);
+        }
+        let second = l.pop().unwrap();
+        let first = l.pop().unwrap();
+        Ok((first, second))
+    };
+    let id = match alt.tag.0 {
+        0 => {
+            let (a, t) = pair(s)?;
+            PdvIdentification::Syntaxes {
+                abstract_syntax: read_implicit_oid(s, &a, depth)?,
+                transfer_syntax: read_implicit_oid(s, &t, depth)?,
+            }
+        }
+        1 => PdvIdentification::Syntax(read_implicit_oid(s, &alt, depth)?),
+        2 => PdvIdentification::PresentationContextId(read_implicit_integer(s, &alt, depth)?),
+        3 => {
+            let (p, t) = pair(s)?;
+            PdvIdentification::ContextNegotiation {
+                presentation_context_id: read_implicit_integer(s, &p, depth)?,
+                transfer_syntax: read_implicit_oid(s, &t, depth)?,
+            }
+        }
+        4 => PdvIdentification::TransferSyntax(read_implicit_oid(s, &alt, depth)?),
+        5 => match read_implicit(s, &alt, BerTag::Null, depth)? {
+            BerObjectContent::Null => PdvIdentification::Fixed,
+            _ => return component_error(s, BerError::InvalidValue),
+        },
+        _ => return component_error(s, BerError::InvalidValue),
+    };
+    Ok(id)
+}
+
+/// Read the content of an EXTERNAL object
+///
+/// ```text
+/// EXTERNAL ::= [UNIVERSAL 8] IMPLICIT SEQUENCE {
+///     direct-reference      OBJECT IDENTIFIER OPTIONAL,
+///     indirect-reference    INTEGER OPTIONAL,
+///     data-value-descriptor ObjectDescriptor OPTIONAL,
+///     encoding CHOICE {
+///         single-ASN1-type [0] ABSTRACT-SYNTAX.&Type,
+///         octet-aligned    [1] IMPLICIT OCTET STRING,
+///         arbitrary        [2] IMPLICIT BIT STRING } }
+/// ```
+pub(crate) fn ber_read_content_external(
+    i: &[u8],
+    len: BerSize,
+    depth: usize,
+) -> BerResult<BerObjectContent> {
+    let (rem, l) = read_components(i, len, depth)?;
+    let mut it = l.into_iter().peekable();
+    let mut next_if = |tag: BerTag| match it.peek() {
+        Some(o) if o.class == 0 && o.tag == tag => it.next(),
+        _ => None,
+    };
+    let direct_reference = match next_if(BerTag::Oid) {
+        Some(o) => Some(o.as_oid_val().or_else(|e| component_error(i, e))?),
+        None => None,
+    };
+    let indirect_reference = match next_if(BerTag::Integer) {
+        Some(o) => Some(o.as_i64().or_else(|e| component_error(i, e))?),
+        None => None,
+    };
+    let data_value_descriptor = match next_if(BerTag::ObjDescriptor).map(|o| o.content) {
+        Some(BerObjectContent::ObjectDescriptor(s)) => Some(s),
+        _ => None,
+    };
+    let encoding = match (it.next(), it.next()) {
+        (Some(o), None) if o.class == 0b10 => match o.tag.0 {
+            0 => {
+                let s = match context_content(&o) {
+                    Some(s) if o.is_constructed() => s,
+                    _ => return component_error(i, BerError::InvalidValue),
+                };
+                let (r, inner) = parse_ber_recursive(s, depth + 1)?;
+                if !r.is_empty() {
+                    return component_error(r, BerError::InvalidValue);
+                }
+                ExternalEncoding::SingleAsn1Type(Box::new(inner))
+            }
+            1 => match read_implicit(i, &o, BerTag::OctetString, depth)? {
+                BerObjectContent::OctetString(s) => ExternalEncoding::OctetAligned(s),
+                _ => return component_error(i, BerError::InvalidValue),
+            },
+            2 => match read_implicit(i, &o, BerTag::BitString, depth)? {
+                BerObjectContent::BitString(u, b) => ExternalEncoding::Arbitrary(u, b),
+                _ => return component_error(i, BerError::InvalidValue),
+            },
+            _ => return component_error(i, BerError::InvalidValue),
+        },
+        _ => return component_error(i, BerError::InvalidValue),
+    };
+    let ext = BerExternal {
+        direct_reference,
+        indirect_reference,
+        data_value_descriptor,
+        encoding,
+    };
+    Ok((rem, BerObjectContent::External(ext)))
+}
+
+/// Read the components of an EMBEDDED PDV or CHARACTER STRING object
+///
+/// Both types have the same associated SEQUENCE type, so the result is always returned as a
+/// `BerEmbeddedPdv`.
+///
+/// ```text
+/// SEQUENCE {
+///     identification        [0] CHOICE { ... },
+///     data-value-descriptor [1] ObjectDescriptor OPTIONAL,
+///     data-value            [2] OCTET STRING }
+/// ```
+fn read_pdv(i: &[u8], len: BerSize, depth: usize) -> BerResult<BerEmbeddedPdv> {
+    let (rem, l) = read_components(i, len, depth)?;
+    let (id, descriptor, value) = match l.len() {
+        2 => (&l[0], None, &l[1]),
+        3 => (&l[0], Some(&l[1]), &l[2]),
+        _ => return component_error(i, BerError::InvalidValue),
+    };
+    let identification = read_identification(i, id, depth)?;
+    let data_value_descriptor = match descriptor {
+        Some(o) if o.tag == BerTag(1) => match read_implicit(i, o, BerTag::ObjDescriptor, depth)? {
+            BerObjectContent::ObjectDescriptor(s) => Some(s),
+            _ => return component_error(i, BerError::InvalidValue),
+        },
+        Some(_) => return component_error(i, BerError::InvalidValue),
+        None => None,
+    };
+    if value.tag != BerTag(2) {
+        return component_error(i, BerError::InvalidValue);
+    }
+    let data_value = match read_implicit(i, value, BerTag::OctetString, depth)? {
+        BerObjectContent::OctetString(s) => s,
+        _ => return component_error(i, BerError::InvalidValue),
+    };
+    let pdv = BerEmbeddedPdv {
+        identification,
+        data_value_descriptor,
+        data_value,
+    };
+    Ok((rem, pdv))
+}
+
+/// Read the content of an EMBEDDED PDV object
+pub(crate) fn ber_read_content_embeddedpdv(
+    i: &[u8],
+    len: BerSize,
+    depth: usize,
+) -> BerResult<BerObjectContent> {
+    let (rem, pdv) = read_pdv(i, len, depth)?;
+    Ok((rem, BerObjectContent::EmbeddedPdv(pdv)))
+}
+
+/// Read the content of an unrestricted CHARACTER STRING object
+pub(crate) fn ber_read_content_characterstring(
+    i: &[u8],
+    len: BerSize,
+    depth: usize,
+) -> BerResult<BerObjectContent> {
+    let (rem, pdv) = read_pdv(i, len, depth)?;
+    let s = BerCharacterString {
+        identification: pdv.identification,
+        data_value_descriptor: pdv.data_value_descriptor,
+        string_value: pdv.data_value,
+    };
+    Ok((rem, BerObjectContent::CharacterString(s)))
+}
+
+/// Build the objects encoding the identification CHOICE
+fn identification_to_ber<'a>(id: &PdvIdentification<'a>) -> BerObject<'a> {
+    fn oid<'a>(tag: u32, o: &Oid<'a>) -> BerObject<'a> {
+        BerObject::from_implicit(
+            BerTag(tag),
+            BerObject::from_obj(BerObjectContent::OID(o.clone())),
+        )
+    }
+    fn int<'a>(tag: u32, i: i64) -> BerObject<'a> {
+        BerObject::from_implicit(BerTag(tag), BerObject::from_i64(i))
+    }
+    let alt = match *id {
+        PdvIdentification::Syntaxes {
+            ref abstract_syntax,
+            ref transfer_syntax,
+        } => BerObject::from_implicit(
+            BerTag(0),
+            BerObject::from_seq(vec![oid(0, abstract_syntax), oid(1, transfer_syntax)]),
+        ),
+        PdvIdentification::Syntax(ref o) => oid(1, o),
+        PdvIdentification::PresentationContextId(i) => int(2, i),
+        PdvIdentification::ContextNegotiation {
+            presentation_context_id,
+            ref transfer_syntax,
+        } => BerObject::from_implicit(
+            BerTag(3),
+            BerObject::from_seq(vec![
+                int(0, presentation_context_id),
+                oid(1, transfer_syntax),
+            ]),
+        ),
+        PdvIdentification::TransferSyntax(ref o) => oid(4, o),
+        PdvIdentification::Fixed => {
+            BerObject::from_implicit(BerTag(5), BerObject::from_obj(BerObjectContent::Null))
+        }
+    };
+    BerObject::from_explicit(BerTag(0), alt)
+}
+
+/// Build the components of the associated SEQUENCE type of an EXTERNAL
+pub(crate) fn external_components<'a>(ext: &BerExternal<'a>) -> Vec<BerObject<'a>> {
+    let mut v = Vec::new();
+    if let Some(ref o) = ext.direct_reference {
+        v.push(BerObject::from_obj(BerObjectContent::OID(o.clone())));
+    }
+    if let Some(i) = ext.indirect_reference {
+        v.push(BerObject::from_i64(i));
+    }
+    if let Some(ref s) = ext.data_value_descriptor {
+        v.push(BerObject::from_obj(BerObjectContent::ObjectDescriptor(
+            s.clone(),
+        )));
+    }
+    v.push(match ext.encoding {
+        ExternalEncoding::SingleAsn1Type(ref o) => {
+            BerObject::from_explicit(BerTag(0), (**o).clone())
+        }
+        ExternalEncoding::OctetAligned(ref s) => {
+            BerObject::from_implicit(BerTag(1), BerObject::from_octetstring(s.clone()))
+        }
+        ExternalEncoding::Arbitrary(u, ref b) => {
+            BerObject::from_implicit(BerTag(2), BerObject::from_bitstring(b.data.clone(), u))
+        }
+    });
+    v
+}
+
+/// Build the components of the associated SEQUENCE type of an EMBEDDED PDV or CHARACTER STRING
+pub(crate) fn pdv_components<'a>(
+    id: &PdvIdentification<'a>,
+    descriptor: &Option<Cow<'a, [u8]>>,
+    value: &Cow<'a, [u8]>,
+) -> Vec<BerObject<'a>> {
+    let mut v = vec![identification_to_ber(id)];
+    if let Some(ref s) = *descriptor {
+        v.push(BerObject::from_implicit(
+            BerTag(1),
+            BerObject::from_obj(BerObjectContent::ObjectDescriptor(s.clone())),
+        ));
+    }
+    v.push(BerObject::from_implicit(
+        BerTag(2),
+        BerObject::from_octetstring(value.clone()),
+    ));
+    v
+}
@@ -0,0 +1,431 @@
+//! Decoding of TIME, DATE, TIME-OF-DAY, DATE-TIME and DURATION values (X.680 section 38)
+//!
+//! Values are ISO 8601 strings. DATE, TIME-OF-DAY and DATE-TIME use the basic format
+//! (`YYYYMMDD`, `hhmmss` and `YYYYMMDDhhmmss`). The extended format (`YYYY-MM-DD`, `hh:mm:ss`,
+//! with a `T` between the date and the time) is also accepted in BER.
+
+use crate::ber::datetime::{fraction_to_nanos, TimeReader};
+use crate::ber::{BerDateTime, BerTimeZone};
+use crate::error::BerError;
+use std::fmt;
+
+/// A decoded DATE value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BerDate {
+    pub year: u32,
+    pub month: u8,
+    pub day: u8,
+}
+
+/// A decoded TIME-OF-DAY value, or the time of a TIME value
+///
+/// TIME-OF-DAY values have no fractional part, and `nanosecond` is always 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BerTimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+/// A decoded DURATION value
+///
+/// Only the seconds can have a fractional part.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BerDuration {
+    pub years: u32,
+    pub months: u32,
+    pub weeks: u32,
+    pub days: u32,
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub nanoseconds: u32,
+}
+
+/// A decoded TIME value
+///
+/// Time intervals and recurrences are not supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BerTime {
+    Date(BerDate),
+    TimeOfDay(BerTimeOfDay, BerTimeZone),
+    DateTime(BerDateTime),
+    Duration(BerDuration),
+}
+
+/// Read a date, `YYYYMMDD` or (if `der` is false) `YYYY-MM-DD`
+fn read_date(r: &mut TimeReader, der: bool) -> Result<BerDate, BerError> {
+    let year = r.digits(4)?;
+    let extended = r.skip(b'-');
+    if extended && der {
+        return Err(BerError::DerConstraintFailed);
+    }
+    let month = r.digits(2)? as u8;
+    if extended && !r.skip(b'-') {
+        return Err(BerError::InvalidValue);
+    }
+    let day = r.digits(2)? as u8;
+    let date = BerDate { year, month, day };
+    date.check()?;
+    Ok(date)
+}
+
+/// Read a time, `hhmmss` or (if `der` is false) `hh:mm:ss`
+///
+/// If `full` is false, minutes and seconds can be omitted, and the last element can have a
+/// decimal fraction.
+fn read_time(r: &mut TimeReader, der: bool, full: bool) -> Result<BerTimeOfDay, BerError> {
+    let hour = r.digits(2)? as u8;
+    let extended = r.peek() == Some(b':');
+    if extended && der {
+        return Err(BerError::DerConstraintFailed);
+    }
+    let next = |r: &mut TimeReader| if extended { r.skip(b':') } else { r.peek_digit() };
+    let mut minute = 0;
+    let mut second = 0;
+    // unit of the fraction, in nanoseconds
+    let mut unit = 3_600_000_000_000;
+    if next(r) {
+        minute = r.digits(2)? as u8;
+        unit = 60_000_000_000;
+        if next(r) {
+            second = r.digits(2)? as u8;
+            unit = 1_000_000_000;
+        }
+    }
+    if full && unit != 1_000_000_000 {
+        return Err(BerError::InvalidValue);
+    }
+    let mut extra = 0;
+    if !full && (r.skip(b'.') || r.skip(b',')) {
+        extra = fraction_to_nanos(r.fraction()?, unit);
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err(BerError::InvalidValue);
+    }
+    let extra_seconds = extra / 1_000_000_000;
+    Ok(BerTimeOfDay {
+        hour,
+        minute: minute + (extra_seconds / 60) as u8,
+        second: second + (extra_seconds % 60) as u8,
+        nanosecond: (extra % 1_000_000_000) as u32,
+    })
+}
+
+/// Read an optional time zone, `Z`, `+hh`, `+hhmm` or `+hh:mm` (or `-`)
+fn read_timezone(r: &mut TimeReader) -> Result<BerTimeZone, BerError> {
+    if r.skip(b'Z') {
+        return Ok(BerTimeZone::Z);
+    }
+    let sign = match r.peek() {
+        Some(b'+') => 1,
+        Some(b'-') => -1,
+        _ => return Ok(BerTimeZone::Undefined),
+    };
+    r.skip(r.0[0]);
+    let hh = r.digits(2)?;
+    let mm = if r.skip(b':') || r.peek_digit() {
+        r.digits(2)?
+    } else {
+        0
+    };
+    if hh > 23 || mm > 59 {
+        return Err(BerError::InvalidValue);
+    }
+    Ok(BerTimeZone::Offset(sign * (hh * 60 + mm) as i16))
+}
+
+impl BerDate {
+    /// Decode the content of a DATE object
+    ///
+    /// If `der` is true, the basic format `YYYYMMDD` is required.
+    pub fn from_bytes(s: &[u8], der: bool) -> Result<BerDate, BerError> {
+        let mut r = TimeReader(s);
+        let date = read_date(&mut r, der)?;
+        r.end()?;
+        Ok(date)
+    }
+
+    fn check(&self) -> Result<(), BerError> {
+        BerDateTime {
+            year: self.year,
+            month: self.month,
+            day: self.day,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            nanosecond: 0,
+            tz: BerTimeZone::Undefined,
+        }
+        .check()
+    }
+}
+
+impl BerTimeOfDay {
+    /// Decode the content of a TIME-OF-DAY object
+    ///
+    /// If `der` is true, the basic format `hhmmss` is required.
+    pub fn from_bytes(s: &[u8], der: bool) -> Result<BerTimeOfDay, BerError> {
+        let mut r = TimeReader(s);
+        let time = read_time(&mut r, der, true)?;
+        r.end()?;
+        Ok(time)
+    }
+}
+
+impl BerDateTime {
+    /// Decode the content of a DATE-TIME object
+    ///
+    /// The value is a local time, without fraction. If `der` is true, the basic format
+    /// `YYYYMMDDhhmmss` is required.
+    pub fn from_datetime(s: &[u8], der: bool) -> Result<BerDateTime, BerError> {
+        let mut r = TimeReader(s);
+        let date = read_date(&mut r, der)?;
+        if r.skip(b'T') && der {
+            return Err(BerError::DerConstraintFailed);
+        }
+        let time = read_time(&mut r, der, true)?;
+        r.end()?;
+        Ok(BerDateTime::from_parts(date, time, BerTimeZone::Undefined))
+    }
+
+    fn from_parts(date: BerDate, time: BerTimeOfDay, tz: BerTimeZone) -> BerDateTime {
+        BerDateTime {
+            year: date.year,
+            month: date.month,
+            day: date.day,
+            hour: time.hour,
+            minute: time.minute,
+            second: time.second,
+            nanosecond: time.nanosecond,
+            tz,
+        }
+    }
+}
+
+impl BerDuration {
+    /// Decode the content of a DURATION object
+    ///
+    /// The format is `PnYnMnWnDTnHnMnS`, where all components are optional (but at least one
+    /// is present, and `T` is only present if it is followed by a component).
+    pub fn from_bytes(s: &[u8]) -> Result<BerDuration, BerError> {
+        let mut r = TimeReader(s);
+        if !r.skip(b'P') {
+            return Err(BerError::InvalidValue);
+        }
+        let mut d = BerDuration::default();
+        let mut in_time = false;
+        let mut empty = true;
+        let mut last = 0;
+        while !r.0.is_empty() {
+            if r.skip(b'T') {
+                if in_time || r.0.is_empty() {
+                    return Err(BerError::InvalidValue);
+                }
+                in_time = true;
+                continue;
+            }
+            let value = r.fraction()?;
+            if value.len() > 9 {
+                return Err(BerError::InvalidValue);
+            }
+            let value = value.iter().fold(0, |acc, &c| acc * 10 + u32::from(c - b'0'));
+            let fraction = if r.skip(b'.') || r.skip(b',') {
+                Some(r.fraction()?)
+            } else {
+                None
+            };
+            let designator = r.peek().ok_or(BerError::InvalidValue)?;
+            r.skip(designator);
+            let (order, field) = match (in_time, designator) {
+                (false, b'Y') => (1, &mut d.years),
+                (false, b'M') => (2, &mut d.months),
+                (false, b'W') => (3, &mut d.weeks),
+                (false, b'D') => (4, &mut d.days),
+                (true, b'H') => (5, &mut d.hours),
+                (true, b'M') => (6, &mut d.minutes),
+                (true, b'S') => (7, &mut d.seconds),
+                _ => return Err(BerError::InvalidValue),
+            };
+            // components are in decreasing order, and not repeated (so the seconds, which can
+            // have a fraction, are the last component)
+            if order <= last {
+                return Err(BerError::InvalidValue);
+            }
+            last = order;
+            *field = value;
+            empty = false;
+            if let Some(fraction) = fraction {
+                if order != 7 {
+                    return Err(BerError::Unsupported);
+                }
+                d.nanoseconds = fraction_to_nanos(fraction, 1_000_000_000) as u32;
+            }
+        }
+        if empty {
+            return Err(BerError::InvalidValue);
+        }
+        Ok(d)
+    }
+}
+
+impl BerTime {
+    /// Decode the content of a TIME object
+    ///
+    /// Dates, times of day (with optional fraction and time zone), dates and times, and
+    /// durations are decoded, in the basic or extended format. Intervals and recurrences return
+    /// `BerError::Unsupported`.
+    pub fn from_bytes(s: &[u8]) -> Result<BerTime, BerError> {
+        match s.first() {
+            Some(b'R') => return Err(BerError::Unsupported),
+            _ if s.contains(&b'/') => return Err(BerError::Unsupported),
+            Some(b'P') => return BerDuration::from_bytes(s).map(BerTime::Duration),
+            _ => (),
+        }
+        let mut r = TimeReader(s);
+        let digits = s.iter().take_while(|c| c.is_ascii_digit()).count();
+        let value = if digits >= 8 || s.get(4) == Some(&b'-') {
+            let date = read_date(&mut r, false)?;
+            if r.skip(b'T') {
+                let time = read_time(&mut r, false, false)?;
+                let tz = read_timezone(&mut r)?;
+                BerTime::DateTime(BerDateTime::from_parts(date, time, tz))
+            } else {
+                BerTime::Date(date)
+            }
+        } else {
+            let time = read_time(&mut r, false, false)?;
+            let tz = read_timezone(&mut r)?;
+            BerTime::TimeOfDay(time, tz)
+        };
+        r.end()?;
+        Ok(value)
+    }
+}
+
+impl fmt::Display for BerDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl fmt::Display for BerTimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        if self.nanosecond != 0 {
+            write!(f, ".{:09}", self.nanosecond)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BerDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("P")?;
+        for &(v, c) in &[(self.years, 'Y'), (self.months, 'M'), (self.weeks, 'W'), (self.days, 'D')] {
+            if v != 0 {
+                write!(f, "{}{}", v, c)?;
+            }
+        }
+        if self.hours != 0 || self.minutes != 0 || self.seconds != 0 || self.nanoseconds != 0 {
+            f.write_str("T")?;
+            if self.hours != 0 {
+                write!(f, "{}H", self.hours)?;
+            }
+            if self.minutes != 0 {
+                write!(f, "{}M", self.minutes)?;
+            }
+            if self.seconds != 0 || self.nanoseconds != 0 {
+                write!(f, "{}", self.seconds)?;
+                if self.nanoseconds != 0 {
+                    let frac = format!("{:09}", self.nanoseconds);
+                    write!(f, ".{}", frac.trim_end_matches('0'))?;
+                }
+                f.write_str("S")?;
+            }
+        } else if *self == BerDuration::default() {
+            f.write_str("T0S")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BerTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BerTime::Date(ref d) => d.fmt(f),
+            BerTime::TimeOfDay(ref t, BerTimeZone::Undefined) => t.fmt(f),
+            BerTime::TimeOfDay(ref t, tz) => write!(f, "{} {}", t, tz),
+            BerTime::DateTime(ref dt) => dt.fmt(f),
+            BerTime::Duration(ref d) => d.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+#[test]
+fn test_date_timeofday_datetime() {
+    let d = BerDate::from_bytes(b"20121221", true).expect("decoding failed");
+    assert_eq!(d, BerDate { year: 2012, month: 12, day: 21 });
+    assert_eq!(d.to_string(), "2012-12-21");
+    assert_eq!(BerDate::from_bytes(b"2012-12-21", false), Ok(d));
+    assert_eq!(BerDate::from_bytes(b"2012-12-21", true), Err(BerError::DerConstraintFailed));
+    assert_eq!(BerDate::from_bytes(b"2012-1221", false), Err(BerError::InvalidValue));
+    assert_eq!(BerDate::from_bytes(b"20130229", false), Err(BerError::InvalidValue));
+    let t = BerTimeOfDay::from_bytes(b"153000", true).expect("decoding failed");
+    assert_eq!((t.hour, t.minute, t.second), (15, 30, 0));
+    assert_eq!(BerTimeOfDay::from_bytes(b"15:30:00", false), Ok(t));
+    assert_eq!(BerTimeOfDay::from_bytes(b"1530", false), Err(BerError::InvalidValue));
+    assert_eq!(BerTimeOfDay::from_bytes(b"153000Z", false), Err(BerError::InvalidValue));
+    assert_eq!(BerTimeOfDay::from_bytes(b"246000", false), Err(BerError::InvalidValue));
+    let dt = BerDateTime::from_datetime(b"20121221153000", true).expect("decoding failed");
+    assert_eq!((dt.year, dt.month, dt.day, dt.hour), (2012, 12, 21, 15));
+    assert_eq!(dt.tz, BerTimeZone::Undefined);
+    assert_eq!(BerDateTime::from_datetime(b"2012-12-21T15:30:00", false), Ok(dt));
+    assert_eq!(BerDateTime::from_datetime(b"20121221T153000", true), Err(BerError::DerConstraintFailed));
+}
+
+#[test]
+fn test_duration() {
+    let d = BerDuration::from_bytes(b"P1Y2M10DT2H30M").expect("decoding failed");
+    assert_eq!((d.years, d.months, d.days, d.hours, d.minutes), (1, 2, 10, 2, 30));
+    assert_eq!(d.to_string(), "P1Y2M10DT2H30M");
+    let d = BerDuration::from_bytes(b"PT0,25S").expect("decoding failed");
+    assert_eq!(d.nanoseconds, 250_000_000);
+    assert_eq!(d.to_string(), "PT0.25S");
+    assert_eq!(BerDuration::from_bytes(b"P3W").map(|d| d.weeks), Ok(3));
+    assert_eq!(BerDuration::from_bytes(b"P"), Err(BerError::InvalidValue));
+    assert_eq!(BerDuration::from_bytes(b"P1DT"), Err(BerError::InvalidValue));
+    assert_eq!(BerDuration::from_bytes(b"P1D2Y"), Err(BerError::InvalidValue));
+    assert_eq!(BerDuration::from_bytes(b"P1H"), Err(BerError::InvalidValue));
+    assert_eq!(BerDuration::from_bytes(b"PT1.5S2M"), Err(BerError::InvalidValue));
+    assert_eq!(BerDuration::from_bytes(b"P1.5D"), Err(BerError::Unsupported));
+}
+
+#[test]
+fn test_time() {
+    let t = BerTime::from_bytes(b"2012-12-21").expect("decoding failed");
+    assert_eq!(t, BerTime::Date(BerDate { year: 2012, month: 12, day: 21 }));
+    let t = BerTime::from_bytes(b"15:30:20.5+01:00").expect("decoding failed");
+    match t {
+        BerTime::TimeOfDay(t, tz) => {
+            assert_eq!((t.hour, t.minute, t.second, t.nanosecond), (15, 30, 20, 500_000_000));
+            assert_eq!(tz, BerTimeZone::Offset(60));
+        }
+        _ => panic!("unexpected value {:?}", t),
+    }
+    assert_eq!(t.to_string(), "15:30:20.500000000 +0100");
+    let t = BerTime::from_bytes(b"20121221T1530Z").expect("decoding failed");
+    assert_eq!(t.to_string(), "2012-12-21 15:30:00 UTC");
+    let t = BerTime::from_bytes(b"PT36H").expect("decoding failed");
+    assert_eq!(t, BerTime::Duration(BerDuration { hours: 36, ..Default::default() }));
+    assert_eq!(BerTime::from_bytes(b"2012-12-21/2012-12-24"), Err(BerError::Unsupported));
+    assert_eq!(BerTime::from_bytes(b"R2/PT1H"), Err(BerError::Unsupported));
+    assert_eq!(BerTime::from_bytes(b"15:30:20X"), Err(BerError::InvalidValue));
+}
+
+}
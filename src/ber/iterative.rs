@@ -0,0 +1,193 @@
+use crate::ber::*;
+use crate::error::*;
+use nom::Err;
+
+/// Parse BER object, using an explicit stack for the elements of SEQUENCE and SET values
+///
+/// The result is the same as [`parse_ber_with_options`](fn.parse_ber_with_options.html), but
+/// nested sequences and sets do not use the call stack, so `options.max_depth` can be greater
+/// than [`MAX_RECURSION`](constant.MAX_RECURSION.html) (the outer object has depth 0). This allows
+/// parsing deep structures on threads with a small stack.
+///
+/// Only universal SEQUENCE and SET values are read using the stack. Other objects, including
+/// constructed strings and tagged values using the indefinite length form, are read by
+/// `parse_ber_with_options`, so their content is still limited to `MAX_RECURSION` levels.
+///
+/// Note that dropping, comparing or printing a `BerObject` is recursive, so `max_depth` should
+/// still be chosen according to the available stack.
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # use der_parser::ber::{parse_ber_iterative, ParserOptions};
+/// # fn main() {
+/// // 100 nested sequences (indefinite length), containing an integer: this is more than the
+/// // limit of `parse_ber`
+/// let mut bytes = vec![0x30, 0x80].repeat(100);
+/// bytes.extend_from_slice(&[0x02, 0x01, 0x01]);
+/// bytes.extend(vec![0x00, 0x00].repeat(100));
+/// let options = ParserOptions { max_depth: 100, ..Default::default() };
+/// let (rem, obj) = parse_ber_iterative(&bytes, &options).expect("parsing failed");
+/// assert!(rem.is_empty());
+/// assert!(obj.as_sequence().is_ok());
+/// # }
+/// ```
+pub fn parse_ber_iterative<'a>(i: &'a [u8], options: &ParserOptions) -> BerResult<'a> {
+    let mut stack = Vec::new();
+    let res = ber_read_iterative(i, options, &mut stack);
+    // the enclosing objects are on the stack: unlike `ber_locate_error`, this does not parse the
+    // input again to find the path of the error
+    ber_locate_error_with(i, res, |e| {
+        let mut path: Vec<_> = stack.iter().map(|frame| frame.hdr).collect();
+        // errors of objects read by `parse_ber_with_options` already have a path, relative to
+        // the object
+        path.extend_from_slice(&e.path);
+        path
+    })
+}
+
+/// A SEQUENCE or SET being read
+struct Frame<'a> {
+    hdr: BerObjectHeader,
+    /// Input starting with the header of the object
+    i: &'a [u8],
+    /// Remaining content (definite form), or remaining input (indefinite form)
+    rem: &'a [u8],
+    /// Input following the object (definite form only)
+    after: Option<&'a [u8]>,
+    objects: Vec<BerObject<'a>>,
+}
+
+impl<'a> Frame<'a> {
+    /// Return the input following the object, if all elements have been read
+    fn end(&self) -> Option<&'a [u8]> {
+        match self.after {
+            Some(after) if self.rem.is_empty() => Some(after),
+            Some(_) => None,
+            None => ber_read_eoc(self.rem).ok().map(|(rem, _)| rem),
+        }
+    }
+
+    fn into_object(self) -> BerObject<'a> {
+        let content = if self.hdr.tag == BerTag::Sequence {
+            BerObjectContent::Sequence(self.objects)
+        } else {
+            BerObjectContent::Set(self.objects)
+        };
+        BerObject::from_header_and_content(self.hdr, content)
+    }
+}
+
+/// Next element of a SEQUENCE or SET
+enum Element<'a> {
+    /// A SEQUENCE or SET, with its elements still to read
+    Open(Frame<'a>),
+    /// Any other object, already read
+    Object(BerObject<'a>),
+}
+
+/// Read the next object, using `stack` for the SEQUENCE and SET values being read
+///
+/// On error, `stack` contains the objects enclosing the error.
+fn ber_read_iterative<'a>(i: &'a [u8], options: &ParserOptions, stack: &mut Vec<Frame<'a>>) -> BerResult<'a> {
+    loop {
+        // close the completed objects, and add them to their parent
+        while let Some(after) = stack.last().and_then(Frame::end) {
+            let frame = stack.pop().expect("stack is not empty");
+            check_size(i, frame.i, after, 0, options)?;
+            let obj = frame.into_object();
+            match stack.last_mut() {
+                Some(parent) => {
+                    parent.objects.push(obj);
+                    parent.rem = after;
+                }
+                None => return Ok((after, obj)),
+            }
+        }
+        let (input, definite) = match stack.last() {
+            Some(frame) => {
+                error_if!(
+                    frame.rem,
+                    frame.objects.len() >= options.max_elements,
+                    BerError::LimitExceeded.into_error_kind()
+                )?;
+                (frame.rem, frame.after.is_some())
+            }
+            None => (i, false),
+        };
+        match ber_read_element_iterative(i, input, stack.len(), options) {
+            Ok((_, Element::Open(frame))) => stack.push(frame),
+            Ok((rem, Element::Object(obj))) => match stack.last_mut() {
+                Some(parent) => {
+                    parent.objects.push(obj);
+                    parent.rem = rem;
+                }
+                None => return Ok((rem, obj)),
+            },
+            // object is larger than the container
+            Err(Err::Incomplete(_)) if definite => {
+                return Err(Err::Error(error_position!(input, BerError::InvalidLength.into_error_kind())))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Fail if the object at `i` ends after `max_size` bytes of `start` (`len` bytes after `rem`)
+fn check_size<'a>(start: &[u8], i: &'a [u8], rem: &[u8], len: usize, options: &ParserOptions) -> BerResult<'a, ()> {
+    let offset = rem.as_ptr() as usize - start.as_ptr() as usize;
+    error_if!(
+        i,
+        offset.saturating_add(len) > options.max_size,
+        BerError::LimitExceeded.into_error_kind()
+    )
+}
+
+/// Read the header of SEQUENCE and SET values, or the entire object for other types
+///
+/// `start` is the input of `parse_ber_iterative`, used to check the size limit.
+fn ber_read_element_iterative<'a>(
+    start: &[u8],
+    i: &'a [u8],
+    depth: usize,
+    options: &ParserOptions,
+) -> BerResult<'a, Element<'a>> {
+    error_if!(i, depth > options.max_depth, BerError::BerMaxDepth.into_error_kind())?;
+    let (rem, hdr) = ber_read_element_header(i)?;
+    if hdr.class != 0 || !hdr.is_constructed() || (hdr.tag != BerTag::Sequence && hdr.tag != BerTag::Set) {
+        // the remaining depth and size limits apply to the object
+        let offset = i.as_ptr() as usize - start.as_ptr() as usize;
+        let options = ParserOptions {
+            max_depth: ::std::cmp::min(options.max_depth - depth, MAX_RECURSION),
+            max_size: options.max_size.saturating_sub(offset),
+            ..*options
+        };
+        return map!(i, call!(parse_ber_with_options, &options), Element::Object);
+    }
+    // same checks as parse_ber_with_options
+    let frame = match hdr.len {
+        BerSize::Definite(l) => {
+            check_size(start, i, rem, l, options)?;
+            error_if!(i, l > i.len() || l > ::std::u32::MAX as usize, BerError::InvalidLength.into_error_kind())?;
+            let (after, content) = ber_take(rem, l)?;
+            Frame {
+                hdr,
+                i,
+                rem: content,
+                after: Some(after),
+                objects: Vec::new(),
+            }
+        }
+        BerSize::Indefinite => {
+            check_size(start, i, rem, 0, options)?;
+            error_if!(i, !options.allow_indefinite, BerError::InvalidLength.into_error_kind())?;
+            Frame {
+                hdr,
+                i,
+                rem,
+                after: None,
+                objects: Vec::new(),
+            }
+        }
+    };
+    Ok((rem, Element::Open(frame)))
+}
@@ -1,8 +1,24 @@
 //! Basic Encoding Rules (BER) objects and parser
 
 mod ber;
+mod datetime;
+mod external;
+mod iso8601;
+mod iterative;
+mod options;
 mod parser;
 mod print;
+mod real;
+mod serialize;
+mod string;
 
 pub use ber::ber::*;
+pub use ber::datetime::*;
+pub use ber::external::*;
+pub use ber::iso8601::*;
+pub use ber::iterative::*;
+pub use ber::options::*;
 pub use ber::parser::*;
+pub use ber::serialize::*;
+pub use ber::string::{is_graphic_char, is_numeric_char, is_printable_char, is_visible_char};
+pub(crate) use ber::real::{der_check_real, der_normalize_real};
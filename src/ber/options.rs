@@ -0,0 +1,169 @@
+use crate::ber::*;
+use crate::error::*;
+
+/// Limits and accepted encodings of the parser
+///
+/// Used by [`parse_ber_with_options`](fn.parse_ber_with_options.html),
+/// [`parse_ber_iterative`](fn.parse_ber_iterative.html) and
+/// [`parse_der_with_options`](../der/fn.parse_der_with_options.html) to restrict the resources
+/// used when parsing untrusted input. The default options accept the same objects as
+/// [`parse_ber`](fn.parse_ber.html).
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # use der_parser::ber::{parse_ber_with_options, ParserOptions};
+/// # fn main() {
+/// let options = ParserOptions {
+///     max_depth: 1,
+///     allow_indefinite: false,
+///     ..Default::default()
+/// };
+/// // sequence containing a sequence containing an integer
+/// let bytes = [0x30, 0x05, 0x30, 0x03, 0x02, 0x01, 0x01];
+/// assert!(parse_ber_with_options(&bytes, &options).is_err());
+/// assert!(parse_ber_with_options(&bytes[2..], &options).is_ok());
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Maximum depth of nested objects (the outer object has depth 0)
+    ///
+    /// The limit can only be lowered: values greater than
+    /// [`MAX_RECURSION`](constant.MAX_RECURSION.html), the limit of the recursive parser, are not
+    /// supported and parsing fails with `Unsupported`. Use
+    /// [`parse_ber_iterative`](fn.parse_ber_iterative.html) for deeper SEQUENCE and SET values.
+    pub max_depth: usize,
+    /// Maximum size of the object (including header), in bytes
+    pub max_size: usize,
+    /// Maximum number of elements of a constructed object (for ex. SEQUENCE or SET), including
+    /// the segments of constructed strings
+    pub max_elements: usize,
+    /// Maximum number of arcs of an OBJECT IDENTIFIER or RELATIVE-OID
+    pub max_oid_arcs: usize,
+    /// Accept universal tags not known by the parser (returned as `BerObjectContent::Unknown`)
+    pub allow_unknown_tags: bool,
+    /// Accept the indefinite length form
+    pub allow_indefinite: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> ParserOptions {
+        ParserOptions {
+            max_depth: MAX_RECURSION,
+            max_size: ::std::usize::MAX,
+            max_elements: ::std::usize::MAX,
+            max_oid_arcs: ::std::usize::MAX,
+            allow_unknown_tags: true,
+            allow_indefinite: true,
+        }
+    }
+}
+
+/// Parse BER object, checking the limits of `options`
+///
+/// The input is read twice: the limits are first verified by reading the headers only, so large
+/// or deeply nested objects are rejected before decoding the content, and the object is then
+/// parsed as [`parse_ber`](fn.parse_ber.html) does. Exceeding the depth fails with `BerMaxDepth`,
+/// an indefinite length with `InvalidLength`, an unknown tag with `UnknownTag`, and the other
+/// limits with `LimitExceeded`.
+pub fn parse_ber_with_options<'a>(i: &'a [u8], options: &ParserOptions) -> BerResult<'a> {
+    ber_locate_error(
+        i,
+        ber_check_options(i, options, false).and_then(|_| parse_ber_recursive(i, MAX_RECURSION - options.max_depth)),
+    )
+}
+
+/// Verify the limits of `options` on the next object, without decoding its content
+///
+/// The elements of constructed objects are checked if the parser decodes them: `der` must be true
+/// for `parse_der`, which also decodes private tags as universal types.
+///
+/// On success, the parsing functions must still limit the depth: they are called with
+/// `MAX_RECURSION - max_depth` as the depth of the object.
+pub(crate) fn ber_check_options<'a>(i: &'a [u8], options: &ParserOptions, der: bool) -> BerResult<'a, ()> {
+    error_if!(i, options.max_depth > MAX_RECURSION, BerError::Unsupported.into_error_kind())?;
+    let checker = OptionsChecker { start: i, options, der };
+    checker.element(i, 0)
+}
+
+struct OptionsChecker<'o, 'a> {
+    start: &'a [u8],
+    options: &'o ParserOptions,
+    der: bool,
+}
+
+impl<'o, 'a> OptionsChecker<'o, 'a> {
+    /// Fail if the object starting at `i` ends after `max_size` bytes (`len` bytes after `rem`)
+    fn check_size(&self, i: &'a [u8], rem: &'a [u8], len: usize) -> BerResult<'a, ()> {
+        let offset = rem.as_ptr() as usize - self.start.as_ptr() as usize;
+        error_if!(
+            i,
+            offset.saturating_add(len) > self.options.max_size,
+            BerError::LimitExceeded.into_error_kind()
+        )
+    }
+
+    fn element(&self, i: &'a [u8], depth: usize) -> BerResult<'a, ()> {
+        error_if!(i, depth > self.options.max_depth, BerError::BerMaxDepth.into_error_kind())?;
+        let (rem, hdr) = ber_read_element_header(i)?;
+        match hdr.len {
+            BerSize::Definite(l) => self.check_size(i, rem, l)?,
+            BerSize::Indefinite => {
+                self.check_size(i, rem, 0)?;
+                error_if!(
+                    i,
+                    !self.options.allow_indefinite || !hdr.is_constructed(),
+                    BerError::InvalidLength.into_error_kind()
+                )?
+            }
+        };
+        if hdr.class == 0 && !self.options.allow_unknown_tags {
+            error_if!(i, !is_known_tag(hdr.tag), BerError::UnknownTag.into_error_kind())?;
+        }
+        let (content, after) = match hdr.len {
+            BerSize::Definite(l) => {
+                let (after, content) = ber_take(rem, l)?;
+                (content, Some(after))
+            }
+            BerSize::Indefinite => (rem, None),
+        };
+        if !hdr.is_constructed() {
+            if hdr.class == 0 && (hdr.tag == BerTag::Oid || hdr.tag == BerTag::RelativeOid) {
+                // each subidentifier ends with an octet with bit 8 set to zero, and the first
+                // subidentifier of an OID encodes two arcs
+                let subids = content.iter().filter(|&&b| b & 0x80 == 0).count();
+                let arcs = if hdr.tag == BerTag::Oid { subids + 1 } else { subids };
+                error_if!(i, arcs > self.options.max_oid_arcs, BerError::LimitExceeded.into_error_kind())?;
+            }
+            return Ok((after.unwrap_or(content), ()));
+        }
+        // the content of other objects is not decoded by the parser, except to find the end of
+        // indefinite length objects
+        let universal = hdr.class == 0 || (self.der && hdr.class == 0b11);
+        let decoded = universal && is_known_tag(hdr.tag);
+        if !decoded && !hdr.len.is_indefinite() {
+            return Ok((after.unwrap_or(content), ()));
+        }
+        let mut c = content;
+        let mut count = 0;
+        loop {
+            match after {
+                Some(after) if c.is_empty() => return Ok((after, ())),
+                None if c.starts_with(&[0, 0]) => {
+                    let after = &c[2..];
+                    self.check_size(i, after, 0)?;
+                    return Ok((after, ()));
+                }
+                _ => (),
+            }
+            count += 1;
+            error_if!(c, count > self.options.max_elements, BerError::LimitExceeded.into_error_kind())?;
+            c = self.element(c, depth + 1)?.0;
+        }
+    }
+}
+
+/// Test if the universal tag is decoded by the parser (other tags are returned as `Unknown`)
+fn is_known_tag(tag: BerTag) -> bool {
+    tag.0 <= BerTag::Duration.0 && tag.0 != 0x0f
+}
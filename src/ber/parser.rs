@@ -1,11 +1,26 @@
+use crate::ber::external::{
+    ber_read_content_characterstring, ber_read_content_embeddedpdv, ber_read_content_external,
+};
+use crate::ber::real::decode_real;
 use crate::ber::*;
 use crate::error::*;
 use crate::oid::*;
-use nom::{be_u8, Context, Err, ErrorKind, IResult, Needed};
+use nom::{Context, Err, ErrorKind, Needed};
+use std::borrow::Cow;
 
 /// Maximum recursion limit
 pub const MAX_RECURSION: usize = 50;
 
+/// Take `n` bytes of input (same as nom's `take!`, but using the crate error type)
+#[inline]
+pub(crate) fn ber_take(i: &[u8], n: usize) -> BerResult<&[u8]> {
+    if i.len() < n {
+        Err(Err::Incomplete(Needed::Size(n)))
+    } else {
+        Ok((&i[n..], &i[..n]))
+    }
+}
+
 /// Try to parse input bytes as u64
 pub(crate) fn bytes_to_u64(s: &[u8]) -> Result<u64, BerError> {
     let mut u: u64 = 0;
@@ -19,7 +34,48 @@ pub(crate) fn bytes_to_u64(s: &[u8]) -> Result<u64, BerError> {
     Ok(u)
 }
 
-pub(crate) fn parse_identifier(i: &[u8]) -> IResult<&[u8], (u8, u8, u32)> {
+/// Try to parse input bytes as an unsigned integer (two's complement)
+///
+/// Fails if the sign bit is set.
+pub(crate) fn bytes_to_unsigned(s: &[u8]) -> Result<u64, BerError> {
+    match s.first() {
+        Some(&b) if b & 0x80 != 0 => Err(BerError::IntegerNegative),
+        _ => bytes_to_u64(s),
+    }
+}
+
+/// Test if the content of an integer is minimally encoded (X.690 section 8.3.2)
+///
+/// The content must not be empty, and the first 9 bits must not be all ones or all zeros.
+pub(crate) fn is_minimal_integer(s: &[u8]) -> bool {
+    match s {
+        [] => false,
+        [0x00, b, ..] => b & 0x80 != 0,
+        [0xff, b, ..] => b & 0x80 == 0,
+        _ => true,
+    }
+}
+
+/// Try to parse input bytes as a signed integer (two's complement)
+pub(crate) fn bytes_to_i128(s: &[u8]) -> Result<i128, BerError> {
+    let mut s = s;
+    // remove redundant sign extension octets
+    while s.len() > 1 && ((s[0] == 0 && s[1] & 0x80 == 0) || (s[0] == 0xff && s[1] & 0x80 != 0)) {
+        s = &s[1..];
+    }
+    if s.len() > 16 {
+        return Err(BerError::IntegerTooLarge);
+    }
+    let fill = match s.first() {
+        Some(&b) if b & 0x80 != 0 => 0xff,
+        _ => 0,
+    };
+    let mut buf = [fill; 16];
+    buf[16 - s.len()..].copy_from_slice(s);
+    Ok(i128::from_be_bytes(buf))
+}
+
+pub(crate) fn parse_identifier(i: &[u8]) -> BerResult<(u8, u8, u32)> {
     if i.is_empty() {
         Err(Err::Incomplete(Needed::Size(1)))
     } else {
@@ -36,12 +92,12 @@ pub(crate) fn parse_identifier(i: &[u8]) -> IResult<&[u8], (u8, u8, u32)> {
                 error_if!(
                     i,
                     tag_byte_count >= i.len(),
-                    ErrorKind::Custom(BER_TAG_ERROR)
+                    BerError::InvalidTag.into_error_kind()
                 )?;
 
                 // With tag defined as u32 the most we can fit in is four tag bytes.
                 // (X.690 doesn't actually specify maximum tag width.)
-                error_if!(i, tag_byte_count > 5, ErrorKind::Custom(BER_TAG_ERROR))?;
+                error_if!(i, tag_byte_count > 5, BerError::InvalidTag.into_error_kind())?;
 
                 c = (c << 7) | ((i[tag_byte_count] as u32) & 0x7f);
                 let done = i[tag_byte_count] & 0x80 == 0;
@@ -56,7 +112,7 @@ pub(crate) fn parse_identifier(i: &[u8]) -> IResult<&[u8], (u8, u8, u32)> {
     }
 }
 
-pub(crate) fn parse_ber_length_byte(i: &[u8]) -> IResult<&[u8], (u8, u8)> {
+pub(crate) fn parse_ber_length_byte(i: &[u8]) -> BerResult<(u8, u8)> {
     if i.is_empty() {
         Err(Err::Incomplete(Needed::Size(1)))
     } else {
@@ -66,69 +122,27 @@ pub(crate) fn parse_ber_length_byte(i: &[u8]) -> IResult<&[u8], (u8, u8)> {
     }
 }
 
-fn ber_read_relative_oid(i: &[u8]) -> Result<Vec<u64>, u64> {
-    let mut oid = Vec::new();
-    let mut acc: u64;
-
-    if i.is_empty() {
-        return Ok(oid);
-    };
-
-    acc = 0;
-    for &c in &i[0..] {
-        acc = (acc << 7) | (c & 0b0111_1111) as u64;
-        if (c & (1 << 7)) == 0 {
-            oid.push(acc);
-            acc = 0;
-        }
-    }
-
-    match acc {
-        0 => Ok(oid),
-        _ => Err(acc),
-    }
-}
-
-fn ber_read_oid(i: &[u8]) -> Result<Vec<u64>, u64> {
-    let mut oid = Vec::new();
-    let mut index = 0;
-
-    if i.is_empty() {
-        return Err(0);
-    };
-
-    /* first element = X*40 + Y (See 8.19.4) */
-    let acc = i[0] as u64;
-    if acc < 128 {
-        oid.push(acc / 40);
-        oid.push(acc % 40);
-        index = 1;
-    }
-
-    let rel_oid = ber_read_relative_oid(&i[index..])?;
-    oid.extend(&rel_oid);
-    Ok(oid)
-}
-
 /// Read an object header
-pub fn ber_read_element_header(i: &[u8]) -> IResult<&[u8], BerObjectHeader> {
+pub fn ber_read_element_header(i: &[u8]) -> BerResult<BerObjectHeader> {
     do_parse! {
         i,
         el:   parse_identifier >>
         len:  parse_ber_length_byte >>
-        llen: cond!(len.0 == 1, take!(len.1)) >>
+        llen: cond!(len.0 == 1, apply!(ber_take, len.1 as usize)) >>
         ( {
-            let len : u64 = match len.0 {
-                0 => len.1 as u64,
+            let len = match (len.0, len.1) {
+                (0, l) => BerSize::Definite(l as usize),
+                // indefinite form (8.1.3.6)
+                (_, 0) => BerSize::Indefinite,
                 _ => {
                     // if len is 0xff -> error (8.1.3.5)
                     if len.1 == 0b0111_1111 {
-                        return Err(::nom::Err::Error(error_position!(&i[1..], ErrorKind::Custom(BER_INVALID_LENGTH))));
+                        return Err(::nom::Err::Error(error_position!(&i[1..], BerError::InvalidLength.into_error_kind())));
                     }
-                    // XXX llen: test if 0 (indefinite form), if len is 0xff -> error
                     match bytes_to_u64(llen.unwrap()) {
-                        Ok(l)  => l,
-                        Err(_) => { return Err(::nom::Err::Error(error_position!(llen.unwrap(), ErrorKind::Custom(BER_TAG_ERROR)))); },
+                        Ok(l) if l <= ::std::usize::MAX as u64 => BerSize::Definite(l as usize),
+                        Ok(_)  => { return Err(::nom::Err::Error(error_position!(llen.unwrap(), BerError::InvalidLength.into_error_kind()))); },
+                        Err(_) => { return Err(::nom::Err::Error(error_position!(llen.unwrap(), BerError::InvalidLength.into_error_kind()))); },
                     }
                 },
             };
@@ -143,203 +157,404 @@ pub fn ber_read_element_header(i: &[u8]) -> IResult<&[u8], BerObjectHeader> {
 }
 
 #[inline]
-pub(crate) fn ber_read_content_eoc(i: &[u8]) -> IResult<&[u8], BerObjectContent> {
+pub(crate) fn ber_read_content_eoc(i: &[u8]) -> BerResult<BerObjectContent> {
     Ok((i, BerObjectContent::EndOfContent))
 }
 
 #[inline]
-pub(crate) fn ber_read_content_bool(i: &[u8]) -> IResult<&[u8], BerObjectContent> {
-    match be_u8(i) {
-        Ok((rem, 0)) => Ok((rem, BerObjectContent::Boolean(false))),
-        Ok((rem, _)) => Ok((rem, BerObjectContent::Boolean(true))),
-        Err(e) => Err(e),
-    }
+pub(crate) fn ber_read_content_bool(i: &[u8]) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, 1), |b| BerObjectContent::Boolean(b[0] != 0))
 }
 
 #[inline]
-pub(crate) fn ber_read_content_integer(i: &[u8], len: usize) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |i| { BerObjectContent::Integer(i) })
+pub(crate) fn ber_read_content_integer(i: &[u8], len: usize) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |i| { BerObjectContent::Integer(Cow::Borrowed(i)) })
 }
 
-// XXX check if constructed (8.6.3)
 #[inline]
-pub(crate) fn ber_read_content_bitstring(i: &[u8], len: usize) -> IResult<&[u8], BerObjectContent> {
+pub(crate) fn ber_read_content_bitstring(i: &[u8], len: usize) -> BerResult<BerObjectContent> {
     do_parse! {
         i,
-        ignored_bits: be_u8 >>
-                      error_if!(len == 0, ErrorKind::Custom(BER_INVALID_LENGTH)) >>
-        s:            take!(len - 1) >>
-        ( BerObjectContent::BitString(ignored_bits,BitStringObject{ data:s }) )
+        ignored_bits: map!(apply!(ber_take, 1), |b| b[0]) >>
+                      error_if!(len == 0, BerError::InvalidLength.into_error_kind()) >>
+        s:            apply!(ber_take, len - 1) >>
+        ( BerObjectContent::BitString(ignored_bits,BitStringObject{ data:Cow::Borrowed(s) }) )
     }
 }
 
-// XXX check if constructed (8.7)
 #[inline]
 pub(crate) fn ber_read_content_octetstring(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |s| BerObjectContent::OctetString(s))
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::OctetString(Cow::Borrowed(s)))
 }
 
 #[inline]
-pub(crate) fn ber_read_content_null(i: &[u8]) -> IResult<&[u8], BerObjectContent> {
+pub(crate) fn ber_read_content_null(i: &[u8]) -> BerResult<BerObjectContent> {
     Ok((i, BerObjectContent::Null))
 }
 
 // XXX check if primitive (8.19.1)
 #[inline]
-pub(crate) fn ber_read_content_oid(i: &[u8], len: usize) -> IResult<&[u8], BerObjectContent> {
-    do_parse! {
-        i,
-             error_if!(len == 0, ErrorKind::LengthValue) >>
-        oid: map_res!(take!(len),ber_read_oid) >>
-        ( BerObjectContent::OID(Oid::from(&oid)) )
+pub(crate) fn ber_read_content_oid(i: &[u8], len: usize) -> BerResult<BerObjectContent> {
+    error_if!(i, len == 0, BerError::InvalidLength.into_error_kind())?;
+    let (rem, s) = ber_take(i, len)?;
+    match Oid::from_ber_content(s) {
+        Ok(oid) => Ok((rem, BerObjectContent::OID(oid))),
+        Err(e) => Err(Err::Error(error_position!(i, e.into_error_kind()))),
     }
 }
 
 // XXX check if primitive (8.4)
 #[inline]
-pub(crate) fn ber_read_content_enum(i: &[u8], len: usize) -> IResult<&[u8], BerObjectContent> {
-    parse_hex_to_u64!(i, len).map(|(rem, i)| (rem, BerObjectContent::Enum(i)))
+pub(crate) fn ber_read_content_enum(i: &[u8], len: usize) -> BerResult<BerObjectContent> {
+    let (rem, s) = ber_take(i, len)?;
+    // the value of an enumerated type is encoded as a signed integer (8.4)
+    let v = bytes_to_i128(s).and_then(|v| {
+        if v < ::std::i64::MIN as i128 || v > ::std::i64::MAX as i128 {
+            Err(BerError::IntegerTooLarge)
+        } else {
+            Ok(v as i64)
+        }
+    });
+    match v {
+        Ok(v) => Ok((rem, BerObjectContent::Enum(v))),
+        Err(e) => Err(Err::Error(error_position!(i, e.into_error_kind()))),
+    }
+}
+
+pub(crate) fn ber_read_content_real(i: &[u8], len: usize) -> BerResult<BerObjectContent> {
+    let (rem, s) = ber_take(i, len)?;
+    match decode_real(s) {
+        Ok(f) => Ok((rem, BerObjectContent::Real(f))),
+        Err(e) => Err(Err::Error(error_position!(i, e.into_error_kind()))),
+    }
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
 pub(crate) fn ber_read_content_utf8string(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |s| BerObjectContent::UTF8String(s))
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::UTF8String(Cow::Borrowed(s)))
 }
 
 #[inline]
 pub(crate) fn ber_read_content_relativeoid(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    do_parse! {
-        i,
-             error_if!(len == 0, ErrorKind::LengthValue) >>
-        oid: map_res!(take!(len), ber_read_relative_oid) >>
-        ( BerObjectContent::RelativeOID(Oid::from(&oid)) )
+) -> BerResult<BerObjectContent> {
+    error_if!(i, len == 0, BerError::InvalidLength.into_error_kind())?;
+    let (rem, s) = ber_take(i, len)?;
+    match RelativeOid::from_ber_content(s) {
+        Ok(oid) => Ok((rem, BerObjectContent::RelativeOID(oid))),
+        Err(e) => Err(Err::Error(error_position!(i, e.into_error_kind()))),
+    }
+}
+
+/// Read objects until the end of the input
+///
+/// Unlike `many0!`, an invalid object is an error, and is not silently discarded.
+fn ber_read_objects(i: &[u8], depth: usize) -> BerResult<Vec<BerObject>> {
+    let mut v = Vec::new();
+    let mut rem = i;
+    while !rem.is_empty() {
+        match parse_ber_recursive(rem, depth) {
+            Ok((r, obj)) => {
+                v.push(obj);
+                rem = r;
+            }
+            // object is larger than the container
+            Err(Err::Incomplete(_)) => {
+                return Err(Err::Error(error_position!(
+                    rem,
+                    BerError::InvalidLength.into_error_kind()
+                )))
+            }
+            Err(e) => return Err(e),
+        }
     }
+    Ok((rem, v))
 }
 
 #[inline]
 pub(crate) fn ber_read_content_sequence(
     i: &[u8],
-    len: usize,
+    len: BerSize,
     depth: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    if len == 0 {
-        // indefinite form
-        // read until end-of-content
-        map!(
-            i,
-            many_till!(
-                apply!(parse_ber_recursive, depth + 1),
-                parse_ber_endofcontent
-            ),
-            |(l, _)| { BerObjectContent::Sequence(l) }
-        )
-    } else {
-        map!(
+) -> BerResult<BerObjectContent> {
+    match len {
+        BerSize::Indefinite => {
+            // indefinite form
+            // read until end-of-content
+            map!(
+                i,
+                many_till!(
+                    apply!(parse_ber_recursive, depth + 1),
+                    ber_read_eoc
+                ),
+                |(l, _)| { BerObjectContent::Sequence(l) }
+            )
+        }
+        BerSize::Definite(len) => map!(
             i,
-            flat_take!(
-                len,
-                many0!(complete!(apply!(parse_ber_recursive, depth + 1)))
-            ),
+            flat_map!(apply!(ber_take, len), apply!(ber_read_objects, depth + 1)),
             |l| { BerObjectContent::Sequence(l) }
-        )
+        ),
     }
 }
 
 #[inline]
 pub(crate) fn ber_read_content_set(
     i: &[u8],
-    len: usize,
+    len: BerSize,
     depth: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    if len == 0 {
-        // indefinite form
-        // read until end-of-content
-        map!(
-            i,
-            many_till!(
-                apply!(parse_ber_recursive, depth + 1),
-                parse_ber_endofcontent
-            ),
-            |(l, _)| { BerObjectContent::Set(l) }
-        )
-    } else {
-        map!(
+) -> BerResult<BerObjectContent> {
+    match len {
+        BerSize::Indefinite => {
+            // indefinite form
+            // read until end-of-content
+            map!(
+                i,
+                many_till!(
+                    apply!(parse_ber_recursive, depth + 1),
+                    ber_read_eoc
+                ),
+                |(l, _)| { BerObjectContent::Set(l) }
+            )
+        }
+        BerSize::Definite(len) => map!(
             i,
-            flat_take!(
-                len,
-                many0!(complete!(apply!(parse_ber_recursive, depth + 1)))
-            ),
+            flat_map!(apply!(ber_take, len), apply!(ber_read_objects, depth + 1)),
             |l| { BerObjectContent::Set(l) }
-        )
+        ),
     }
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
 pub(crate) fn ber_read_content_numericstring(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |s| BerObjectContent::NumericString(s))
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::NumericString(Cow::Borrowed(s)))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
 pub(crate) fn ber_read_content_printablestring(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |s| BerObjectContent::PrintableString(s))
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::PrintableString(Cow::Borrowed(s)))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
-pub(crate) fn ber_read_content_t61string(i: &[u8], len: usize) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |s| BerObjectContent::T61String(s))
+pub(crate) fn ber_read_content_t61string(i: &[u8], len: usize) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::T61String(Cow::Borrowed(s)))
+}
+
+#[inline]
+pub(crate) fn ber_read_content_videotexstring(
+    i: &[u8],
+    len: usize,
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::VideotexString(Cow::Borrowed(s)))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
-pub(crate) fn ber_read_content_ia5string(i: &[u8], len: usize) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |s| BerObjectContent::IA5String(s))
+pub(crate) fn ber_read_content_ia5string(i: &[u8], len: usize) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::IA5String(Cow::Borrowed(s)))
 }
 
 #[inline]
-pub(crate) fn ber_read_content_utctime(i: &[u8], len: usize) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |s| BerObjectContent::UTCTime(s))
+pub(crate) fn ber_read_content_utctime(i: &[u8], len: usize) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::UTCTime(Cow::Borrowed(s)))
 }
 
 #[inline]
 pub(crate) fn ber_read_content_generalizedtime(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |s| BerObjectContent::GeneralizedTime(s))
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::GeneralizedTime(Cow::Borrowed(s)))
+}
+
+/// Read the content of a TIME, DATE, TIME-OF-DAY, DATE-TIME or DURATION object
+///
+/// The value is not decoded, see `BerObject::as_time` and similar functions.
+#[inline]
+pub(crate) fn ber_read_content_iso8601(
+    i: &[u8],
+    tag: BerTag,
+    len: usize,
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| {
+        let s = Cow::Borrowed(s);
+        match tag {
+            BerTag::Time => BerObjectContent::Time(s),
+            BerTag::Date => BerObjectContent::Date(s),
+            BerTag::TimeOfDay => BerObjectContent::TimeOfDay(s),
+            BerTag::DateTime => BerObjectContent::DateTime(s),
+            _ => BerObjectContent::Duration(s),
+        }
+    })
+}
+
+#[inline]
+pub(crate) fn ber_read_content_graphicstring(
+    i: &[u8],
+    len: usize,
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::GraphicString(Cow::Borrowed(s)))
+}
+
+#[inline]
+pub(crate) fn ber_read_content_visiblestring(
+    i: &[u8],
+    len: usize,
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::VisibleString(Cow::Borrowed(s)))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
 pub(crate) fn ber_read_content_generalstring(
     i: &[u8],
     len: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |s| BerObjectContent::GeneralString(s))
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::GeneralString(Cow::Borrowed(s)))
+}
+
+#[inline]
+pub(crate) fn ber_read_content_universalstring(
+    i: &[u8],
+    len: usize,
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::UniversalString(Cow::Borrowed(s)))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
-pub(crate) fn ber_read_content_bmpstring(i: &[u8], len: usize) -> IResult<&[u8], BerObjectContent> {
-    map!(i, take!(len), |s| BerObjectContent::BmpString(s))
+pub(crate) fn ber_read_content_bmpstring(i: &[u8], len: usize) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::BmpString(Cow::Borrowed(s)))
+}
+
+#[inline]
+pub(crate) fn ber_read_content_objectdescriptor(
+    i: &[u8],
+    len: usize,
+) -> BerResult<BerObjectContent> {
+    map!(i, apply!(ber_take, len), |s| BerObjectContent::ObjectDescriptor(Cow::Borrowed(s)))
+}
+
+/// Read a segment of a constructed string, expecting a value with tag `tag`
+fn ber_read_segment(i: &[u8], tag: BerTag, depth: usize) -> BerResult {
+    error_if!(i, depth > MAX_RECURSION, BerError::BerMaxDepth.into_error_kind())?;
+    do_parse! {
+        i,
+        hdr: ber_read_element_header >>
+             error_if!(hdr.class != 0b00, BerError::UnexpectedClass{ expected: 0, actual: hdr.class }.into_error_kind()) >>
+             error_if!(hdr.tag != tag, BerError::UnexpectedTag{ expected: Some(tag), actual: hdr.tag }.into_error_kind()) >>
+        o:   apply!(ber_read_element_content_as, hdr.tag, hdr.len, hdr.is_constructed(), depth) >>
+        ( BerObject::from_header_and_content(hdr, o) )
+    }
+}
+
+/// Read the segments of a constructed string of type `tag`
+///
+/// Segments of a BIT STRING are bit strings, and segments of all other types are octet strings
+/// (8.6.4.1, 8.7.3.2 and 8.23.6). Segments can also be constructed.
+fn ber_read_segments(
+    i: &[u8],
+    tag: BerTag,
+    len: BerSize,
+    depth: usize,
+) -> BerResult<Vec<BerObject>> {
+    let seg_tag = if tag == BerTag::BitString {
+        BerTag::BitString
+    } else {
+        BerTag::OctetString
+    };
+    match len {
+        BerSize::Indefinite => {
+            // indefinite form
+            // read until end-of-content
+            map!(
+                i,
+                many_till!(
+                    apply!(ber_read_segment, seg_tag, depth + 1),
+                    ber_read_eoc
+                ),
+                |(l, _)| l
+            )
+        }
+        BerSize::Definite(len) => flat_take!(
+            i,
+            len,
+            do_parse!(
+                l: many0!(ber_complete!(apply!(ber_read_segment, seg_tag, depth + 1))) >>
+                   ber_empty!() >>
+                ( l )
+            )
+        ),
+    }
+}
+
+/// Concatenate the (already reassembled) segments of a string of type `tag`
+fn ber_assemble_segments<'a>(
+    i: &'a [u8],
+    tag: BerTag,
+    segments: &[BerObject<'a>],
+) -> BerResult<'a, BerObjectContent<'a>> {
+    let mut ignored_bits = 0;
+    let mut parts = Vec::with_capacity(segments.len());
+    for (idx, seg) in segments.iter().enumerate() {
+        match seg.content {
+            BerObjectContent::BitString(u, ref b) => {
+                // only the last segment can have unused bits (8.6.4.1)
+                error_if!(i, u != 0 && idx + 1 != segments.len(), BerError::InvalidValue.into_error_kind())?;
+                ignored_bits = u;
+                parts.push(&b.data);
+            }
+            BerObjectContent::OctetString(ref s) => parts.push(s),
+            _ => return Err(Err::Error(error_position!(i, BerError::UnexpectedTag{ expected: None, actual: seg.tag }.into_error_kind()))),
+        }
+    }
+    // avoid copying data if there is only one segment
+    let data = match parts.len() {
+        1 => parts[0].clone(),
+        _ => Cow::Owned(parts.iter().flat_map(|s| s.iter().cloned()).collect()),
+    };
+    let content = match tag {
+        BerTag::BitString       => BerObjectContent::BitString(ignored_bits, BitStringObject{ data }),
+        BerTag::OctetString     => BerObjectContent::OctetString(data),
+        BerTag::Utf8String      => BerObjectContent::UTF8String(data),
+        BerTag::NumericString   => BerObjectContent::NumericString(data),
+        BerTag::PrintableString => BerObjectContent::PrintableString(data),
+        BerTag::T61String       => BerObjectContent::T61String(data),
+        BerTag::VideotexString  => BerObjectContent::VideotexString(data),
+        BerTag::GraphicString   => BerObjectContent::GraphicString(data),
+        BerTag::VisibleString   => BerObjectContent::VisibleString(data),
+        BerTag::UniversalString => BerObjectContent::UniversalString(data),
+        BerTag::ObjDescriptor   => BerObjectContent::ObjectDescriptor(data),
+        BerTag::Ia5String       => BerObjectContent::IA5String(data),
+        BerTag::UtcTime         => BerObjectContent::UTCTime(data),
+        BerTag::GeneralizedTime => BerObjectContent::GeneralizedTime(data),
+        BerTag::GeneralString   => BerObjectContent::GeneralString(data),
+        BerTag::BmpString       => BerObjectContent::BmpString(data),
+        _ => return Err(Err::Error(error_position!(i, BerError::UnknownTag.into_error_kind()))),
+    };
+    Ok((i, content))
+}
+
+/// Read a constructed string, and reassemble the segments into a single value
+pub(crate) fn ber_read_content_segmented(
+    i: &[u8],
+    tag: BerTag,
+    len: BerSize,
+    depth: usize,
+) -> BerResult<BerObjectContent> {
+    let (rem, segments) = ber_read_segments(i, tag, len, depth)?;
+    let (_, content) = ber_assemble_segments(i, tag, &segments)?;
+    Ok((rem, content))
 }
 
 /// Parse the next bytes as the content of a BER object.
@@ -348,134 +563,196 @@ pub(crate) fn ber_read_content_bmpstring(i: &[u8], len: usize) -> IResult<&[u8],
 pub fn ber_read_element_content_as(
     i: &[u8],
     tag: BerTag,
-    len: usize,
+    len: BerSize,
     constructed: bool,
     depth: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    if i.len() < len {
-        return Err(Err::Incomplete(Needed::Size(len)));
-    }
+) -> BerResult<BerObjectContent> {
+    // the indefinite form is only allowed for constructed objects (8.1.3.2), and the types reading
+    // `plen` below are primitive, so `plen` is always a definite length
+    let plen = match len {
+        BerSize::Definite(l) => {
+            if i.len() < l {
+                return Err(Err::Incomplete(Needed::Size(l)));
+            }
+            l
+        }
+        BerSize::Indefinite => {
+            error_if!(i, !constructed, BerError::InvalidLength.into_error_kind())?;
+            0
+        }
+    };
     match tag {
+        // constructed strings: read and reassemble segments (8.6.4, 8.7.3 and 8.23.6)
+        BerTag::BitString
+        | BerTag::OctetString
+        | BerTag::Utf8String
+        | BerTag::NumericString
+        | BerTag::PrintableString
+        | BerTag::T61String
+        | BerTag::VideotexString
+        | BerTag::Ia5String
+        | BerTag::UtcTime
+        | BerTag::GeneralizedTime
+        | BerTag::GraphicString
+        | BerTag::VisibleString
+        | BerTag::GeneralString
+        | BerTag::UniversalString
+        | BerTag::BmpString
+        | BerTag::ObjDescriptor
+            if constructed =>
+        {
+            ber_read_content_segmented(i, tag, len, depth)
+        }
         // 0x00 end-of-content
         BerTag::EndOfContent => {
-            error_if!(i, len != 0, ErrorKind::Custom(BER_INVALID_LENGTH))?;
+            error_if!(i, len != BerSize::Definite(0), BerError::InvalidLength.into_error_kind())?;
             ber_read_content_eoc(i)
         }
         // 0x01 bool
         BerTag::Boolean => {
-            error_if!(i, len != 1, ErrorKind::Custom(BER_INVALID_LENGTH))?;
+            error_if!(i, len != BerSize::Definite(1), BerError::InvalidLength.into_error_kind())?;
             ber_read_content_bool(i)
         }
         // 0x02
         BerTag::Integer => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_STRUCT_ERROR))?;
-            ber_read_content_integer(i, len)
+            error_if!(i, constructed, BerError::ConstructUnexpected.into_error_kind())?;
+            ber_read_content_integer(i, plen)
         }
         // 0x03: bitstring
-        BerTag::BitString => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_UNSUPPORTED))?; // XXX valid in BER
-            ber_read_content_bitstring(i, len)
-        }
+        BerTag::BitString => ber_read_content_bitstring(i, plen),
         // 0x04: octetstring
-        BerTag::OctetString => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_UNSUPPORTED))?; // XXX valid in BER
-            ber_read_content_octetstring(i, len)
-        }
+        BerTag::OctetString => ber_read_content_octetstring(i, plen),
         // 0x05: null
         BerTag::Null => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_STRUCT_ERROR))?;
-            error_if!(i, len != 0, ErrorKind::Custom(BER_INVALID_LENGTH))?;
+            error_if!(i, constructed, BerError::ConstructUnexpected.into_error_kind())?;
+            error_if!(i, plen != 0, BerError::InvalidLength.into_error_kind())?;
             ber_read_content_null(i)
         }
         // 0x06: object identified
         BerTag::Oid => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_STRUCT_ERROR))?;
-            ber_read_content_oid(i, len)
+            error_if!(i, constructed, BerError::ConstructUnexpected.into_error_kind())?;
+            ber_read_content_oid(i, plen)
+        }
+        // 0x07: object descriptor
+        BerTag::ObjDescriptor => ber_read_content_objectdescriptor(i, plen),
+        // 0x08: external
+        BerTag::External => {
+            error_if!(i, !constructed, BerError::ConstructExpected.into_error_kind())?;
+            ber_read_content_external(i, len, depth)
+        }
+        // 0x09: real
+        BerTag::RealType => {
+            error_if!(i, constructed, BerError::ConstructUnexpected.into_error_kind())?;
+            ber_read_content_real(i, plen)
         }
         // 0x0a: enumerated
         BerTag::Enumerated => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_STRUCT_ERROR))?;
-            ber_read_content_enum(i, len)
+            error_if!(i, constructed, BerError::ConstructUnexpected.into_error_kind())?;
+            ber_read_content_enum(i, plen)
         }
-        // 0x0c: UTF8String
-        BerTag::Utf8String => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_UNSUPPORTED))?; // XXX valid in BER
-            ber_read_content_utf8string(i, len)
+        // 0x0b: embedded pdv
+        BerTag::EmbeddedPdv => {
+            error_if!(i, !constructed, BerError::ConstructExpected.into_error_kind())?;
+            ber_read_content_embeddedpdv(i, len, depth)
         }
+        // 0x0c: UTF8String
+        BerTag::Utf8String => ber_read_content_utf8string(i, plen),
         // 0x0d: relative object identified
         BerTag::RelativeOid => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_STRUCT_ERROR))?;
-            ber_read_content_relativeoid(i, len)
+            error_if!(i, constructed, BerError::ConstructUnexpected.into_error_kind())?;
+            ber_read_content_relativeoid(i, plen)
+        }
+        // 0x0e: time, 0x1f: date, 0x20: time-of-day, 0x21: date-time, 0x22: duration
+        BerTag::Time
+        | BerTag::Date
+        | BerTag::TimeOfDay
+        | BerTag::DateTime
+        | BerTag::Duration => {
+            error_if!(i, constructed, BerError::ConstructUnexpected.into_error_kind())?;
+            ber_read_content_iso8601(i, tag, plen)
         }
         // 0x10: sequence
         BerTag::Sequence => {
-            error_if!(i, !constructed, ErrorKind::Custom(BER_STRUCT_ERROR))?;
+            error_if!(i, !constructed, BerError::ConstructExpected.into_error_kind())?;
             ber_read_content_sequence(i, len, depth)
         }
         // 0x11: set
         BerTag::Set => {
-            error_if!(i, !constructed, ErrorKind::Custom(BER_STRUCT_ERROR))?;
+            error_if!(i, !constructed, BerError::ConstructExpected.into_error_kind())?;
             ber_read_content_set(i, len, depth)
         }
         // 0x12: numericstring
-        BerTag::NumericString => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_UNSUPPORTED))?; // XXX valid in BER
-            ber_read_content_numericstring(i, len)
-        }
+        BerTag::NumericString => ber_read_content_numericstring(i, plen),
         // 0x13: printablestring
-        BerTag::PrintableString => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_UNSUPPORTED))?; // XXX valid in BER
-            ber_read_content_printablestring(i, len)
-        }
+        BerTag::PrintableString => ber_read_content_printablestring(i, plen),
         // 0x14: t61string
-        BerTag::T61String => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_UNSUPPORTED))?; // XXX valid in BER
-            ber_read_content_t61string(i, len)
-        }
+        BerTag::T61String => ber_read_content_t61string(i, plen),
+        // 0x15: videotexstring
+        BerTag::VideotexString => ber_read_content_videotexstring(i, plen),
         // 0x16: ia5string
-        BerTag::Ia5String => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_UNSUPPORTED))?; // XXX valid in BER
-            ber_read_content_ia5string(i, len)
-        }
+        BerTag::Ia5String => ber_read_content_ia5string(i, plen),
         // 0x17: utctime
-        BerTag::UtcTime => ber_read_content_utctime(i, len),
+        BerTag::UtcTime => ber_read_content_utctime(i, plen),
         // 0x18: generalizedtime
-        BerTag::GeneralizedTime => ber_read_content_generalizedtime(i, len),
+        BerTag::GeneralizedTime => ber_read_content_generalizedtime(i, plen),
+        // 0x19: graphicstring
+        BerTag::GraphicString => ber_read_content_graphicstring(i, plen),
+        // 0x1a: visiblestring
+        BerTag::VisibleString => ber_read_content_visiblestring(i, plen),
         // 0x1b: generalstring
-        BerTag::GeneralString => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_UNSUPPORTED))?; // XXX valid in BER
-            ber_read_content_generalstring(i, len)
+        BerTag::GeneralString => ber_read_content_generalstring(i, plen),
+        // 0x1c: universalstring
+        BerTag::UniversalString => ber_read_content_universalstring(i, plen),
+        // 0x1d: character string
+        BerTag::CharacterString => {
+            error_if!(i, !constructed, BerError::ConstructExpected.into_error_kind())?;
+            ber_read_content_characterstring(i, len, depth)
         }
         // 0x1e: bmpstring
-        BerTag::BmpString => {
-            error_if!(i, constructed, ErrorKind::Custom(BER_UNSUPPORTED))?; // XXX valid in BER
-            ber_read_content_bmpstring(i, len)
-        }
+        BerTag::BmpString => ber_read_content_bmpstring(i, plen),
         // all unknown values
         _ => Err(Err::Error(error_position!(
             i,
-            ErrorKind::Custom(BER_TAG_UNKNOWN)
+            BerError::UnknownTag.into_error_kind()
         ))),
     }
 }
 
 /// Parse a BER object, expecting a value with specificed tag
-pub fn parse_ber_with_tag(i: &[u8], tag: BerTag) -> IResult<&[u8], BerObject> {
-    do_parse! {
+pub fn parse_ber_with_tag(i: &[u8], tag: BerTag) -> BerResult {
+    ber_locate_error(
         i,
-        hdr: ber_read_element_header >>
-             error_if!(hdr.tag != tag, ErrorKind::Custom(BER_TAG_ERROR)) >>
-        o:   apply!(ber_read_element_content_as, hdr.tag, hdr.len as usize, hdr.is_constructed(), 0) >>
-        ( BerObject::from_header_and_content(hdr, o) )
-    }
+        do_parse! {
+            i,
+            hdr: ber_read_element_header >>
+                 error_if!(hdr.tag != tag, BerError::UnexpectedTag{ expected: Some(tag), actual: hdr.tag }.into_error_kind()) >>
+            o:   apply!(ber_read_element_content_as, hdr.tag, hdr.len, hdr.is_constructed(), 0) >>
+            ( BerObject::from_header_and_content(hdr, o) )
+        },
+    )
 }
 
 /// Read end of content marker
 #[inline]
-pub fn parse_ber_endofcontent(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_endofcontent(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::EndOfContent)
 }
 
+/// Read end of content marker, without locating the error
+///
+/// Used to find the end of indefinite length objects: most calls fail, and `parse_ber_endofcontent`
+/// would parse the input again to build the path of each error.
+pub(crate) fn ber_read_eoc(i: &[u8]) -> BerResult<()> {
+    let (rem, hdr) = ber_read_element_header(i)?;
+    error_if!(
+        i,
+        hdr.tag != BerTag::EndOfContent,
+        BerError::UnexpectedTag { expected: Some(BerTag::EndOfContent), actual: hdr.tag }.into_error_kind()
+    )?;
+    error_if!(i, hdr.len != BerSize::Definite(0), BerError::InvalidLength.into_error_kind())?;
+    Ok((rem, ()))
+}
+
 /// Read a boolean value
 ///
 /// The encoding of a boolean value shall be primitive. The contents octets shall consist of a
@@ -484,7 +761,7 @@ pub fn parse_ber_endofcontent(i: &[u8]) -> IResult<&[u8], BerObject> {
 /// If the boolean value is FALSE, the octet shall be zero.
 /// If the boolean value is TRUE, the octet shall be one byte, and have all bits set to one (0xff).
 #[inline]
-pub fn parse_ber_bool(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_bool(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::Boolean)
 }
 
@@ -508,10 +785,11 @@ pub fn parse_ber_bool(i: &[u8]) -> IResult<&[u8], BerObject> {
 /// # use nom::IResult;
 /// # use der_parser::ber::parse_ber_integer;
 /// # use der_parser::ber::{BerObject,BerObjectContent};
+/// # use std::borrow::Cow;
 /// # fn main() {
 /// let empty = &b""[..];
 /// let bytes = [0x02, 0x03, 0x01, 0x00, 0x01];
-/// let expected  = BerObject::from_obj(BerObjectContent::Integer(b"\x01\x00\x01"));
+/// let expected  = BerObject::from_obj(BerObjectContent::Integer(Cow::Borrowed(b"\x01\x00\x01")));
 /// assert_eq!(
 ///     parse_ber_integer(&bytes),
 ///     Ok((empty, expected))
@@ -519,49 +797,57 @@ pub fn parse_ber_bool(i: &[u8]) -> IResult<&[u8], BerObject> {
 /// # }
 /// ```
 #[inline]
-pub fn parse_ber_integer(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_integer(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::Integer)
 }
 
 /// Read an bitstring value
 #[inline]
-pub fn parse_ber_bitstring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_bitstring(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::BitString)
 }
 
 /// Read an octetstring value
 #[inline]
-pub fn parse_ber_octetstring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_octetstring(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::OctetString)
 }
 
 /// Read a null value
 #[inline]
-pub fn parse_ber_null(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_null(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::Null)
 }
 
 /// Read an object identifier value
 #[inline]
-pub fn parse_ber_oid(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_oid(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::Oid)
 }
 
+/// Read a real value
+///
+/// Binary, decimal and special value encodings are accepted (X.690 section 8.5).
+#[inline]
+pub fn parse_ber_real(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::RealType)
+}
+
 /// Read an enumerated value
 #[inline]
-pub fn parse_ber_enum(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_enum(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::Enumerated)
 }
 
 /// Read a UTF-8 string value
 #[inline]
-pub fn parse_ber_utf8string(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_utf8string(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::Utf8String)
 }
 
 /// Read a relative object identifier value
 #[inline]
-pub fn parse_ber_relative_oid(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_relative_oid(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::RelativeOid)
 }
 
@@ -574,7 +860,7 @@ pub fn parse_ber_relative_oid(i: &[u8]) -> IResult<&[u8], BerObject> {
 /// To read a specific sequence of objects (giving the expected types), use the
 /// [`parse_ber_sequence_defined`](macro.parse_ber_sequence_defined.html) macro.
 #[inline]
-pub fn parse_ber_sequence(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_sequence(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::Sequence)
 }
 
@@ -587,139 +873,317 @@ pub fn parse_ber_sequence(i: &[u8]) -> IResult<&[u8], BerObject> {
 /// To read a specific set of objects (giving the expected types), use the
 /// [`parse_ber_set_defined`](macro.parse_ber_set_defined.html) macro.
 #[inline]
-pub fn parse_ber_set(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_set(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::Set)
 }
 
 /// Read a numeric string value
 #[inline]
-pub fn parse_ber_numericstring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_numericstring(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::NumericString)
 }
 
 /// Read a printable string value
 #[inline]
-pub fn parse_ber_printablestring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_printablestring(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::PrintableString)
 }
 
 /// Read a T61 string value
 #[inline]
-pub fn parse_ber_t61string(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_t61string(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::T61String)
 }
 
+/// Read a Videotex string value
+#[inline]
+pub fn parse_ber_videotexstring(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::VideotexString)
+}
+
 /// Read an IA5 string value
 #[inline]
-pub fn parse_ber_ia5string(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_ia5string(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::Ia5String)
 }
 
 /// Read an UTC time value
 #[inline]
-pub fn parse_ber_utctime(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_utctime(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::UtcTime)
 }
 
 /// Read a Generalized time value
 #[inline]
-pub fn parse_ber_generalizedtime(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_generalizedtime(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::GeneralizedTime)
 }
 
+/// Read a TIME value
+#[inline]
+pub fn parse_ber_time(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::Time)
+}
+
+/// Read a DATE value
+#[inline]
+pub fn parse_ber_date(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::Date)
+}
+
+/// Read a TIME-OF-DAY value
+#[inline]
+pub fn parse_ber_timeofday(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::TimeOfDay)
+}
+
+/// Read a DATE-TIME value
+#[inline]
+pub fn parse_ber_datetime(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::DateTime)
+}
+
+/// Read a DURATION value
+#[inline]
+pub fn parse_ber_duration(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::Duration)
+}
+
+/// Read a GraphicString value
+#[inline]
+pub fn parse_ber_graphicstring(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::GraphicString)
+}
+
+/// Read a VisibleString value
+#[inline]
+pub fn parse_ber_visiblestring(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::VisibleString)
+}
+
 /// Read a GeneralString value
 #[inline]
-pub fn parse_ber_generalstring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_generalstring(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::GeneralString)
 }
 
+/// Read a UniversalString value
+#[inline]
+pub fn parse_ber_universalstring(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::UniversalString)
+}
+
+/// Read an unrestricted CHARACTER STRING value
+///
+/// The value is encoded as its associated SEQUENCE type (X.690 section 8.20), and decoded as a
+/// `BerCharacterString`.
+#[inline]
+pub fn parse_ber_characterstring(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::CharacterString)
+}
+
 /// Read a BmpString value
 #[inline]
-pub fn parse_ber_bmpstring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_ber_bmpstring(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, BerTag::BmpString)
 }
 
-pub fn parse_ber_explicit_failed(i: &[u8], tag: BerTag) -> IResult<&[u8], BerObject, u32> {
-    value!(
+/// Read an ObjectDescriptor value
+#[inline]
+pub fn parse_ber_objectdescriptor(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::ObjDescriptor)
+}
+
+/// Read an EXTERNAL value
+///
+/// The value is encoded as its associated SEQUENCE type (X.690 section 8.18), and decoded as a
+/// `BerExternal`.
+#[inline]
+pub fn parse_ber_external(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::External)
+}
+
+/// Read an EMBEDDED PDV value
+///
+/// The value is encoded as its associated SEQUENCE type (X.690 section 8.19), and decoded as a
+/// `BerEmbeddedPdv`.
+#[inline]
+pub fn parse_ber_embedded_pdv(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, BerTag::EmbeddedPdv)
+}
+
+/// Read a string value, and return the segments of the encoding
+///
+/// In BER, strings (BIT STRING, OCTET STRING, character strings and time types) can use the
+/// constructed form, and be split in segments (X.690 sections 8.6.4, 8.7.3 and 8.23.6).
+/// The [`parse_ber`](fn.parse_ber.html) and `parse_ber_xxx` functions return the reassembled
+/// value. This function also returns the segments, in the order of the encoding (if a segment is
+/// constructed, its content is reassembled).
+///
+/// If the string uses the primitive form, the list of segments is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # use der_parser::ber::parse_ber_string_segments;
+/// # fn main() {
+/// let bytes = [0x24, 0x80,
+///              0x04, 0x02, 0x41, 0x42,
+///              0x04, 0x01, 0x43,
+///              0x00, 0x00];
+/// let (_, (obj, segments)) = parse_ber_string_segments(&bytes).expect("parsing failed");
+/// assert_eq!(obj.as_slice(), Ok(&b"ABC"[..]));
+/// assert_eq!(segments.len(), 2);
+/// assert_eq!(segments[1].as_slice(), Ok(&b"C"[..]));
+/// # }
+/// ```
+pub fn parse_ber_string_segments(i: &[u8]) -> BerResult<(BerObject, Vec<BerObject>)> {
+    ber_locate_error(i, ber_read_string_segments(i))
+}
+
+fn ber_read_string_segments(i: &[u8]) -> BerResult<(BerObject, Vec<BerObject>)> {
+    let (rem, hdr) = ber_read_element_header(i)?;
+    error_if!(i, hdr.class != 0b00, BerError::UnexpectedClass{ expected: 0, actual: hdr.class }.into_error_kind())?;
+    if !hdr.is_constructed() {
+        return map!(
+            rem,
+            apply!(ber_read_element_content_as, hdr.tag, hdr.len, false, 0),
+            |c| (BerObject::from_header_and_content(hdr, c), Vec::new())
+        );
+    }
+    let (rem2, segments) = ber_read_segments(rem, hdr.tag, hdr.len, 0)?;
+    let (_, content) = ber_assemble_segments(rem, hdr.tag, &segments)?;
+    Ok((rem2, (BerObject::from_header_and_content(hdr, content), segments)))
+}
+
+pub fn parse_ber_explicit_failed(i: &[u8], tag: BerTag) -> BerResult {
+    Ok((
         i,
-        BerObject::from_obj(BerObjectContent::ContextSpecific(tag, None))
-    )
+        BerObject::from_obj(BerObjectContent::ContextSpecific(tag, None)),
+    ))
 }
 
-pub fn parse_ber_explicit<F>(i: &[u8], tag: BerTag, f: F) -> IResult<&[u8], BerObject, u32>
+pub fn parse_ber_explicit<F>(i: &[u8], tag: BerTag, f: F) -> BerResult
 where
-    F: Fn(&[u8]) -> IResult<&[u8], BerObject, u32>,
+    F: Fn(&[u8]) -> BerResult,
 {
-    alt_complete! {
+    ber_locate_error(
         i,
-        do_parse!(
-            hdr:     ber_read_element_header >>
-            error_if!(hdr.tag != tag, ErrorKind::Custom(BER_TAG_ERROR)) >>
-            content: f >>
-            (
-                BerObject::from_header_and_content(
-                    hdr,
-                    BerObjectContent::ContextSpecific(tag,Some(Box::new(content)))
+        alt! {
+            i,
+            ber_complete!(do_parse!(
+                hdr:     ber_read_element_header >>
+                error_if!(hdr.tag != tag, BerError::UnexpectedTag{ expected: Some(tag), actual: hdr.tag }.into_error_kind()) >>
+                content: f >>
+                // indefinite form: the inner object is followed by end-of-content
+                cond!(hdr.len.is_indefinite(), ber_read_eoc) >>
+                (
+                    BerObject::from_header_and_content(
+                        hdr,
+                        BerObjectContent::ContextSpecific(tag,Some(Box::new(content)))
+                    )
                 )
-            )
-        ) |
-        apply!(parse_ber_explicit_failed, tag)
-    }
+            )) |
+            apply!(parse_ber_explicit_failed, tag)
+        },
+    )
 }
 
 /// call der *content* parsing function
-pub fn parse_ber_implicit<F>(i: &[u8], tag: BerTag, f: F) -> IResult<&[u8], BerObject, u32>
+pub fn parse_ber_implicit<F>(i: &[u8], tag: BerTag, f: F) -> BerResult
 where
-    F: Fn(&[u8], BerTag, usize) -> IResult<&[u8], BerObjectContent, u32>,
+    F: Fn(&[u8], BerTag, BerSize) -> BerResult<BerObjectContent>,
 {
-    alt_complete! {
+    ber_locate_error(
         i,
-        do_parse!(
-            hdr:     ber_read_element_header >>
-            error_if!(hdr.tag != tag, ErrorKind::Custom(BER_TAG_ERROR)) >>
-            content: map!(
-                apply!(f, tag, hdr.len as usize),
-                |b| { BerObject::from_obj(b) }
-            ) >>
-            (
-                BerObject::from_header_and_content(
-                    hdr,
-                    BerObjectContent::ContextSpecific(tag,Some(Box::new(content)))
+        alt! {
+            i,
+            ber_complete!(do_parse!(
+                hdr:     ber_read_element_header >>
+                error_if!(hdr.tag != tag, BerError::UnexpectedTag{ expected: Some(tag), actual: hdr.tag }.into_error_kind()) >>
+                content: map!(
+                    apply!(f, tag, hdr.len),
+                    |b| { BerObject::from_obj(b) }
+                ) >>
+                (
+                    BerObject::from_header_and_content(
+                        hdr,
+                        BerObjectContent::ContextSpecific(tag,Some(Box::new(content)))
+                    )
                 )
-            )
-        ) |
-        apply!(parse_ber_explicit_failed, tag)
+            )) |
+            apply!(parse_ber_explicit_failed, tag)
+        },
+    )
+}
+
+/// Read the content octets of an object, which must use the definite length form
+///
+/// Fails with `InvalidLength` if `len` is indefinite.
+pub fn ber_read_content_definite(i: &[u8], len: BerSize) -> BerResult<&[u8]> {
+    match len {
+        BerSize::Definite(l) => ber_take(i, l),
+        BerSize::Indefinite => Err(Err::Error(error_position!(i, BerError::InvalidLength.into_error_kind()))),
     }
 }
 
-fn parse_ber_recursive(i: &[u8], depth: usize) -> IResult<&[u8], BerObject, u32> {
-    error_if!(i, depth > MAX_RECURSION, ErrorKind::Custom(BER_MAX_DEPTH))?;
+/// Read the raw content of an object of unknown type
+///
+/// For the indefinite length form, nested objects are parsed to find the matching end-of-content,
+/// which is consumed but not included in the returned content.
+fn ber_read_content_unknown(i: &[u8], len: BerSize, depth: usize) -> BerResult<&[u8]> {
+    if let BerSize::Definite(l) = len {
+        return ber_take(i, l);
+    }
+    let mut rem = i;
+    loop {
+        match ber_read_eoc(rem) {
+            Ok((r, _)) => return Ok((r, &i[..i.len() - rem.len()])),
+            Err(Err::Incomplete(n)) => return Err(Err::Incomplete(n)),
+            Err(_) => (),
+        }
+        let (r, _) = parse_ber_recursive(rem, depth + 1)?;
+        rem = r;
+    }
+}
+
+pub(crate) fn parse_ber_recursive(i: &[u8], depth: usize) -> BerResult {
+    error_if!(i, depth > MAX_RECURSION, BerError::BerMaxDepth.into_error_kind())?;
     let (rem, hdr) = ber_read_element_header(i)?;
-    error_if!(
-        i,
-        hdr.len as usize > i.len() || hdr.len > ::std::u32::MAX as u64,
-        ErrorKind::Custom(BER_INVALID_LENGTH)
-    )?;
+    match hdr.len {
+        BerSize::Definite(l) => error_if!(
+            i,
+            l > i.len() || l > ::std::u32::MAX as usize,
+            BerError::InvalidLength.into_error_kind()
+        )?,
+        // the indefinite form can only be used for constructed objects (8.1.3.2)
+        BerSize::Indefinite => error_if!(
+            i,
+            !hdr.is_constructed(),
+            BerError::InvalidLength.into_error_kind()
+        )?,
+    };
     match hdr.class {
         // universal
-        0b00 |
-        // private
-        0b11 => (),
+        0b00 => (),
         // application
         0b01 |
         // context-specific
-        0b10 => return map!(
+        0b10 |
+        // private
+        0b11 => return map!(
             rem,
-            take!(hdr.len),
-            |b| { BerObject::from_header_and_content(hdr,BerObjectContent::Unknown(hdr.tag, b)) }
+            apply!(ber_read_content_unknown, hdr.len, depth),
+            |b| { BerObject::from_header_and_content(hdr,BerObjectContent::Unknown(hdr.tag, Cow::Borrowed(b))) }
         ),
-        _    => { return Err(Err::Error(error_position!(i, ErrorKind::Custom(BER_CLASS_ERROR)))); },
+        _    => { return Err(Err::Error(error_position!(i, BerError::InvalidClass.into_error_kind()))); },
     }
-    match ber_read_element_content_as(rem, hdr.tag, hdr.len as usize, hdr.is_constructed(), depth) {
+    match ber_read_element_content_as(rem, hdr.tag, hdr.len, hdr.is_constructed(), depth) {
         Ok((rem, content)) => Ok((rem, BerObject::from_header_and_content(hdr, content))),
-        Err(Err::Error(Context::Code(_, ErrorKind::Custom(BER_TAG_UNKNOWN)))) => {
-            map!(rem, take!(hdr.len), |b| {
-                BerObject::from_header_and_content(hdr, BerObjectContent::Unknown(hdr.tag, b))
+        Err(Err::Error(Context::Code(_, ErrorKind::Custom(ParseError { kind: BerError::UnknownTag, .. })))) => {
+            map!(rem, apply!(ber_read_content_unknown, hdr.len, depth), |b| {
+                BerObject::from_header_and_content(hdr, BerObjectContent::Unknown(hdr.tag, Cow::Borrowed(b)))
             })
         }
         Err(e) => Err(e),
@@ -728,6 +1192,9 @@ fn parse_ber_recursive(i: &[u8], depth: usize) -> IResult<&[u8], BerObject, u32>
 
 /// Parse BER object
 #[inline]
-pub fn parse_ber(i: &[u8]) -> IResult<&[u8], BerObject, u32> {
-    parse_ber_recursive(i, 0)
+pub fn parse_ber(i: &[u8]) -> BerResult {
+    ber_locate_error(
+        i,
+        parse_ber_recursive(i, 0),
+    )
 }
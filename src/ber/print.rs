@@ -2,9 +2,13 @@ use std::fmt;
 use std::str;
 use ber::{BerObject, BerObjectContent, BerTag};
 use ber::BitStringObject;
+use oid::Oid;
 
 use rusticata_macros::debug;
 
+#[cfg(feature = "oid-registry")]
+use oid_registry::OidRegistry;
+
 #[derive(Clone,PartialEq)]
 pub enum PrettyPrinterFlag {
     ShowHeader,
@@ -16,6 +20,9 @@ pub struct PrettyBer<'a> {
     inc:     usize,
 
     flags:   Vec<PrettyPrinterFlag>,
+
+    #[cfg(feature = "oid-registry")]
+    registry: Option<&'a OidRegistry>,
 }
 
 impl<'a> BerObject<'a> {
@@ -26,6 +33,9 @@ impl<'a> BerObject<'a> {
             inc:     increment,
 
             flags:   Vec::new(),
+
+            #[cfg(feature = "oid-registry")]
+            registry: None,
         }
     }
 }
@@ -37,13 +47,32 @@ impl<'a> PrettyBer<'a> {
         }
     }
 
-    pub fn next_indent<'b>(&self, obj: &'b BerObject) -> PrettyBer<'b> {
+    /// Use `registry` to show the names of OIDs
+    #[cfg(feature = "oid-registry")]
+    pub fn set_registry(&mut self, registry: &'a OidRegistry) {
+        self.registry = Some(registry);
+    }
+
+    pub fn next_indent<'b>(&self, obj: &'b BerObject) -> PrettyBer<'b> where 'a: 'b {
         PrettyBer{
             obj:     obj,
             indent:  self.indent + self.inc,
             inc:     self.inc,
             flags:   self.flags.to_vec(),
+
+            #[cfg(feature = "oid-registry")]
+            registry: self.registry,
+        }
+    }
+
+    fn write_oid(&self, f: &mut fmt::Formatter, oid: &Oid) -> fmt::Result {
+        #[cfg(feature = "oid-registry")]
+        {
+            if let Some(entry) = self.registry.and_then(|r| r.get(oid)) {
+                return writeln!(f, "OID({:?}) {}", oid, entry.sn());
+            }
         }
+        writeln!(f, "OID({:?})", oid)
     }
 }
 
@@ -61,26 +90,46 @@ impl<'a> fmt::Debug for PrettyBer<'a> {
                 Err(e) => writeln!(f, "{}({:?}) <error decoding utf8 string: {:?}>", ty, s, e),
             }
         }
+        fn print_string_with_type(f: &mut fmt::Formatter, obj: &BerObjectContent, ty: &str) -> fmt::Result {
+            match obj.as_str() {
+                Ok(b)  => writeln!(f, "{}(\"{}\")", ty, b),
+                Err(e) => writeln!(f, "{}({:?}) <error decoding string: {}>", ty, obj.to_string_lossy().unwrap_or_default(), e),
+            }
+        }
         match self.obj.content {
             BerObjectContent::EndOfContent           => writeln!(f, "EndOfContent"),
             BerObjectContent::Boolean(b)             => writeln!(f, "Boolean({:?})", b),
-            BerObjectContent::Integer(i)             => writeln!(f, "Integer({:?})", debug::HexSlice{d:i}),
+            BerObjectContent::Integer(ref i)         => writeln!(f, "Integer({:?})", debug::HexSlice{d:i}),
             BerObjectContent::Enum(i)                => writeln!(f, "Enum({})", i),
-            BerObjectContent::OID(ref v)             => writeln!(f, "OID({:?})", v),
+            BerObjectContent::Real(r)                => writeln!(f, "Real({})", r),
+            BerObjectContent::OID(ref v)             => self.write_oid(f, v),
             BerObjectContent::RelativeOID(ref v)     => writeln!(f, "RelativeOID({:?})", v),
             BerObjectContent::Null                   => writeln!(f, "Null"),
-            BerObjectContent::OctetString(v)         => writeln!(f, "OctetString({:?})", debug::HexSlice{d:v}),
-            BerObjectContent::BitString(u,BitStringObject{data:v})
+            BerObjectContent::OctetString(ref v)     => writeln!(f, "OctetString({:?})", debug::HexSlice{d:v}),
+            BerObjectContent::BitString(u,BitStringObject{data:ref v})
                                                      => writeln!(f, "BitString({},{:?})", u, debug::HexSlice{d:v}),
-            BerObjectContent::GeneralizedTime(s)     => print_utf8_string_with_type(f, s, "GeneralizedTime"),
-            BerObjectContent::UTCTime(s)             => print_utf8_string_with_type(f, s, "UTCTime"),
-            BerObjectContent::PrintableString(s)     => print_utf8_string_with_type(f, s, "PrintableString"),
-            BerObjectContent::NumericString(s)       => print_utf8_string_with_type(f, s, "NumericString"),
-            BerObjectContent::UTF8String(s)          => print_utf8_string_with_type(f, s, "UTF8String"),
-            BerObjectContent::IA5String(s)           => print_utf8_string_with_type(f, s, "IA5String"),
-            BerObjectContent::T61String(s)           => print_utf8_string_with_type(f, s, "T61String"),
-            BerObjectContent::BmpString(s)           => print_utf8_string_with_type(f, s, "BmpString"),
-            BerObjectContent::GeneralString(s)       => print_utf8_string_with_type(f, s, "GeneralString"),
+            BerObjectContent::GeneralizedTime(ref s) => print_utf8_string_with_type(f, s, "GeneralizedTime"),
+            BerObjectContent::UTCTime(ref s)         => print_utf8_string_with_type(f, s, "UTCTime"),
+            BerObjectContent::Time(ref s)            => print_utf8_string_with_type(f, s, "Time"),
+            BerObjectContent::Date(ref s)            => print_utf8_string_with_type(f, s, "Date"),
+            BerObjectContent::TimeOfDay(ref s)       => print_utf8_string_with_type(f, s, "TimeOfDay"),
+            BerObjectContent::DateTime(ref s)        => print_utf8_string_with_type(f, s, "DateTime"),
+            BerObjectContent::Duration(ref s)        => print_utf8_string_with_type(f, s, "Duration"),
+            BerObjectContent::PrintableString(_)     => print_string_with_type(f, &self.obj.content, "PrintableString"),
+            BerObjectContent::NumericString(_)       => print_string_with_type(f, &self.obj.content, "NumericString"),
+            BerObjectContent::UTF8String(_)          => print_string_with_type(f, &self.obj.content, "UTF8String"),
+            BerObjectContent::IA5String(_)           => print_string_with_type(f, &self.obj.content, "IA5String"),
+            BerObjectContent::T61String(_)           => print_string_with_type(f, &self.obj.content, "T61String"),
+            BerObjectContent::BmpString(_)           => print_string_with_type(f, &self.obj.content, "BmpString"),
+            BerObjectContent::GeneralString(_)       => print_string_with_type(f, &self.obj.content, "GeneralString"),
+            BerObjectContent::VideotexString(_)      => print_string_with_type(f, &self.obj.content, "VideotexString"),
+            BerObjectContent::GraphicString(_)       => print_string_with_type(f, &self.obj.content, "GraphicString"),
+            BerObjectContent::VisibleString(_)       => print_string_with_type(f, &self.obj.content, "VisibleString"),
+            BerObjectContent::UniversalString(_)     => print_string_with_type(f, &self.obj.content, "UniversalString"),
+            BerObjectContent::ObjectDescriptor(_)    => print_string_with_type(f, &self.obj.content, "ObjectDescriptor"),
+            BerObjectContent::CharacterString(ref v) => writeln!(f, "{:?}", v),
+            BerObjectContent::External(ref v)        => writeln!(f, "{:?}", v),
+            BerObjectContent::EmbeddedPdv(ref v)     => writeln!(f, "{:?}", v),
             BerObjectContent::ContextSpecific(n,ref o) => {
                 let new_indent = self.indent + self.inc;
                 writeln!(f, "ContextSpecific [{}] {{", n)?;
@@ -107,7 +156,7 @@ impl<'a> fmt::Debug for PrettyBer<'a> {
                 writeln!(f, "]")?;
                 Ok(())
             },
-            BerObjectContent::Unknown(tag,o)         => writeln!(f, "Unknown({:?},{:x?})", tag, o),
+            BerObjectContent::Unknown(tag,ref o)     => writeln!(f, "Unknown({:?},{:x?})", tag, o),
         }
     }
 }
@@ -135,5 +184,21 @@ fn test_pretty_print() {
     println!("{:?}", pp);
 }
 
+#[cfg(feature = "oid-registry")]
+#[test]
+fn test_pretty_print_registry() {
+    use oid_registry::{OidRegistry, OID_X509_COMMON_NAME};
+
+    let d = BerObject::from_obj(BerObjectContent::Sequence(vec![
+        BerObject::from_obj(BerObjectContent::OID(OID_X509_COMMON_NAME)),
+        BerObject::from_obj(BerObjectContent::UTF8String(::std::borrow::Cow::Borrowed(b"test"))),
+    ]));
+    let registry = OidRegistry::default().with_well_known();
+    let mut pp = d.as_pretty(0,2);
+    pp.set_registry(&registry);
+    let s = format!("{:?}", pp);
+    assert_eq!(s, "Sequence[\n  OID(OID(2.5.4.3)) commonName\n  UTF8String(\"test\")\n]\n");
+}
+
 }
 
@@ -0,0 +1,521 @@
+//! Decoding and encoding of REAL values (X.690 sections 8.5 and 11.3)
+
+use crate::ber::serialize::encode_signed;
+use crate::error::BerError;
+
+/// Return `m` multiplied by 2 to the power `e`, with a single rounding (to nearest, ties to even)
+///
+/// The mantissa is rounded to the precision of the result, which is lower than 53 bits for
+/// subnormal numbers, so the final scaling is exact.
+fn ldexp(m: u64, e: i64) -> f64 {
+    fn pow2(e: i64) -> f64 {
+        // e is in the range of normal or subnormal numbers
+        if e >= -1022 {
+            f64::from_bits(((e + 1023) as u64) << 52)
+        } else {
+            f64::from_bits(1 << (e + 1074))
+        }
+    }
+    if m == 0 {
+        return 0.0;
+    }
+    let bits = 64 - i64::from(m.leading_zeros());
+    // exponent of the most significant bit
+    let top = e.saturating_add(bits - 1);
+    if top > 1023 {
+        return f64::INFINITY;
+    }
+    // significant bits of the result: subnormal numbers have a fixed exponent of -1074
+    let keep = if top >= -1022 { 53 } else { top + 1075 };
+    if keep < 0 {
+        // lower than half of the smallest subnormal number
+        return 0.0;
+    }
+    let drop = bits - keep;
+    if drop <= 0 {
+        return m as f64 * pow2(e);
+    }
+    let m = u128::from(m);
+    let q = m >> drop;
+    let rem = m & ((1 << drop) - 1);
+    let half = 1 << (drop - 1);
+    let q = if rem > half || (rem == half && q & 1 == 1) { q + 1 } else { q };
+    // q has at most 53 bits, and the product is representable
+    q as f64 * pow2(e + drop)
+}
+
+/// Decode the mantissa N of a binary encoding, as `(m, shift)` where `N = m * 2^shift`
+///
+/// Only the 64 most significant bits are kept, the other ones are folded into the lowest bit of
+/// `m`, so the conversion to `f64` is correctly rounded.
+fn decode_mantissa(s: &[u8]) -> (u64, i64) {
+    let s = &s[s.iter().take_while(|&&b| b == 0).count()..];
+    if s.len() <= 8 {
+        let m = s.iter().fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+        return (m, 0);
+    }
+    let mut m = s[..8].iter().fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+    let mut shift = 8 * (s.len() as i64 - 8);
+    let mut sticky = s[8..].iter().any(|&b| b != 0);
+    // keep 64 significant bits
+    let lz = m.leading_zeros();
+    if lz > 0 {
+        m = (m << lz) | u64::from(s[8] >> (8 - lz));
+        sticky = (s[8] << lz) != 0 || s[9..].iter().any(|&b| b != 0);
+        shift -= i64::from(lz);
+    }
+    (m | u64::from(sticky), shift)
+}
+
+/// Decode the exponent of a binary encoding (two's complement), saturating on overflow
+fn decode_exponent(s: &[u8]) -> i64 {
+    let mut e = i64::from(s[0] as i8);
+    for &b in &s[1..] {
+        e = match e.checked_mul(256) {
+            Some(v) => v + i64::from(b),
+            None if e > 0 => return i64::MAX,
+            None => return i64::MIN,
+        };
+    }
+    e
+}
+
+/// Fields of a binary encoding (8.5.7)
+struct BinaryReal<'a> {
+    /// log2 of the base
+    base_bits: u8,
+    /// binary scaling factor F
+    scale: u8,
+    exponent: &'a [u8],
+    mantissa: &'a [u8],
+}
+
+/// Split the content of a binary encoding
+fn split_binary(s: &[u8]) -> Result<BinaryReal<'_>, BerError> {
+    let b0 = s[0];
+    // base 2, 8 or 16 (8.5.7.2)
+    let base_bits = match (b0 >> 4) & 0b11 {
+        0 => 1,
+        1 => 3,
+        2 => 4,
+        _ => return Err(BerError::InvalidValue),
+    };
+    let scale = (b0 >> 2) & 0b11;
+    // exponent length (8.5.7.4)
+    let (exp_len, rest) = match b0 & 0b11 {
+        0b11 => match s.get(1) {
+            Some(&l) if l > 0 => (l as usize, &s[2..]),
+            _ => return Err(BerError::InvalidLength),
+        },
+        n => (n as usize + 1, &s[1..]),
+    };
+    // the mantissa must be present
+    if rest.len() <= exp_len {
+        return Err(BerError::InvalidLength);
+    }
+    Ok(BinaryReal {
+        base_bits,
+        scale,
+        exponent: &rest[..exp_len],
+        mantissa: &rest[exp_len..],
+    })
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit())
+}
+
+/// Fields of a decimal encoding: `int_part.frac_part * 10^exponent`
+struct DecimalReal<'a> {
+    neg: bool,
+    int_part: &'a str,
+    frac_part: &'a str,
+    /// Optional sign, followed by digits
+    exponent: &'a str,
+}
+
+/// Split a decimal encoding, in the ISO 6093 form `nr` (8.5.8)
+fn split_decimal(s: &[u8], nr: u8) -> Result<DecimalReal<'_>, BerError> {
+    let s = ::std::str::from_utf8(s).map_err(|_| BerError::InvalidValue)?;
+    // leading spaces are allowed
+    let s = s.trim_start_matches(' ');
+    let (mantissa, exponent) = match nr {
+        3 => match s.find(['E', 'e']) {
+            Some(idx) => (&s[..idx], Some(&s[idx + 1..])),
+            None => return Err(BerError::InvalidValue),
+        },
+        _ => (s, None),
+    };
+    let (neg, mantissa) = match mantissa.as_bytes().first() {
+        Some(b'-') => (true, &mantissa[1..]),
+        Some(b'+') => (false, &mantissa[1..]),
+        _ => (false, mantissa),
+    };
+    let (int_part, frac_part) = match mantissa.find(['.', ',']) {
+        // NR2 and NR3 require a decimal mark
+        Some(idx) if nr != 1 => (&mantissa[..idx], &mantissa[idx + 1..]),
+        None if nr == 1 => (mantissa, ""),
+        _ => return Err(BerError::InvalidValue),
+    };
+    if (!int_part.is_empty() && !is_digits(int_part))
+        || (!frac_part.is_empty() && !is_digits(frac_part))
+        || (int_part.is_empty() && frac_part.is_empty())
+    {
+        return Err(BerError::InvalidValue);
+    }
+    let exponent = match exponent {
+        Some(e) => {
+            let digits = e.trim_start_matches(['+', '-']);
+            if e.len() - digits.len() > 1 || !is_digits(digits) {
+                return Err(BerError::InvalidValue);
+            }
+            e
+        }
+        None => "0",
+    };
+    Ok(DecimalReal {
+        neg,
+        int_part,
+        frac_part,
+        exponent,
+    })
+}
+
+/// Parse a decimal encoding, in the ISO 6093 form `nr` (8.5.8)
+fn decode_decimal(s: &[u8], nr: u8) -> Result<f64, BerError> {
+    let DecimalReal { neg, int_part, frac_part, exponent } = split_decimal(s, nr)?;
+    let normalized = format!(
+        "{}{}.{}e{}",
+        if neg { "-" } else { "" },
+        if int_part.is_empty() { "0" } else { int_part },
+        if frac_part.is_empty() { "0" } else { frac_part },
+        exponent
+    );
+    normalized.parse::<f64>().map_err(|_| BerError::InvalidValue)
+}
+
+/// Decode the content of a REAL object (X.690 section 8.5)
+pub(crate) fn decode_real(s: &[u8]) -> Result<f64, BerError> {
+    let b0 = match s.first() {
+        // plus zero (8.5.2)
+        None => return Ok(0.0),
+        Some(&b) => b,
+    };
+    match b0 >> 6 {
+        // binary encoding (8.5.7)
+        0b10 | 0b11 => {
+            let BinaryReal { base_bits, scale, exponent, mantissa } = split_binary(s)?;
+            let (m, shift) = decode_mantissa(mantissa);
+            let e = decode_exponent(exponent)
+                .saturating_mul(i64::from(base_bits))
+                .saturating_add(i64::from(scale))
+                .saturating_add(shift);
+            let value = ldexp(m, e);
+            Ok(if b0 & 0x40 != 0 { -value } else { value })
+        }
+        // decimal encoding (8.5.8)
+        0b00 => match b0 & 0b11_1111 {
+            nr @ 1..=3 => decode_decimal(&s[1..], nr),
+            _ => Err(BerError::InvalidValue),
+        },
+        // special real values (8.5.9)
+        _ => {
+            if s.len() != 1 {
+                return Err(BerError::InvalidLength);
+            }
+            match b0 {
+                0x40 => Ok(f64::INFINITY),
+                0x41 => Ok(f64::NEG_INFINITY),
+                0x42 => Ok(f64::NAN),
+                0x43 => Ok(-0.0),
+                _ => Err(BerError::InvalidValue),
+            }
+        }
+    }
+}
+
+/// Check the DER restrictions on a REAL encoding (X.690 section 11.3)
+///
+/// The content must be a valid BER encoding.
+pub(crate) fn der_check_real(s: &[u8]) -> Result<(), BerError> {
+    let b0 = match s.first() {
+        None => return Ok(()),
+        Some(&b) => b,
+    };
+    let valid = match b0 >> 6 {
+        0b10 | 0b11 => {
+            let BinaryReal { base_bits, scale, exponent, mantissa } = split_binary(s)?;
+            // exponent is encoded using the minimal number of octets (8.5.7.4)
+            let minimal_exp = exponent.len() == 1
+                || match exponent[0] {
+                    0x00 => exponent[1] & 0x80 != 0,
+                    0xff => exponent[1] & 0x80 == 0,
+                    _ => true,
+                };
+            // the long form of the exponent length is only used if required
+            let minimal_len = b0 & 0b11 != 0b11 || exponent.len() > 3;
+            // base is 2, F is 0, and N is odd (11.3.1)
+            base_bits == 1
+                && scale == 0
+                && mantissa[0] != 0
+                && mantissa[mantissa.len() - 1] & 1 != 0
+                && minimal_exp
+                && minimal_len
+        }
+        0b00 => b0 == 3 && der_check_decimal(&s[1..]),
+        _ => true,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(BerError::DerConstraintFailed)
+    }
+}
+
+/// Check the canonical NR3 form of a decimal encoding (11.3.2)
+///
+/// The mantissa is an integer with no leading or trailing zeros, followed by `.E`, and the
+/// exponent has no leading zeros and no plus sign, except for `+0`.
+fn der_check_decimal(s: &[u8]) -> bool {
+    let s = match ::std::str::from_utf8(s) {
+        Ok(s) => s,
+        Err(_) => return false,
+    };
+    let idx = match s.find(".E") {
+        Some(idx) => idx,
+        None => return false,
+    };
+    let (mantissa, exponent) = (&s[..idx], &s[idx + 2..]);
+    let mantissa = mantissa.strip_prefix('-').unwrap_or(mantissa);
+    let exponent_digits = exponent.strip_prefix('-').unwrap_or(exponent);
+    is_digits(mantissa)
+        && !mantissa.starts_with('0')
+        && !mantissa.ends_with('0')
+        && (exponent == "+0"
+            || (is_digits(exponent_digits) && !exponent_digits.starts_with('0')))
+}
+
+/// Convert a REAL encoding to an encoding of the same value respecting the DER restrictions
+/// (X.690 section 11.3)
+///
+/// Binary encodings are converted to base 2 with an odd mantissa, and decimal encodings to the
+/// canonical NR3 form. Returns `None` if the content is not a valid BER encoding, or if the
+/// exponent cannot be represented.
+pub(crate) fn der_normalize_real(s: &[u8]) -> Option<Vec<u8>> {
+    let b0 = match s.first() {
+        None => return Some(Vec::new()),
+        Some(&b) => b,
+    };
+    let neg = b0 & 0x40 != 0;
+    match b0 >> 6 {
+        0b10 | 0b11 => {
+            let BinaryReal { base_bits, scale, exponent, mantissa } = split_binary(s).ok()?;
+            let mantissa = &mantissa[mantissa.iter().take_while(|&&b| b == 0).count()..];
+            if mantissa.is_empty() {
+                return Some(encode_real(if neg { -0.0 } else { 0.0 }));
+            }
+            if exponent.len() > 8 {
+                return None;
+            }
+            // remove the trailing zero bits of the mantissa, and add them to the exponent
+            let zero_octets = mantissa.iter().rev().take_while(|&&b| b == 0).count();
+            let mantissa = &mantissa[..mantissa.len() - zero_octets];
+            let bits = mantissa[mantissa.len() - 1].trailing_zeros();
+            let e = decode_exponent(exponent)
+                .checked_mul(i64::from(base_bits))?
+                .checked_add(i64::from(scale))?
+                .checked_add(8 * zero_octets as i64 + i64::from(bits))?;
+            let mut m = Vec::with_capacity(mantissa.len());
+            let mut carry = 0u8;
+            for &b in mantissa {
+                // bits is lower than 8
+                m.push(carry | (b >> bits));
+                carry = b.checked_shl(8 - bits).unwrap_or(0);
+            }
+            let m = &m[m.iter().take_while(|&&b| b == 0).count()..];
+            let exponent = encode_signed(e);
+            let mut v = Vec::with_capacity(2 + exponent.len() + m.len());
+            if exponent.len() <= 3 {
+                v.push(0x80 | (b0 & 0x40) | (exponent.len() as u8 - 1));
+            } else {
+                v.push(0x80 | (b0 & 0x40) | 0b11);
+                v.push(exponent.len() as u8);
+            }
+            v.extend_from_slice(&exponent);
+            v.extend_from_slice(m);
+            Some(v)
+        }
+        0b00 => {
+            let DecimalReal { neg, int_part, frac_part, exponent } = split_decimal(&s[1..], b0 & 0b11_1111).ok()?;
+            let digits = format!("{}{}", int_part, frac_part);
+            let digits = digits.trim_start_matches('0');
+            if digits.is_empty() {
+                return Some(encode_real(if neg { -0.0 } else { 0.0 }));
+            }
+            let mantissa = digits.trim_end_matches('0');
+            let e = exponent
+                .parse::<i64>()
+                .ok()?
+                .checked_sub(frac_part.len() as i64)?
+                .checked_add((digits.len() - mantissa.len()) as i64)?;
+            let exponent = if e == 0 { "+0".to_string() } else { e.to_string() };
+            let nr3 = format!("{}{}.E{}", if neg { "-" } else { "" }, mantissa, exponent);
+            let mut v = vec![0x03];
+            v.extend_from_slice(nr3.as_bytes());
+            Some(v)
+        }
+        // special values have a single encoding
+        _ => Some(s.to_vec()),
+    }
+}
+
+/// Encode a REAL value (X.690 section 8.5), respecting the DER restrictions
+///
+/// Finite values use the binary encoding, with base 2 and an odd mantissa.
+pub(crate) fn encode_real(f: f64) -> Vec<u8> {
+    if f == 0.0 {
+        return if f.is_sign_negative() { vec![0x43] } else { Vec::new() };
+    }
+    if f.is_nan() {
+        return vec![0x42];
+    }
+    if f.is_infinite() {
+        return vec![if f > 0.0 { 0x40 } else { 0x41 }];
+    }
+    let bits = f.to_bits();
+    let biased_exp = ((bits >> 52) & 0x7ff) as i64;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mut m, mut e) = if biased_exp == 0 {
+        // subnormal number
+        (fraction, -1074)
+    } else {
+        (fraction | (1 << 52), biased_exp - 1075)
+    };
+    let tz = m.trailing_zeros();
+    m >>= tz;
+    e += i64::from(tz);
+    let exponent = encode_signed(e);
+    let mantissa = m.to_be_bytes();
+    let mantissa = &mantissa[mantissa.iter().take_while(|&&b| b == 0).count()..];
+    let mut v = Vec::with_capacity(1 + exponent.len() + mantissa.len());
+    let sign = if f < 0.0 { 0x40 } else { 0 };
+    v.push(0x80 | sign | (exponent.len() as u8 - 1));
+    v.extend_from_slice(&exponent);
+    v.extend_from_slice(mantissa);
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+#[test]
+fn test_decode_real_binary() {
+    assert_eq!(decode_real(b""), Ok(0.0));
+    // 1 = 1 * 2^0
+    assert_eq!(decode_real(b"\x80\x00\x01"), Ok(1.0));
+    // -0.5 = -1 * 2^-1
+    assert_eq!(decode_real(b"\xc0\xff\x01"), Ok(-0.5));
+    // 3 * 8^2, base 8
+    assert_eq!(decode_real(b"\x90\x02\x03"), Ok(192.0));
+    // 1 * 16^1 * 2^2, base 16, F = 2
+    assert_eq!(decode_real(b"\xa8\x01\x01"), Ok(64.0));
+    // 2-octet exponent
+    assert_eq!(decode_real(b"\x81\x01\x00\x01"), Ok(2f64.powi(256)));
+    // exponent length in the second octet
+    assert_eq!(decode_real(b"\x83\x01\x03\x05"), Ok(40.0));
+    // mantissa larger than 64 bits
+    assert_eq!(decode_real(b"\x80\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x01"), Ok(2f64.powi(72)));
+    // overflow and underflow
+    assert_eq!(decode_real(b"\x82\x7f\xff\xff\x01"), Ok(f64::INFINITY));
+    assert_eq!(decode_real(b"\x82\x80\x00\x00\x01"), Ok(0.0));
+    // subnormal numbers: 2^-1074, and (2^54 + 1) * 2^-1129, slightly above half of 2^-1074
+    assert_eq!(decode_real(b"\x81\xfb\xce\x01"), Ok(f64::from_bits(1)));
+    assert_eq!(decode_real(b"\x81\xfb\x97\x40\x00\x00\x00\x00\x00\x01"), Ok(f64::from_bits(1)));
+    assert_eq!(decode_real(b"\x81\xfb\x97\x40\x00\x00\x00\x00\x00\x00"), Ok(0.0));
+    // reserved base
+    assert_eq!(decode_real(b"\xb0\x00\x01"), Err(BerError::InvalidValue));
+    // missing mantissa
+    assert_eq!(decode_real(b"\x80\x00"), Err(BerError::InvalidLength));
+}
+
+#[test]
+fn test_decode_real_decimal_special() {
+    assert_eq!(decode_real(b"\x01  -12"), Ok(-12.0));
+    assert_eq!(decode_real(b"\x0212,5"), Ok(12.5));
+    assert_eq!(decode_real(b"\x02.5"), Ok(0.5));
+    assert_eq!(decode_real(b"\x0315.E-1"), Ok(1.5));
+    assert_eq!(decode_real(b"\x03+1.5e+2"), Ok(150.0));
+    assert_eq!(decode_real(b"\x0212"), Err(BerError::InvalidValue));
+    assert_eq!(decode_real(b"\x031.5"), Err(BerError::InvalidValue));
+    assert_eq!(decode_real(b"\x01 1a"), Err(BerError::InvalidValue));
+    assert_eq!(decode_real(b"\x04 1"), Err(BerError::InvalidValue));
+    assert_eq!(decode_real(b"\x40"), Ok(f64::INFINITY));
+    assert_eq!(decode_real(b"\x41"), Ok(f64::NEG_INFINITY));
+    assert!(decode_real(b"\x42").unwrap().is_nan());
+    let f = decode_real(b"\x43").unwrap();
+    assert!(f == 0.0 && f.is_sign_negative());
+    assert_eq!(decode_real(b"\x44"), Err(BerError::InvalidValue));
+}
+
+#[test]
+fn test_der_check_real() {
+    assert_eq!(der_check_real(b"\x80\xff\x01"), Ok(()));
+    assert_eq!(der_check_real(b"\x80\x00\x02"), Err(BerError::DerConstraintFailed));
+    assert_eq!(der_check_real(b"\x90\x00\x01"), Err(BerError::DerConstraintFailed));
+    assert_eq!(der_check_real(b"\x84\x00\x01"), Err(BerError::DerConstraintFailed));
+    assert_eq!(der_check_real(b"\x81\x00\x01\x01"), Err(BerError::DerConstraintFailed));
+    assert_eq!(der_check_real(b"\x0315.E-1"), Ok(()));
+    assert_eq!(der_check_real(b"\x031.E+0"), Ok(()));
+    assert_eq!(der_check_real(b"\x0310.E-1"), Err(BerError::DerConstraintFailed));
+    assert_eq!(der_check_real(b"\x031.E0"), Err(BerError::DerConstraintFailed));
+    assert_eq!(der_check_real(b"\x0215.5"), Err(BerError::DerConstraintFailed));
+    assert_eq!(der_check_real(b"\x43"), Ok(()));
+}
+
+#[test]
+fn test_encode_real() {
+    assert_eq!(encode_real(0.0), b"");
+    assert_eq!(encode_real(-0.0), b"\x43");
+    assert_eq!(encode_real(1.0), b"\x80\x00\x01");
+    assert_eq!(encode_real(-0.5), b"\xc0\xff\x01");
+    assert_eq!(encode_real(f64::NAN), b"\x42");
+    for &f in &[3.25, -1e300, 5e-324, f64::MAX, 0.1] {
+        let v = encode_real(f);
+        assert_eq!(der_check_real(&v), Ok(()));
+        assert_eq!(decode_real(&v), Ok(f));
+    }
+}
+
+#[test]
+fn test_der_normalize_real() {
+    let cases: &[(&[u8], &[u8])] = &[
+        // even mantissa
+        (b"\x80\xcf\xfe", b"\x80\xd0\x7f"),
+        (b"\x80\x00\x02", b"\x80\x01\x01"),
+        // base 8, base 16 and scaling factor
+        (b"\x90\x02\x03", b"\x80\x06\x03"),
+        (b"\xa8\x01\x01", b"\x80\x06\x01"),
+        // non-minimal exponent, and mantissa larger than 64 bits
+        (b"\x81\x00\x01\x01", b"\x80\x01\x01"),
+        (b"\xc0\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00", b"\xc0\x48\x01"),
+        (b"\x80\x00\x00", b""),
+        // decimal encodings
+        (b"\x0210.50", b"\x03105.E-1"),
+        (b"\x0310.E-1", b"\x031.E+0"),
+        (b"\x01 100", b"\x031.E2"),
+        (b"\x01-0", b"\x43"),
+        // already canonical
+        (b"\x80\xff\x01", b"\x80\xff\x01"),
+        (b"\x0315.E-1", b"\x0315.E-1"),
+        (b"\x42", b"\x42"),
+    ];
+    for &(ber, der) in cases {
+        assert_eq!(der_normalize_real(ber).as_deref(), Some(der));
+        assert_eq!(der_check_real(der), Ok(()));
+        assert_eq!(decode_real(der).map(f64::to_bits), decode_real(ber).map(f64::to_bits));
+    }
+    assert_eq!(der_normalize_real(b"\x80"), None);
+}
+
+}
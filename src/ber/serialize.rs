@@ -0,0 +1,278 @@
+//! BER/DER encoder
+//!
+//! Objects are always written using the definite length form, with the minimal number of length
+//! octets. Since this is also the only form allowed by DER, decoding a DER object and encoding it
+//! again produces the same bytes, except for REAL values: they are decoded as `f64`, and always
+//! encoded in binary form, so a decimal encoding (also valid in DER) does not round trip, and
+//! mantissas wider than 53 bits are rounded.
+
+use ber::external::{external_components, pdv_components};
+use ber::real::encode_real;
+use ber::{BerObject, BerObjectContent, BerTag, BitStringObject};
+use std::fmt;
+use std::io::{self, Write};
+
+/// Errors that can occur while encoding a BER/DER object
+#[derive(Debug)]
+pub enum SerializeError {
+    /// Object class is not one of universal, application, context-specific or private
+    InvalidClass,
+    /// Object identifier cannot be encoded (less than two arcs, or invalid first arcs)
+    InvalidOid,
+    /// Error writing to the output
+    IOError(io::Error),
+}
+
+impl From<io::Error> for SerializeError {
+    fn from(e: io::Error) -> SerializeError {
+        SerializeError::IOError(e)
+    }
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SerializeError::InvalidClass => f.write_str("invalid object class"),
+            SerializeError::InvalidOid => f.write_str("object identifier cannot be encoded"),
+            SerializeError::IOError(ref e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl ::std::error::Error for SerializeError {}
+
+/// Holds the result of encoding functions
+pub type SerializeResult<T> = Result<T, SerializeError>;
+
+/// Encode the identifier octets (X.690 section 8.1.2)
+pub(crate) fn ber_encode_identifier(class: u8, structured: u8, tag: BerTag) -> SerializeResult<Vec<u8>> {
+    if class > 0b11 {
+        return Err(SerializeError::InvalidClass);
+    }
+    let b0 = (class << 6) | ((structured & 1) << 5);
+    if tag.0 < 0x1f {
+        return Ok(vec![b0 | tag.0 as u8]);
+    }
+    // high tag number form: tag is encoded in base 128, most significant group first
+    let mut v = vec![b0 | 0x1f];
+    v.extend(encode_base128(u64::from(tag.0)));
+    Ok(v)
+}
+
+/// Encode the length octets, using the short form when possible (X.690 section 8.1.3)
+pub(crate) fn ber_encode_length(len: usize) -> Vec<u8> {
+    if len < 128 {
+        return vec![len as u8];
+    }
+    let bytes = (len as u64).to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count();
+    let mut v = vec![0x80 | (bytes.len() - skip) as u8];
+    v.extend_from_slice(&bytes[skip..]);
+    v
+}
+
+/// Encode a value as a sequence of 7-bit groups, with bit 8 set on all but the last octet
+fn encode_base128(mut n: u64) -> Vec<u8> {
+    let mut v = vec![(n & 0x7f) as u8];
+    n >>= 7;
+    while n > 0 {
+        v.push(0x80 | (n & 0x7f) as u8);
+        n >>= 7;
+    }
+    v.reverse();
+    v
+}
+
+/// Encode an unsigned value as the shortest two's complement integer content
+pub(crate) fn encode_unsigned(n: u64) -> Vec<u8> {
+    let bytes = n.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count().min(7);
+    let mut v = Vec::with_capacity(9 - skip);
+    if bytes[skip] & 0x80 != 0 {
+        v.push(0);
+    }
+    v.extend_from_slice(&bytes[skip..]);
+    v
+}
+
+/// Encode a signed value as the shortest two's complement integer content (X.690 section 8.3)
+pub(crate) fn encode_signed(n: i64) -> Vec<u8> {
+    let bytes = n.to_be_bytes();
+    let mut skip = 0;
+    while skip < 7 {
+        let redundant = match bytes[skip] {
+            0x00 => bytes[skip + 1] & 0x80 == 0,
+            0xff => bytes[skip + 1] & 0x80 != 0,
+            _ => false,
+        };
+        if !redundant {
+            break;
+        }
+        skip += 1;
+    }
+    bytes[skip..].to_vec()
+}
+
+impl<'a> BerObject<'a> {
+    /// Encode the object using the Distinguished Encoding Rules
+    ///
+    /// Note that the content is not checked nor converted, so the object must already satisfy the
+    /// DER constraints (for ex. minimal integers, or sorted sets). This is the case for all
+    /// objects returned by [`parse_der`](../der/fn.parse_der.html). REAL values are always written
+    /// in binary form, even if they were decoded from a decimal encoding.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::BerObject;
+    /// # fn main() {
+    /// let obj = BerObject::from_seq(vec![
+    ///     BerObject::from_int_slice(b"\x01\x00\x01"),
+    /// ]);
+    /// assert_eq!(
+    ///     obj.to_der().expect("serialization failed"),
+    ///     vec![0x30, 0x05, 0x02, 0x03, 0x01, 0x00, 0x01]
+    /// );
+    /// # }
+    /// ```
+    pub fn to_der(&self) -> SerializeResult<Vec<u8>> {
+        let mut v = Vec::new();
+        self.write_der(&mut v)?;
+        Ok(v)
+    }
+
+    /// Encode the object using the Basic Encoding Rules
+    ///
+    /// Objects are always encoded using the definite length form, so the output is the same as
+    /// [`to_der`](struct.BerObject.html#method.to_der).
+    pub fn to_ber(&self) -> SerializeResult<Vec<u8>> {
+        self.to_der()
+    }
+
+    /// Encode the object using the Distinguished Encoding Rules, and write it to `writer`
+    ///
+    /// Returns the number of bytes written.
+    pub fn write_der<W: Write>(&self, writer: &mut W) -> SerializeResult<usize> {
+        if let BerObjectContent::ContextSpecific(_, None) = self.content {
+            // absent optional value: nothing to encode
+            return Ok(0);
+        }
+        let content = self.encode_content()?;
+        let mut header = ber_encode_identifier(self.class, self.structured, self.tag)?;
+        header.extend(ber_encode_length(content.len()));
+        writer.write_all(&header)?;
+        writer.write_all(&content)?;
+        Ok(header.len() + content.len())
+    }
+
+    fn encode_content(&self) -> SerializeResult<Vec<u8>> {
+        let v = match self.content {
+            BerObjectContent::EndOfContent | BerObjectContent::Null => Vec::new(),
+            BerObjectContent::Boolean(b) => vec![if b { 0xff } else { 0x00 }],
+            BerObjectContent::BitString(ignored_bits, BitStringObject { ref data }) => {
+                let mut v = Vec::with_capacity(data.len() + 1);
+                v.push(ignored_bits);
+                v.extend_from_slice(data);
+                v
+            }
+            BerObjectContent::Enum(i) => encode_signed(i),
+            BerObjectContent::Real(f) => encode_real(f),
+            BerObjectContent::OID(ref oid) => oid.as_bytes().to_vec(),
+            BerObjectContent::RelativeOID(ref oid) => oid.as_bytes().to_vec(),
+            BerObjectContent::Integer(ref s)
+            | BerObjectContent::OctetString(ref s)
+            | BerObjectContent::NumericString(ref s)
+            | BerObjectContent::PrintableString(ref s)
+            | BerObjectContent::IA5String(ref s)
+            | BerObjectContent::UTF8String(ref s)
+            | BerObjectContent::T61String(ref s)
+            | BerObjectContent::VideotexString(ref s)
+            | BerObjectContent::GraphicString(ref s)
+            | BerObjectContent::VisibleString(ref s)
+            | BerObjectContent::UniversalString(ref s)
+            | BerObjectContent::ObjectDescriptor(ref s)
+            | BerObjectContent::BmpString(ref s)
+            | BerObjectContent::UTCTime(ref s)
+            | BerObjectContent::GeneralizedTime(ref s)
+            | BerObjectContent::Time(ref s)
+            | BerObjectContent::Date(ref s)
+            | BerObjectContent::TimeOfDay(ref s)
+            | BerObjectContent::DateTime(ref s)
+            | BerObjectContent::Duration(ref s)
+            | BerObjectContent::GeneralString(ref s)
+            | BerObjectContent::Unknown(_, ref s) => s.to_vec(),
+            BerObjectContent::Sequence(ref l) | BerObjectContent::Set(ref l) => {
+                let mut v = Vec::new();
+                for o in l {
+                    o.write_der(&mut v)?;
+                }
+                v
+            }
+            // encoded as their associated SEQUENCE types
+            BerObjectContent::External(ref e) => {
+                BerObject::from_seq(external_components(e)).encode_content()?
+            }
+            BerObjectContent::EmbeddedPdv(ref p) => {
+                let l = pdv_components(&p.identification, &p.data_value_descriptor, &p.data_value);
+                BerObject::from_seq(l).encode_content()?
+            }
+            BerObjectContent::CharacterString(ref s) => {
+                let l = pdv_components(&s.identification, &s.data_value_descriptor, &s.string_value);
+                BerObject::from_seq(l).encode_content()?
+            }
+            BerObjectContent::ContextSpecific(_, Some(ref o)) => {
+                if self.is_constructed() {
+                    // EXPLICIT tagging: the inner object is fully encoded
+                    o.to_der()?
+                } else {
+                    // IMPLICIT tagging: only the content of the inner object is kept
+                    o.encode_content()?
+                }
+            }
+            BerObjectContent::ContextSpecific(_, None) => Vec::new(),
+        };
+        Ok(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ber_encode_identifier, ber_encode_length, encode_signed, encode_unsigned};
+    use ber::BerTag;
+
+#[test]
+fn test_encode_identifier() {
+    assert_eq!(ber_encode_identifier(0, 1, BerTag::Sequence).unwrap(), vec![0x30]);
+    assert_eq!(ber_encode_identifier(2, 0, BerTag(0x1e)).unwrap(), vec![0x9e]);
+    assert_eq!(ber_encode_identifier(2, 0, BerTag(0x1f)).unwrap(), vec![0x9f, 0x1f]);
+    assert_eq!(ber_encode_identifier(2, 0, BerTag(0x1122)).unwrap(), vec![0x9f, 0xa2, 0x22]);
+    assert!(ber_encode_identifier(4, 0, BerTag(0)).is_err());
+}
+
+#[test]
+fn test_encode_length() {
+    assert_eq!(ber_encode_length(0), vec![0x00]);
+    assert_eq!(ber_encode_length(127), vec![0x7f]);
+    assert_eq!(ber_encode_length(128), vec![0x81, 0x80]);
+    assert_eq!(ber_encode_length(0x1234), vec![0x82, 0x12, 0x34]);
+}
+
+#[test]
+fn test_encode_unsigned() {
+    assert_eq!(encode_unsigned(0), vec![0x00]);
+    assert_eq!(encode_unsigned(0x7f), vec![0x7f]);
+    assert_eq!(encode_unsigned(0x80), vec![0x00, 0x80]);
+    assert_eq!(encode_unsigned(0x10001), vec![0x01, 0x00, 0x01]);
+}
+
+#[test]
+fn test_encode_signed() {
+    assert_eq!(encode_signed(0), vec![0x00]);
+    assert_eq!(encode_signed(127), vec![0x7f]);
+    assert_eq!(encode_signed(128), vec![0x00, 0x80]);
+    assert_eq!(encode_signed(-1), vec![0xff]);
+    assert_eq!(encode_signed(-128), vec![0x80]);
+    assert_eq!(encode_signed(-129), vec![0xff, 0x7f]);
+    assert_eq!(encode_signed(::std::i64::MIN), vec![0x80, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+}
@@ -0,0 +1,256 @@
+//! Decoding of character string types (X.680 section 41)
+
+use crate::error::BerError;
+use std::borrow::Cow;
+use std::char;
+use std::str;
+
+/// Test if `c` is in the alphabet of NumericString (X.680 section 41.2)
+#[inline]
+pub fn is_numeric_char(c: u8) -> bool {
+    c.is_ascii_digit() || c == b' '
+}
+
+/// Test if `c` is in the alphabet of PrintableString (X.680 section 41.4)
+#[inline]
+pub fn is_printable_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric()
+        || matches!(
+            c,
+            b' ' | b'\'' | b'(' | b')' | b'+' | b',' | b'-' | b'.' | b'/' | b':' | b'=' | b'?'
+        )
+}
+
+/// Test if `c` is in the alphabet of VisibleString (X.680 section 41.4)
+///
+/// VisibleString contains the printing characters of ISO 646, and space.
+#[inline]
+pub fn is_visible_char(c: u8) -> bool {
+    (0x20..0x7f).contains(&c)
+}
+
+/// Test if `c` can appear in a GraphicString or ObjectDescriptor (X.680 section 41.4)
+///
+/// Control characters are not allowed, except ESC which is required to designate the graphic
+/// character sets (ISO 2022).
+#[inline]
+pub fn is_graphic_char(c: u8) -> bool {
+    match c {
+        0x1b => true,
+        // C0 and C1 control characters, and DEL
+        0x00..=0x1f | 0x7f..=0x9f => false,
+        _ => true,
+    }
+}
+
+/// Decode an ASCII string (IA5String, NumericString, PrintableString, VisibleString)
+pub(crate) fn decode_ascii<'a>(s: &'a [u8], lossy: bool) -> Result<Cow<'a, str>, BerError> {
+    if s.is_ascii() {
+        // ASCII is valid UTF-8
+        return str::from_utf8(s)
+            .map(Cow::Borrowed)
+            .map_err(|_| BerError::StringInvalidCharset);
+    }
+    if !lossy {
+        return Err(BerError::StringInvalidCharset);
+    }
+    Ok(Cow::Owned(
+        s.iter()
+            .map(|&c| if c.is_ascii() { c as char } else { char::REPLACEMENT_CHARACTER })
+            .collect(),
+    ))
+}
+
+/// Decode a UTF-8 string
+pub(crate) fn decode_utf8<'a>(s: &'a [u8], lossy: bool) -> Result<Cow<'a, str>, BerError> {
+    if lossy {
+        Ok(String::from_utf8_lossy(s))
+    } else {
+        str::from_utf8(s)
+            .map(Cow::Borrowed)
+            .map_err(|_| BerError::StringInvalidCharset)
+    }
+}
+
+/// Decode a BMPString (UCS-2, big-endian)
+///
+/// Surrogate pairs (UTF-16) are accepted.
+pub(crate) fn decode_bmp<'a>(s: &'a [u8], lossy: bool) -> Result<Cow<'a, str>, BerError> {
+    if s.len() % 2 != 0 && !lossy {
+        return Err(BerError::StringInvalidCharset);
+    }
+    let units = s.chunks(2).map(|c| match *c {
+        [hi, lo] => (u16::from(hi) << 8) | u16::from(lo),
+        // odd length, only in lossy mode
+        _ => 0xfffd,
+    });
+    let mut out = String::with_capacity(s.len() / 2);
+    for c in char::decode_utf16(units) {
+        match c {
+            Ok(c) => out.push(c),
+            Err(_) if lossy => out.push(char::REPLACEMENT_CHARACTER),
+            Err(_) => return Err(BerError::StringInvalidCharset),
+        }
+    }
+    Ok(Cow::Owned(out))
+}
+
+/// Decode a UniversalString (UCS-4, big-endian)
+pub(crate) fn decode_ucs4<'a>(s: &'a [u8], lossy: bool) -> Result<Cow<'a, str>, BerError> {
+    if s.len() % 4 != 0 && !lossy {
+        return Err(BerError::StringInvalidCharset);
+    }
+    let mut out = String::with_capacity(s.len() / 4);
+    for c in s.chunks(4) {
+        let c = match *c {
+            [a, b, c, d] => char::from_u32(u32::from_be_bytes([a, b, c, d])),
+            // odd length, only in lossy mode
+            _ => None,
+        };
+        match c {
+            Some(c) => out.push(c),
+            None if lossy => out.push(char::REPLACEMENT_CHARACTER),
+            None => return Err(BerError::StringInvalidCharset),
+        }
+    }
+    Ok(Cow::Owned(out))
+}
+
+/// Characters 0xa0 to 0xff of T.61 (`None` if undefined, `\0` for non-spacing diacritics)
+const T61_HIGH: [Option<char>; 96] = [
+    // 0xa0
+    Some('\u{a0}'), Some('¡'), Some('¢'), Some('£'), Some('$'), Some('¥'), Some('#'), Some('§'),
+    Some('¤'), None, None, Some('«'), None, None, None, None,
+    // 0xb0
+    Some('°'), Some('±'), Some('²'), Some('³'), Some('×'), Some('µ'), Some('¶'), Some('·'),
+    Some('÷'), None, None, Some('»'), Some('¼'), Some('½'), Some('¾'), Some('¿'),
+    // 0xc0: non-spacing diacritics, see `t61_diacritic`
+    None, Some('\0'), Some('\0'), Some('\0'), Some('\0'), Some('\0'), Some('\0'), Some('\0'),
+    Some('\0'), Some('\0'), Some('\0'), Some('\0'), None, Some('\0'), Some('\0'), Some('\0'),
+    // 0xd0
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    // 0xe0
+    Some('Ω'), Some('Æ'), Some('Đ'), Some('ª'), Some('Ħ'), None, Some('Ĳ'), Some('Ŀ'),
+    Some('Ł'), Some('Ø'), Some('Œ'), Some('º'), Some('Þ'), Some('Ŧ'), Some('Ŋ'), Some('ŉ'),
+    // 0xf0
+    Some('ĸ'), Some('æ'), Some('đ'), Some('ð'), Some('ħ'), Some('ı'), Some('ĳ'), Some('ŀ'),
+    Some('ł'), Some('ø'), Some('œ'), Some('ß'), Some('þ'), Some('ŧ'), Some('ŋ'), None,
+];
+
+/// Unicode combining character for a T.61 non-spacing diacritic
+fn t61_diacritic(c: u8) -> char {
+    match c {
+        0xc1 => '\u{300}',
+        0xc2 => '\u{301}',
+        0xc3 => '\u{302}',
+        0xc4 => '\u{303}',
+        0xc5 => '\u{304}',
+        0xc6 => '\u{306}',
+        0xc7 => '\u{307}',
+        0xc8 | 0xc9 => '\u{308}',
+        0xca => '\u{30a}',
+        0xcb => '\u{327}',
+        0xcd => '\u{30b}',
+        0xce => '\u{328}',
+        _ => '\u{30c}',
+    }
+}
+
+/// Decode a T61String (Teletex, ITU-T T.61)
+///
+/// Non-spacing diacritics precede the base character in T.61: they are emitted as Unicode
+/// combining characters, after the base character.
+pub(crate) fn decode_t61<'a>(s: &'a [u8], lossy: bool) -> Result<Cow<'a, str>, BerError> {
+    if s.is_ascii() {
+        return decode_ascii(s, lossy);
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut pending = None;
+    for &c in s {
+        let decoded = match c {
+            0x00..=0x9f => Some(c as char),
+            _ => T61_HIGH[(c - 0xa0) as usize],
+        };
+        match decoded {
+            Some('\0') if pending.is_none() => {
+                pending = Some(t61_diacritic(c));
+                continue;
+            }
+            Some(ch) if ch != '\0' => {
+                out.push(ch);
+                if let Some(d) = pending.take() {
+                    out.push(d);
+                }
+            }
+            // undefined character, or two diacritics
+            _ => {
+                if !lossy {
+                    return Err(BerError::StringInvalidCharset);
+                }
+                pending = None;
+                out.push(char::REPLACEMENT_CHARACTER);
+            }
+        }
+    }
+    if pending.is_some() {
+        // diacritic without base character
+        if !lossy {
+            return Err(BerError::StringInvalidCharset);
+        }
+        out.push(char::REPLACEMENT_CHARACTER);
+    }
+    Ok(Cow::Owned(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+#[test]
+fn test_decode_bmp() {
+    assert_eq!(decode_bmp(b"\x00A\x00\xe9\x20\xac", false), Ok(Cow::Borrowed("Aé€")));
+    // surrogate pair
+    assert_eq!(decode_bmp(b"\xd8\x3d\xde\x00", false), Ok(Cow::Borrowed("\u{1f600}")));
+    assert_eq!(decode_bmp(b"\x00A\x00", false), Err(BerError::StringInvalidCharset));
+    assert_eq!(decode_bmp(b"\x00A\x00", true), Ok(Cow::Borrowed("A\u{fffd}")));
+    assert_eq!(decode_bmp(b"\xd8\x3d", false), Err(BerError::StringInvalidCharset));
+}
+
+#[test]
+fn test_decode_t61() {
+    assert_eq!(decode_t61(b"abc", false), Ok(Cow::Borrowed("abc")));
+    // e acute, and sharp s
+    assert_eq!(decode_t61(b"\xc2e\xfb", false), Ok(Cow::Borrowed("e\u{301}ß")));
+    assert_eq!(decode_t61(b"\xa9", false), Err(BerError::StringInvalidCharset));
+    assert_eq!(decode_t61(b"a\xc2", false), Err(BerError::StringInvalidCharset));
+    assert_eq!(decode_t61(b"a\xc2", true), Ok(Cow::Borrowed("a\u{fffd}")));
+}
+
+#[test]
+fn test_decode_ucs4() {
+    assert_eq!(decode_ucs4(b"\x00\x00\x00A\x00\x01\xf6\x00", false), Ok(Cow::Borrowed("A\u{1f600}")));
+    assert_eq!(decode_ucs4(b"\x00\x00\x00", false), Err(BerError::StringInvalidCharset));
+    assert_eq!(decode_ucs4(b"\x00\x00\x00", true), Ok(Cow::Borrowed("\u{fffd}")));
+    // surrogates and values above 0x10ffff are not characters
+    assert_eq!(decode_ucs4(b"\x00\x00\xd8\x00", false), Err(BerError::StringInvalidCharset));
+    assert_eq!(decode_ucs4(b"\x00\x11\x00\x00", false), Err(BerError::StringInvalidCharset));
+}
+
+#[test]
+fn test_charsets() {
+    assert!(b"0123 456".iter().all(|&c| is_numeric_char(c)));
+    assert!(!is_numeric_char(b'a'));
+    assert!(b"Test (1), a+b=c?".iter().all(|&c| is_printable_char(c)));
+    assert!(!is_printable_char(b'@'));
+    assert!(!is_printable_char(b'*'));
+    assert!(b"a@b ~".iter().all(|&c| is_visible_char(c)));
+    assert!(!is_visible_char(b'\n'));
+    assert!(b"\x1b(Ba\xe9".iter().all(|&c| is_graphic_char(c)));
+    assert!(!is_graphic_char(b'\r'));
+    assert!(!is_graphic_char(0x85));
+    assert_eq!(decode_ascii(b"a\xe9", false), Err(BerError::StringInvalidCharset));
+    assert_eq!(decode_ascii(b"a\xe9", true), Ok(Cow::Borrowed("a\u{fffd}")));
+}
+
+}
@@ -0,0 +1,16 @@
+//! Canonical Encoding Rules (CER) parser
+//!
+//! CER (X.690 section 9) is, like DER, a canonical subset of BER, designed for streaming:
+//!
+//! - constructed encodings use the indefinite form of length, and primitive encodings the
+//!   definite form with the minimum number of length octets (9.1)
+//! - string values of up to 1000 octets use the primitive form, and longer values the
+//!   constructed form, with segments of 1000 octets (9.2)
+//! - the components of SET values are sorted (9.3), and the restrictions of section 11
+//!   (booleans, bit strings, reals, times, SET OF ordering) are the same as for DER
+//!
+//! CER objects are represented as [`BerObject`](../ber/struct.BerObject.html): the parsing
+//! functions of this module parse the input as BER, and verify the CER constraints.
+
+mod parser;
+pub use cer::parser::*;
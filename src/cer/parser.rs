@@ -0,0 +1,428 @@
+use crate::ber::*;
+use crate::der::{is_minimal_length, is_set_sorted, is_string_tag, read_integer_as, DerSetOrder};
+use crate::error::*;
+
+/// Maximum number of content octets of a primitive string encoding (X.690 section 9.2)
+pub const CER_MAX_SEGMENT_SIZE: usize = 1000;
+
+macro_rules! cer_constraint_fail_if(
+    ($slice:expr, $cond:expr) => (
+        {
+            if $cond {
+                return Err(::nom::Err::Error(error_position!($slice, BerError::CerConstraintFailed.into_error_kind())));
+            }
+        }
+    );
+);
+
+/// Parse CER object
+///
+/// The input is parsed as BER, and the CER constraints are verified for the object and all its
+/// elements.
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # use der_parser::cer::parse_cer;
+/// # fn main() {
+/// // sequence (indefinite length) containing an integer
+/// let bytes = [0x30, 0x80, 0x02, 0x01, 0x01, 0x00, 0x00];
+/// assert!(parse_cer(&bytes).is_ok());
+/// // definite length is not allowed for constructed objects
+/// let bytes = [0x30, 0x03, 0x02, 0x01, 0x01];
+/// assert!(parse_cer(&bytes).is_err());
+/// # }
+/// ```
+pub fn parse_cer(i: &[u8]) -> BerResult {
+    ber_locate_error(i, cer_check_object(i, parse_ber_recursive(i, 0)))
+}
+
+/// Parse a CER object, expecting a value with specificed tag
+pub fn parse_cer_with_tag(i: &[u8], tag: BerTag) -> BerResult {
+    ber_locate_error(i, cer_check_object(i, parse_ber_with_tag(i, tag)))
+}
+
+/// Read end of content marker
+#[inline]
+pub fn parse_cer_endofcontent(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::EndOfContent)
+}
+
+/// Read a boolean value
+///
+/// If the boolean value is TRUE, the octet shall have all bits set to one (0xff).
+#[inline]
+pub fn parse_cer_bool(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Boolean)
+}
+
+/// Read an integer value
+///
+/// The integer must be encoded using the minimum number of octets.
+#[inline]
+pub fn parse_cer_integer(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Integer)
+}
+
+/// Read a bitstring value
+///
+/// Unused bits must be zero, and values longer than 1000 octets must be split in segments.
+#[inline]
+pub fn parse_cer_bitstring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::BitString)
+}
+
+/// Read an octetstring value
+///
+/// Values of up to 1000 octets use the primitive form. Longer values use the constructed form,
+/// with segments of 1000 octets (except the last one).
+#[inline]
+pub fn parse_cer_octetstring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::OctetString)
+}
+
+/// Read a null value
+#[inline]
+pub fn parse_cer_null(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Null)
+}
+
+/// Read an object identifier value
+#[inline]
+pub fn parse_cer_oid(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Oid)
+}
+
+/// Read a real value
+///
+/// The restrictions of X.690 section 11.3 are the same as for DER.
+#[inline]
+pub fn parse_cer_real(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::RealType)
+}
+
+/// Read an enumerated value
+#[inline]
+pub fn parse_cer_enum(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Enumerated)
+}
+
+/// Read a UTF-8 string value
+#[inline]
+pub fn parse_cer_utf8string(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Utf8String)
+}
+
+/// Read a relative object identifier value
+#[inline]
+pub fn parse_cer_relative_oid(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::RelativeOid)
+}
+
+/// Parse a sequence of CER elements
+#[inline]
+pub fn parse_cer_sequence(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Sequence)
+}
+
+/// Parse a set of CER elements
+///
+/// The elements must be sorted, either by tag (SET) or by encoding (SET OF).
+#[inline]
+pub fn parse_cer_set(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Set)
+}
+
+/// Read a numeric string value
+#[inline]
+pub fn parse_cer_numericstring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::NumericString)
+}
+
+/// Read a printable string value
+#[inline]
+pub fn parse_cer_printablestring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::PrintableString)
+}
+
+/// Read a T61 string value
+#[inline]
+pub fn parse_cer_t61string(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::T61String)
+}
+
+/// Read a Videotex string value
+#[inline]
+pub fn parse_cer_videotexstring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::VideotexString)
+}
+
+/// Read an IA5 string value
+#[inline]
+pub fn parse_cer_ia5string(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Ia5String)
+}
+
+/// Read an UTC time value
+#[inline]
+pub fn parse_cer_utctime(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::UtcTime)
+}
+
+/// Read a Generalized time value
+#[inline]
+pub fn parse_cer_generalizedtime(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::GeneralizedTime)
+}
+
+/// Read a TIME value
+#[inline]
+pub fn parse_cer_time(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Time)
+}
+
+/// Read a DATE value
+#[inline]
+pub fn parse_cer_date(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Date)
+}
+
+/// Read a TIME-OF-DAY value
+#[inline]
+pub fn parse_cer_timeofday(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::TimeOfDay)
+}
+
+/// Read a DATE-TIME value
+#[inline]
+pub fn parse_cer_datetime(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::DateTime)
+}
+
+/// Read a DURATION value
+#[inline]
+pub fn parse_cer_duration(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::Duration)
+}
+
+/// Read a GraphicString value
+#[inline]
+pub fn parse_cer_graphicstring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::GraphicString)
+}
+
+/// Read a VisibleString value
+#[inline]
+pub fn parse_cer_visiblestring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::VisibleString)
+}
+
+/// Read a GeneralString value
+#[inline]
+pub fn parse_cer_generalstring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::GeneralString)
+}
+
+/// Read a UniversalString value
+#[inline]
+pub fn parse_cer_universalstring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::UniversalString)
+}
+
+/// Read an unrestricted CHARACTER STRING value
+#[inline]
+pub fn parse_cer_characterstring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::CharacterString)
+}
+
+/// Read a BmpString value
+#[inline]
+pub fn parse_cer_bmpstring(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::BmpString)
+}
+
+/// Read an ObjectDescriptor value
+#[inline]
+pub fn parse_cer_objectdescriptor(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::ObjDescriptor)
+}
+
+/// Read an EXTERNAL value
+#[inline]
+pub fn parse_cer_external(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::External)
+}
+
+/// Read an EMBEDDED PDV value
+#[inline]
+pub fn parse_cer_embedded_pdv(i: &[u8]) -> BerResult {
+    parse_cer_with_tag(i, BerTag::EmbeddedPdv)
+}
+
+pub fn parse_cer_explicit<F>(i: &[u8], tag: BerTag, f: F) -> BerResult
+where
+    F: Fn(&[u8]) -> BerResult,
+{
+    ber_locate_error(i, cer_check_object(i, parse_ber_explicit(i, tag, f)))
+}
+
+pub fn parse_cer_implicit<F>(i: &[u8], tag: BerTag, f: F) -> BerResult
+where
+    F: Fn(&[u8], BerTag, BerSize) -> BerResult<BerObjectContent>,
+{
+    ber_locate_error(i, cer_check_object(i, parse_ber_implicit(i, tag, f)))
+}
+
+/// Parse CER object and try to decode it as a 32-bits unsigned integer
+///
+/// Negative values are rejected.
+pub fn parse_cer_u32(i: &[u8]) -> BerResult<u32> {
+    ber_locate_error(i, read_integer_as(i, parse_cer_integer, BerObjectContent::as_u32))
+}
+
+/// Parse CER object and try to decode it as a 64-bits unsigned integer
+///
+/// Negative values are rejected.
+pub fn parse_cer_u64(i: &[u8]) -> BerResult<u64> {
+    ber_locate_error(i, read_integer_as(i, parse_cer_integer, BerObjectContent::as_u64))
+}
+
+/// Parse CER object and try to decode it as a 32-bits signed integer
+pub fn parse_cer_i32(i: &[u8]) -> BerResult<i32> {
+    ber_locate_error(i, read_integer_as(i, parse_cer_integer, BerObjectContent::as_i32))
+}
+
+/// Parse CER object and try to decode it as a 64-bits signed integer
+pub fn parse_cer_i64(i: &[u8]) -> BerResult<i64> {
+    ber_locate_error(i, read_integer_as(i, parse_cer_integer, BerObjectContent::as_i64))
+}
+
+// --------- end of parse_cer_xxx functions ----------
+
+/// Verify the CER constraints on the input consumed by a BER parser
+fn cer_check_object<'a, O>(i: &'a [u8], res: BerResult<'a, O>) -> BerResult<'a, O> {
+    let (rem, o) = res?;
+    // optional objects may not consume any input
+    if rem.len() < i.len() {
+        cer_check_element(&i[..i.len() - rem.len()], 0)?;
+    }
+    Ok((rem, o))
+}
+
+/// Verify the CER constraints on the next element, and return its class and tag
+fn cer_check_element(i: &[u8], depth: usize) -> BerResult<(u8, u32)> {
+    error_if!(i, depth > MAX_RECURSION, BerError::BerMaxDepth.into_error_kind())?;
+    let (rem, hdr) = ber_read_element_header(i)?;
+    // primitive encodings use the minimum number of length octets (9.1)
+    cer_constraint_fail_if!(i, !is_minimal_length(&i[..i.len() - rem.len()]));
+    let tag = (hdr.class, hdr.tag.0);
+    if hdr.is_constructed() {
+        // constructed encodings use the indefinite form (9.1)
+        cer_constraint_fail_if!(i, !hdr.len.is_indefinite());
+        if hdr.class == 0 && is_string_tag(hdr.tag) {
+            let (rem, _) = cer_check_segments(i, rem, hdr.tag)?;
+            return Ok((rem, tag));
+        }
+        let mut elements = Vec::new();
+        let mut c = rem;
+        while !c.starts_with(&[0, 0]) {
+            let (r, t) = cer_check_element(c, depth + 1)?;
+            elements.push((t, &c[..c.len() - r.len()]));
+            c = r;
+        }
+        // components of SET values are sorted (9.3 and 11.6)
+        if hdr.class == 0 && hdr.tag == BerTag::Set {
+            for pair in elements.windows(2) {
+                cer_constraint_fail_if!(pair[1].1, !is_set_sorted(DerSetOrder::Any, pair[0], pair[1]));
+            }
+        }
+        Ok((&c[2..], tag))
+    } else {
+        let len = cer_definite_length(i, hdr.len)?;
+        let (rem, content) = ber_take(rem, len)?;
+        if hdr.class == 0 {
+            cer_check_primitive(i, hdr.tag, content)?;
+        }
+        Ok((rem, tag))
+    }
+}
+
+#[inline]
+fn cer_definite_length(i: &[u8], len: BerSize) -> Result<usize, ::nom::Err<&[u8], ParseError>> {
+    len.primitive()
+        .map_err(|_| ::nom::Err::Error(error_position!(i, BerError::CerConstraintFailed.into_error_kind())))
+}
+
+/// Verify the segments of a constructed string (X.690 section 9.2)
+///
+/// `i` is the start of the string, and `rem` the start of the content.
+fn cer_check_segments<'a>(i: &'a [u8], rem: &'a [u8], tag: BerTag) -> BerResult<'a, ()> {
+    let mut segments = Vec::new();
+    let mut c = rem;
+    while !c.starts_with(&[0, 0]) {
+        let (r, hdr) = ber_read_element_header(c)?;
+        // segments are primitive
+        cer_constraint_fail_if!(c, hdr.is_constructed());
+        cer_constraint_fail_if!(c, !is_minimal_length(&c[..c.len() - r.len()]));
+        let (r, content) = ber_take(r, cer_definite_length(c, hdr.len)?)?;
+        segments.push((c, content));
+        c = r;
+    }
+    // strings of up to 1000 octets use the primitive form
+    cer_constraint_fail_if!(i, segments.len() < 2);
+    let (last, segments) = segments.split_last().expect("segments is not empty");
+    // all segments but the last one have 1000 octets
+    for &(seg, content) in segments {
+        cer_constraint_fail_if!(seg, content.len() != CER_MAX_SEGMENT_SIZE);
+        if tag == BerTag::BitString {
+            cer_constraint_fail_if!(seg, content[0] != 0);
+        }
+    }
+    let (seg, content) = *last;
+    cer_constraint_fail_if!(seg, content.is_empty() || content.len() > CER_MAX_SEGMENT_SIZE);
+    if tag == BerTag::BitString {
+        cer_check_primitive(seg, tag, content)?;
+    }
+    Ok((&c[2..], ()))
+}
+
+/// Verify the constraints on the content of a primitive object (X.690 sections 9.2 and 11)
+fn cer_check_primitive<'a>(i: &'a [u8], tag: BerTag, content: &[u8]) -> BerResult<'a, ()> {
+    if is_string_tag(tag) {
+        cer_constraint_fail_if!(i, content.len() > CER_MAX_SEGMENT_SIZE);
+    }
+    let res = match tag {
+        BerTag::Boolean => {
+            cer_constraint_fail_if!(i, content != [0x00] && content != [0xff]);
+            Ok(())
+        }
+        BerTag::Integer | BerTag::Enumerated => {
+            cer_constraint_fail_if!(i, !is_minimal_integer(content));
+            Ok(())
+        }
+        BerTag::BitString => {
+            // unused bits are zero (11.2.1)
+            if let Some((&ignored_bits, data)) = content.split_first() {
+                if let Some(&last) = data.last() {
+                    let mask = ((1u16 << ignored_bits.min(8)) - 1) as u8;
+                    cer_constraint_fail_if!(i, last & mask != 0);
+                }
+            }
+            Ok(())
+        }
+        BerTag::RealType => der_check_real(content),
+        BerTag::UtcTime => BerDateTime::from_utctime(content, true).map(|_| ()),
+        BerTag::GeneralizedTime => BerDateTime::from_generalizedtime(content, true).map(|_| ()),
+        BerTag::Date => BerDate::from_bytes(content, true).map(|_| ()),
+        BerTag::TimeOfDay => BerTimeOfDay::from_bytes(content, true).map(|_| ()),
+        BerTag::DateTime => BerDateTime::from_datetime(content, true).map(|_| ()),
+        _ => Ok(()),
+    };
+    match res {
+        Ok(()) => Ok((i, ())),
+        // the restrictions of DER on values also apply to CER
+        Err(BerError::DerConstraintFailed) => {
+            Err(::nom::Err::Error(error_position!(i, BerError::CerConstraintFailed.into_error_kind())))
+        }
+        Err(e) => Err(::nom::Err::Error(error_position!(i, e.into_error_kind()))),
+    }
+}
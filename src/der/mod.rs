@@ -3,7 +3,11 @@
 use crate::ber::{BerObject, BerTag};
 
 mod parser;
+mod transcode;
+mod validate;
 pub use der::parser::*;
+pub use der::transcode::*;
+pub use der::validate::*;
 
 /// DER tag (same as BER tag)
 pub type DerTag = BerTag;
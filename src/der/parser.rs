@@ -1,16 +1,44 @@
 use crate::ber::*;
 use crate::error::*;
 use der::DerObject;
-use nom::{be_u8, Context, Err, ErrorKind, IResult, Needed};
+use nom::{Context, Err, ErrorKind, Needed};
+use std::borrow::Cow;
 
 /// Parse DER object
-pub fn parse_der(i: &[u8]) -> IResult<&[u8], DerObject, u32> {
+///
+/// Structured objects are parsed recursively, and the DER constraints are verified for all
+/// elements. The elements of SET values must be sorted (X.690 section 11.6).
+pub fn parse_der(i: &[u8]) -> DerResult {
+    ber_locate_error(i, parse_der_recursive(i, 0, DerSetOrder::Any))
+}
+
+/// Parse DER object, without checking the ordering of the elements of SET values
+///
+/// All other DER constraints are verified. This can be used to read data from encoders which do
+/// not sort SET values.
+pub fn parse_der_unordered(i: &[u8]) -> DerResult {
+    ber_locate_error(i, parse_der_recursive(i, 0, DerSetOrder::Unordered))
+}
+
+/// Parse DER object, checking the limits of `options`
+///
+/// See [`parse_ber_with_options`](../ber/fn.parse_ber_with_options.html). The indefinite length
+/// form is always rejected, as required by DER.
+pub fn parse_der_with_options<'a>(i: &'a [u8], options: &ParserOptions) -> DerResult<'a> {
+    ber_locate_error(
+        i,
+        ber_check_options(i, options, true)
+            .and_then(|_| parse_der_recursive(i, MAX_RECURSION - options.max_depth, DerSetOrder::Any)),
+    )
+}
+
+fn parse_der_recursive(i: &[u8], depth: usize, order: DerSetOrder) -> DerResult {
     do_parse! {
         i,
         hdr:     der_read_element_header >>
                  // XXX safety check: length cannot be more than 2^32 bytes
-                 error_if!(hdr.len > ::std::u32::MAX as u64, ErrorKind::Custom(BER_INVALID_LENGTH)) >>
-        content: apply!(der_read_element_content,hdr) >>
+                 error_if!(hdr.len.primitive().map_or(true, |l| l > ::std::u32::MAX as usize), BerError::InvalidLength.into_error_kind()) >>
+        content: apply!(der_read_element_content_recursive, hdr, depth, order) >>
         ( content )
     }
 }
@@ -21,26 +49,97 @@ macro_rules! der_constraint_fail_if(
     ($slice:expr, $cond:expr) => (
         {
             if $cond {
-                return Err(::nom::Err::Error(error_position!($slice, ErrorKind::Custom(DER_CONSTRAINT_FAIL))));
+                return Err(::nom::Err::Error(error_position!($slice, BerError::DerConstraintFailed.into_error_kind())));
             }
         }
     );
 );
 
+/// Ordering of the elements of a SET value
+///
+/// DER requires the components of a SET to be sorted by tag (X.690 section 10.3), and the
+/// elements of a SET OF to be sorted by encoding (X.690 section 11.6).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DerSetOrder {
+    /// SET: the components are sorted by tag (class, then number)
+    Set,
+    /// SET OF: the encodings of the elements are sorted, compared as octet strings
+    SetOf,
+    /// The type is not known: consecutive elements are sorted either by tag, or by encoding
+    Any,
+    /// The ordering is not checked
+    Unordered,
+}
+
+/// Test if two consecutive elements of a SET value, given as (class and tag, encoding), are sorted
+pub(crate) fn is_set_sorted(
+    order: DerSetOrder,
+    prev: ((u8, u32), &[u8]),
+    next: ((u8, u32), &[u8]),
+) -> bool {
+    match order {
+        DerSetOrder::Set => prev.0 <= next.0,
+        DerSetOrder::SetOf => prev.1 <= next.1,
+        DerSetOrder::Any => prev.0 < next.0 || prev.1 <= next.1,
+        DerSetOrder::Unordered => true,
+    }
+}
+
+/// Check the ordering of the elements of a SET value
+///
+/// `i` is the content of the SET. The input is not consumed: this function can be used before
+/// parsing the elements. If the elements cannot be delimited, no error is returned, since parsing
+/// them will report the error.
+pub fn der_check_set_order(i: &[u8], order: DerSetOrder) -> BerResult<()> {
+    if order == DerSetOrder::Unordered {
+        return Ok((i, ()));
+    }
+    let mut rem = i;
+    let mut prev: Option<((u8, u32), &[u8])> = None;
+    while !rem.is_empty() {
+        let r = match ber_read_element_header(rem) {
+            Ok((r, hdr)) => {
+                let next = match hdr.len {
+                    BerSize::Definite(l) => ber_take(r, l).map(|(r, _)| r),
+                    BerSize::Indefinite => parse_ber_recursive(rem, 0).map(|(r, _)| r),
+                };
+                match next {
+                    Ok(next) => {
+                        let elt = &rem[..rem.len() - next.len()];
+                        let tag = (hdr.class, hdr.tag.0);
+                        if let Some(prev) = prev {
+                            der_constraint_fail_if!(rem, !is_set_sorted(order, prev, (tag, elt)));
+                        }
+                        prev = Some((tag, elt));
+                        next
+                    }
+                    Err(_) => break,
+                }
+            }
+            Err(_) => break,
+        };
+        rem = r;
+    }
+    Ok((i, ()))
+}
+
 /// Parse a DER object, expecting a value with specificed tag
-pub fn parse_der_with_tag(i: &[u8], tag: BerTag) -> IResult<&[u8], BerObject> {
-    do_parse! {
+pub fn parse_der_with_tag(i: &[u8], tag: BerTag) -> BerResult {
+    ber_locate_error(
         i,
-        hdr: der_read_element_header >>
-             error_if!(hdr.tag != tag, ErrorKind::Custom(BER_TAG_ERROR)) >>
-        o:   apply!(der_read_element_content_as, hdr.tag, hdr.len as usize, hdr.is_constructed(), 0) >>
-        ( BerObject::from_header_and_content(hdr, o) )
-    }
+        do_parse! {
+            i,
+            hdr: der_read_element_header >>
+                 error_if!(hdr.tag != tag, BerError::UnexpectedTag{ expected: Some(tag), actual: hdr.tag }.into_error_kind()) >>
+            o:   apply!(der_read_element_content_as, hdr.tag, hdr.len, hdr.is_constructed(), 0) >>
+            ( BerObject::from_header_and_content(hdr, o) )
+        },
+    )
 }
 
 /// Read end of content marker
 #[inline]
-pub fn parse_der_endofcontent(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_endofcontent(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::EndOfContent)
 }
 
@@ -52,14 +151,14 @@ pub fn parse_der_endofcontent(i: &[u8]) -> IResult<&[u8], BerObject> {
 /// If the boolean value is FALSE, the octet shall be zero.
 /// If the boolean value is TRUE, the octet shall be one byte, and have all bits set to one (0xff).
 #[inline]
-pub fn parse_der_bool(i: &[u8]) -> IResult<&[u8], DerObject> {
+pub fn parse_der_bool(i: &[u8]) -> DerResult {
     parse_der_with_tag(i, BerTag::Boolean)
 }
 
 /// Read an integer value
 ///
-/// The encoding of a boolean value shall be primitive. The contents octets shall consist of one or
-/// more octets.
+/// The encoding of an integer value shall be primitive. The contents octets shall consist of one or
+/// more octets, and the first nine bits shall not be all ones or all zeros (X.690 section 8.3.2).
 ///
 /// To access the content, use the [`as_u64`](struct.BerObject.html#method.as_u64),
 /// [`as_u32`](struct.BerObject.html#method.as_u32),
@@ -76,10 +175,11 @@ pub fn parse_der_bool(i: &[u8]) -> IResult<&[u8], DerObject> {
 /// # use nom::IResult;
 /// # use der_parser::der::{parse_der_integer, DerObject};
 /// # use der_parser::ber::BerObjectContent;
+/// # use std::borrow::Cow;
 /// # fn main() {
 /// let empty = &b""[..];
 /// let bytes = [0x02, 0x03, 0x01, 0x00, 0x01];
-/// let expected  = DerObject::from_obj(BerObjectContent::Integer(b"\x01\x00\x01"));
+/// let expected  = DerObject::from_obj(BerObjectContent::Integer(Cow::Borrowed(b"\x01\x00\x01")));
 /// assert_eq!(
 ///     parse_der_integer(&bytes),
 ///     Ok((empty, expected))
@@ -87,55 +187,69 @@ pub fn parse_der_bool(i: &[u8]) -> IResult<&[u8], DerObject> {
 /// # }
 /// ```
 #[inline]
-pub fn parse_der_integer(i: &[u8]) -> IResult<&[u8], DerObject> {
+pub fn parse_der_integer(i: &[u8]) -> DerResult {
     parse_der_with_tag(i, BerTag::Integer)
 }
 
 /// Read an bitstring value
-pub fn parse_der_bitstring(i: &[u8]) -> IResult<&[u8], DerObject> {
-    do_parse! {
+pub fn parse_der_bitstring(i: &[u8]) -> DerResult {
+    ber_locate_error(
         i,
-        hdr: der_read_element_header >>
-             error_if!(hdr.tag != BerTag::BitString, ErrorKind::Custom(BER_TAG_ERROR)) >>
-             error_if!(hdr.is_constructed(), ErrorKind::Custom(DER_CONSTRAINT_FAIL)) >>
-        b:   apply!(der_read_content_bitstring, hdr.len as usize) >>
-        ( DerObject::from_header_and_content(hdr, b) )
-    }
+        do_parse! {
+            i,
+            hdr: der_read_element_header >>
+                 error_if!(hdr.tag != BerTag::BitString, BerError::UnexpectedTag{ expected: Some(BerTag::BitString), actual: hdr.tag }.into_error_kind()) >>
+                 error_if!(hdr.is_constructed(), BerError::DerConstraintFailed.into_error_kind()) >>
+            b:   apply!(der_read_element_content_as, BerTag::BitString, hdr.len, false, 0) >>
+            ( DerObject::from_header_and_content(hdr, b) )
+        },
+    )
 }
 
 /// Read an octetstring value
 #[inline]
-pub fn parse_der_octetstring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_octetstring(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::OctetString)
 }
 
 /// Read a null value
 #[inline]
-pub fn parse_der_null(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_null(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::Null)
 }
 
 /// Read an object identifier value
 #[inline]
-pub fn parse_der_oid(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_oid(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::Oid)
 }
 
+/// Read a real value
+///
+/// The DER restrictions are checked (X.690 section 11.3): binary encodings use base 2, no
+/// scaling factor and an odd mantissa, and decimal encodings use the canonical NR3 form.
+#[inline]
+pub fn parse_der_real(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::RealType)
+}
+
 /// Read an enumerated value
+///
+/// The same encoding rules as for integers apply.
 #[inline]
-pub fn parse_der_enum(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_enum(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::Enumerated)
 }
 
 /// Read a UTF-8 string value
 #[inline]
-pub fn parse_der_utf8string(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_utf8string(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::Utf8String)
 }
 
 /// Read a relative object identifier value
 #[inline]
-pub fn parse_der_relative_oid(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_relative_oid(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::RelativeOid)
 }
 
@@ -148,7 +262,7 @@ pub fn parse_der_relative_oid(i: &[u8]) -> IResult<&[u8], BerObject> {
 /// To read a specific sequence of objects (giving the expected types), use the
 /// [`parse_ber_sequence_defined`](macro.parse_ber_sequence_defined.html) macro.
 #[inline]
-pub fn parse_der_sequence(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_sequence(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::Sequence)
 }
 
@@ -158,123 +272,219 @@ pub fn parse_der_sequence(i: &[u8]) -> IResult<&[u8], BerObject> {
 /// Set is parsed recursively, so if structured elements are found, they are parsed using the
 /// same function.
 ///
+/// The elements must be sorted, either by tag (SET) or by encoding (SET OF). Use
+/// [`parse_der_set_unordered`](fn.parse_der_set_unordered.html) to accept any ordering.
+///
 /// To read a specific set of objects (giving the expected types), use the
 /// [`parse_ber_set_defined`](macro.parse_ber_set_defined.html) macro.
 #[inline]
-pub fn parse_der_set(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_set(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::Set)
 }
 
+/// Parse a set of DER elements, without checking the ordering of the elements
+///
+/// See [`parse_der_unordered`](fn.parse_der_unordered.html).
+pub fn parse_der_set_unordered(i: &[u8]) -> BerResult {
+    ber_locate_error(
+        i,
+        do_parse! {
+            i,
+            hdr: der_read_element_header >>
+                 error_if!(hdr.tag != BerTag::Set, BerError::UnexpectedTag{ expected: Some(BerTag::Set), actual: hdr.tag }.into_error_kind()) >>
+            o:   apply!(der_read_element_content_as_ordered, hdr.tag, hdr.len, hdr.is_constructed(), 0, DerSetOrder::Unordered) >>
+            ( BerObject::from_header_and_content(hdr, o) )
+        },
+    )
+}
+
 /// Read a numeric string value
 #[inline]
-pub fn parse_der_numericstring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_numericstring(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::NumericString)
 }
 
 /// Read a printable string value
 #[inline]
-pub fn parse_der_printablestring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_printablestring(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::PrintableString)
 }
 
 /// Read a T61 string value
 #[inline]
-pub fn parse_der_t61string(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_t61string(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::T61String)
 }
 
+/// Read a Videotex string value
+#[inline]
+pub fn parse_der_videotexstring(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::VideotexString)
+}
+
 /// Read an IA5 string value
 #[inline]
-pub fn parse_der_ia5string(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_ia5string(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::Ia5String)
 }
 
 /// Read an UTC time value
 #[inline]
-pub fn parse_der_utctime(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_utctime(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::UtcTime)
 }
 
 /// Read a Generalized time value
 #[inline]
-pub fn parse_der_generalizedtime(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_generalizedtime(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::GeneralizedTime)
 }
 
+/// Read a TIME value
+///
+/// Unlike the other time types, the value is not checked if it is a time interval or
+/// recurrence.
+#[inline]
+pub fn parse_der_time(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::Time)
+}
+
+/// Read a DATE value
+#[inline]
+pub fn parse_der_date(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::Date)
+}
+
+/// Read a TIME-OF-DAY value
+#[inline]
+pub fn parse_der_timeofday(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::TimeOfDay)
+}
+
+/// Read a DATE-TIME value
+#[inline]
+pub fn parse_der_datetime(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::DateTime)
+}
+
+/// Read a DURATION value
+#[inline]
+pub fn parse_der_duration(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::Duration)
+}
+
+/// Read a GraphicString value
+#[inline]
+pub fn parse_der_graphicstring(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::GraphicString)
+}
+
+/// Read a VisibleString value
+#[inline]
+pub fn parse_der_visiblestring(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::VisibleString)
+}
+
 /// Read a GeneralString value
 #[inline]
-pub fn parse_der_generalstring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_generalstring(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::GeneralString)
 }
 
+/// Read a UniversalString value
+#[inline]
+pub fn parse_der_universalstring(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::UniversalString)
+}
+
+/// Read an unrestricted CHARACTER STRING value
+#[inline]
+pub fn parse_der_characterstring(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::CharacterString)
+}
+
 /// Read a BmpString value
 #[inline]
-pub fn parse_der_bmpstring(i: &[u8]) -> IResult<&[u8], BerObject> {
+pub fn parse_der_bmpstring(i: &[u8]) -> BerResult {
     parse_der_with_tag(i, BerTag::BmpString)
 }
 
+/// Read an ObjectDescriptor value
+#[inline]
+pub fn parse_der_objectdescriptor(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::ObjDescriptor)
+}
+
+/// Read an EXTERNAL value
+#[inline]
+pub fn parse_der_external(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::External)
+}
+
+/// Read an EMBEDDED PDV value
 #[inline]
-pub fn parse_der_explicit<F>(i: &[u8], tag: BerTag, f: F) -> IResult<&[u8], DerObject, u32>
+pub fn parse_der_embedded_pdv(i: &[u8]) -> BerResult {
+    parse_der_with_tag(i, BerTag::EmbeddedPdv)
+}
+
+#[inline]
+pub fn parse_der_explicit<F>(i: &[u8], tag: BerTag, f: F) -> DerResult
 where
-    F: Fn(&[u8]) -> IResult<&[u8], DerObject, u32>,
+    F: Fn(&[u8]) -> DerResult,
 {
     parse_ber_explicit(i, tag, f)
 }
 
 #[inline]
-pub fn parse_der_implicit<F>(i: &[u8], tag: BerTag, f: F) -> IResult<&[u8], DerObject, u32>
+pub fn parse_der_implicit<F>(i: &[u8], tag: BerTag, f: F) -> DerResult
 where
-    F: Fn(&[u8], BerTag, usize) -> IResult<&[u8], BerObjectContent, u32>,
+    F: Fn(&[u8], BerTag, BerSize) -> BerResult<BerObjectContent>,
 {
     parse_ber_implicit(i, tag, f)
 }
 
 /// Parse DER object and try to decode it as a 32-bits unsigned integer
-pub fn parse_der_u32(i: &[u8]) -> IResult<&[u8], u32> {
-    match parse_ber_integer(i) {
-        Ok((rem, ref obj)) => match obj.content {
-            BerObjectContent::Integer(i) => match i.len() {
-                1 => Ok((rem, i[0] as u32)),
-                2 => Ok((rem, (i[0] as u32) << 8 | (i[1] as u32))),
-                3 => Ok((
-                    rem,
-                    (i[0] as u32) << 16 | (i[1] as u32) << 8 | (i[2] as u32),
-                )),
-                4 => Ok((
-                    rem,
-                    (i[0] as u32) << 24 | (i[1] as u32) << 16 | (i[2] as u32) << 8 | (i[3] as u32),
-                )),
-                _ => Err(Err::Error(error_position!(
-                    i,
-                    ErrorKind::Custom(BER_INTEGER_TOO_LARGE)
-                ))),
-            },
-            _ => Err(Err::Error(error_position!(
-                i,
-                ErrorKind::Custom(BER_TAG_ERROR)
-            ))),
-        },
-        Err(e) => Err(e),
-    }
+///
+/// Negative values are rejected.
+pub fn parse_der_u32(i: &[u8]) -> BerResult<u32> {
+    ber_locate_error(i, read_integer_as(i, parse_der_integer, BerObjectContent::as_u32))
 }
 
 /// Parse DER object and try to decode it as a 64-bits unsigned integer
-pub fn parse_der_u64(i: &[u8]) -> IResult<&[u8], u64> {
-    match parse_ber_integer(i) {
-        Ok((rem, ref obj)) => match obj.content {
-            BerObjectContent::Integer(i) => match bytes_to_u64(i) {
-                Ok(l) => Ok((rem, l)),
-                Err(_) => Err(Err::Error(error_position!(
-                    i,
-                    ErrorKind::Custom(BER_INTEGER_TOO_LARGE)
-                ))),
-            },
-            _ => Err(Err::Error(error_position!(
-                i,
-                ErrorKind::Custom(BER_TAG_ERROR)
-            ))),
-        },
-        Err(e) => Err(e),
+///
+/// Negative values are rejected.
+pub fn parse_der_u64(i: &[u8]) -> BerResult<u64> {
+    ber_locate_error(i, read_integer_as(i, parse_der_integer, BerObjectContent::as_u64))
+}
+
+/// Parse DER object and try to decode it as a 32-bits signed integer
+pub fn parse_der_i32(i: &[u8]) -> BerResult<i32> {
+    ber_locate_error(i, read_integer_as(i, parse_der_integer, BerObjectContent::as_i32))
+}
+
+/// Parse DER object and try to decode it as a 64-bits signed integer
+pub fn parse_der_i64(i: &[u8]) -> BerResult<i64> {
+    ber_locate_error(i, read_integer_as(i, parse_der_integer, BerObjectContent::as_i64))
+}
+
+/// Parse an integer using `parse`, and convert it using `f`
+///
+/// Conversion errors are reported at the position of the content.
+pub(crate) fn read_integer_as<'a, T, P, F>(i: &'a [u8], parse: P, f: F) -> BerResult<'a, T>
+where
+    P: Fn(&'a [u8]) -> BerResult<'a>,
+    F: Fn(&BerObjectContent<'a>) -> Result<T, BerError>,
+{
+    let (rem, obj) = parse(i)?;
+    match f(&obj.content) {
+        Ok(v) => Ok((rem, v)),
+        Err(e) => {
+            let content = match obj.content {
+                BerObjectContent::Integer(Cow::Borrowed(s)) => s,
+                _ => i,
+            };
+            Err(Err::Error(error_position!(content, e.into_error_kind())))
+        }
     }
 }
 
@@ -286,47 +496,137 @@ pub fn parse_der_u64(i: &[u8]) -> IResult<&[u8], u64> {
 pub fn der_read_element_content_as(
     i: &[u8],
     tag: BerTag,
-    len: usize,
+    len: BerSize,
     constructed: bool,
     depth: usize,
-) -> IResult<&[u8], BerObjectContent> {
-    if i.len() < len {
-        return Err(Err::Incomplete(Needed::Size(len)));
+) -> BerResult<BerObjectContent> {
+    der_read_element_content_as_ordered(i, tag, len, constructed, depth, DerSetOrder::Any)
+}
+
+fn der_read_element_content_as_ordered(
+    i: &[u8],
+    tag: BerTag,
+    len: BerSize,
+    constructed: bool,
+    depth: usize,
+    order: DerSetOrder,
+) -> BerResult<BerObjectContent> {
+    // DER: only the definite form of length can be used (10.1)
+    let plen = der_definite_length(i, len)?;
+    if i.len() < plen {
+        return Err(Err::Incomplete(Needed::Size(plen)));
     }
     match tag {
+        BerTag::Sequence | BerTag::Set => {
+            error_if!(i, !constructed, BerError::ConstructExpected.into_error_kind())?;
+            error_if!(i, depth > MAX_RECURSION, BerError::BerMaxDepth.into_error_kind())?;
+            // elements are parsed using the DER rules
+            let (rem, data) = ber_take(i, plen)?;
+            if tag == BerTag::Set {
+                der_check_set_order(data, order)?;
+            }
+            let (_, l) = der_read_objects(data, depth + 1, order)?;
+            let content = if tag == BerTag::Set {
+                BerObjectContent::Set(l)
+            } else {
+                BerObjectContent::Sequence(l)
+            };
+            return Ok((rem, content));
+        }
         BerTag::Boolean => {
-            error_if!(i, len != 1, ErrorKind::Custom(BER_INVALID_LENGTH))?;
+            error_if!(i, plen != 1, BerError::InvalidLength.into_error_kind())?;
             der_constraint_fail_if!(i, i[0] != 0 && i[0] != 0xff);
         }
+        BerTag::Integer | BerTag::Enumerated => {
+            // X.690 section 8.3.2: minimal encoding, at least one octet
+            der_constraint_fail_if!(i, !is_minimal_integer(&i[..plen]));
+        }
         BerTag::BitString => {
             der_constraint_fail_if!(i, constructed);
             // exception: read and verify padding bits
-            return der_read_content_bitstring(i, len);
+            return der_read_content_bitstring(i, plen);
         }
         BerTag::NumericString
         | BerTag::PrintableString
         | BerTag::Ia5String
         | BerTag::Utf8String
         | BerTag::T61String
+        | BerTag::VideotexString
+        | BerTag::GraphicString
+        | BerTag::VisibleString
+        | BerTag::UniversalString
         | BerTag::BmpString
+        | BerTag::ObjDescriptor
         | BerTag::GeneralString => {
             der_constraint_fail_if!(i, constructed);
+            // reject characters outside the alphabet of the type
+            let s = &i[..plen];
+            let valid = match tag {
+                BerTag::NumericString => s.iter().all(|&c| is_numeric_char(c)),
+                BerTag::PrintableString => s.iter().all(|&c| is_printable_char(c)),
+                BerTag::Ia5String => s.is_ascii(),
+                BerTag::VisibleString => s.iter().all(|&c| is_visible_char(c)),
+                BerTag::GraphicString | BerTag::ObjDescriptor => {
+                    s.iter().all(|&c| is_graphic_char(c))
+                }
+                BerTag::UniversalString => {
+                    BerObjectContent::UniversalString(Cow::Borrowed(s)).as_str().is_ok()
+                }
+                _ => true,
+            };
+            error_if!(i, !valid, BerError::StringInvalidCharset.into_error_kind())?;
         }
-        BerTag::UtcTime | BerTag::GeneralizedTime => match i.last() {
-            Some(b'Z') => (),
-            _ => {
-                return Err(Err::Error(error_position!(
-                    i,
-                    ErrorKind::Custom(DER_CONSTRAINT_FAIL)
-                )));
+        BerTag::RealType => {
+            der_constraint_fail_if!(i, constructed);
+            if let Err(e) = der_check_real(&i[..plen]) {
+                return Err(Err::Error(error_position!(i, e.into_error_kind())));
             }
-        },
+        }
+        BerTag::UtcTime | BerTag::GeneralizedTime => {
+            der_constraint_fail_if!(i, constructed);
+            // X.690 sections 11.7 and 11.8
+            let res = if tag == BerTag::UtcTime {
+                BerDateTime::from_utctime(&i[..plen], true)
+            } else {
+                BerDateTime::from_generalizedtime(&i[..plen], true)
+            };
+            if let Err(e) = res {
+                return Err(Err::Error(error_position!(i, e.into_error_kind())));
+            }
+        }
+        BerTag::Time | BerTag::Date | BerTag::TimeOfDay | BerTag::DateTime | BerTag::Duration => {
+            der_constraint_fail_if!(i, constructed);
+            let s = &i[..plen];
+            let res = match tag {
+                BerTag::Time => match BerTime::from_bytes(s) {
+                    Err(BerError::Unsupported) => Ok(()),
+                    r => r.map(|_| ()),
+                },
+                BerTag::Date => BerDate::from_bytes(s, true).map(|_| ()),
+                BerTag::TimeOfDay => BerTimeOfDay::from_bytes(s, true).map(|_| ()),
+                BerTag::DateTime => BerDateTime::from_datetime(s, true).map(|_| ()),
+                _ => BerDuration::from_bytes(s).map(|_| ()),
+            };
+            if let Err(e) = res {
+                return Err(Err::Error(error_position!(i, e.into_error_kind())));
+            }
+        }
         _ => (),
     }
     ber_read_element_content_as(i, tag, len, constructed, depth)
 }
 
-pub fn der_read_element_content(i: &[u8], hdr: BerObjectHeader) -> IResult<&[u8], DerObject> {
+pub fn der_read_element_content(i: &[u8], hdr: BerObjectHeader) -> DerResult {
+    der_read_element_content_recursive(i, hdr, 0, DerSetOrder::Any)
+}
+
+fn der_read_element_content_recursive(
+    i: &[u8],
+    hdr: BerObjectHeader,
+    depth: usize,
+    order: DerSetOrder,
+) -> DerResult {
+    let len = der_definite_length(i, hdr.len)?;
     match hdr.class {
         // universal
         0b00 |
@@ -337,30 +637,58 @@ pub fn der_read_element_content(i: &[u8], hdr: BerObjectHeader) -> IResult<&[u8]
         // context-specific
         0b10 => return map!(
             i,
-            take!(hdr.len),
-            |b| { DerObject::from_header_and_content(hdr,BerObjectContent::Unknown(hdr.tag, b)) }
+            apply!(ber_take, len),
+            |b| { DerObject::from_header_and_content(hdr,BerObjectContent::Unknown(hdr.tag, Cow::Borrowed(b))) }
         ),
-        _    => { return Err(Err::Error(error_position!(i, ErrorKind::Custom(BER_CLASS_ERROR)))); },
+        _    => { return Err(Err::Error(error_position!(i, BerError::InvalidClass.into_error_kind()))); },
     }
-    match der_read_element_content_as(i, hdr.tag, hdr.len as usize, hdr.is_constructed(), 0) {
+    match der_read_element_content_as_ordered(i, hdr.tag, hdr.len, hdr.is_constructed(), depth, order) {
         Ok((rem, content)) => Ok((rem, DerObject::from_header_and_content(hdr, content))),
-        Err(Err::Error(Context::Code(_, ErrorKind::Custom(BER_TAG_UNKNOWN)))) => {
-            map!(i, take!(hdr.len), |b| {
-                DerObject::from_header_and_content(hdr, BerObjectContent::Unknown(hdr.tag, b))
+        Err(Err::Error(Context::Code(_, ErrorKind::Custom(ParseError { kind: BerError::UnknownTag, .. })))) => {
+            map!(i, apply!(ber_take, len), |b| {
+                DerObject::from_header_and_content(hdr, BerObjectContent::Unknown(hdr.tag, Cow::Borrowed(b)))
             })
         }
         Err(e) => Err(e),
     }
 }
 
+fn der_read_objects(i: &[u8], depth: usize, order: DerSetOrder) -> BerResult<Vec<BerObject>> {
+    let mut v = Vec::new();
+    let mut rem = i;
+    while !rem.is_empty() {
+        match parse_der_recursive(rem, depth, order) {
+            Ok((r, obj)) => {
+                v.push(obj);
+                rem = r;
+            }
+            // object is larger than the container
+            Err(Err::Incomplete(_)) => {
+                return Err(Err::Error(error_position!(
+                    rem,
+                    BerError::InvalidLength.into_error_kind()
+                )))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok((rem, v))
+}
+
+#[inline]
+fn der_definite_length(i: &[u8], len: BerSize) -> Result<usize, Err<&[u8], ParseError>> {
+    len.primitive()
+        .map_err(|_| Err::Error(error_position!(i, BerError::DerConstraintFailed.into_error_kind())))
+}
+
 #[inline]
-fn der_read_content_bitstring(i: &[u8], len: usize) -> IResult<&[u8], BerObjectContent> {
+fn der_read_content_bitstring(i: &[u8], len: usize) -> BerResult<BerObjectContent> {
     do_parse! {
         i,
-        ignored_bits: be_u8 >>
-                      error_if!(ignored_bits > 7, ErrorKind::Custom(DER_CONSTRAINT_FAIL)) >>
-                      error_if!(len == 0, ErrorKind::Custom(BER_INVALID_LENGTH)) >>
-        s:            take!(len - 1) >>
+        ignored_bits: map!(apply!(ber_take, 1), |b| b[0]) >>
+                      error_if!(ignored_bits > 7, BerError::DerConstraintFailed.into_error_kind()) >>
+                      error_if!(len == 0, BerError::InvalidLength.into_error_kind()) >>
+        s:            apply!(ber_take, len - 1) >>
                       call!(|input| {
                           if len > 1 {
                               let mut last_byte = s[len-2];
@@ -371,39 +699,40 @@ fn der_read_content_bitstring(i: &[u8], len: usize) -> IResult<&[u8], BerObjectC
                           }
                           Ok((input,()))
                       }) >>
-        ( BerObjectContent::BitString(ignored_bits,BitStringObject{ data:s }) )
+        ( BerObjectContent::BitString(ignored_bits,BitStringObject{ data:Cow::Borrowed(s) }) )
     }
 }
 
 /// Read an object header (DER)
-pub fn der_read_element_header(i: &[u8]) -> IResult<&[u8], BerObjectHeader> {
+pub fn der_read_element_header(i: &[u8]) -> BerResult<BerObjectHeader> {
     do_parse! {
         i,
         el:   parse_identifier >>
         len:  parse_ber_length_byte >>
-        llen: cond!(len.0 == 1, take!(len.1)) >>
+        llen: cond!(len.0 == 1, apply!(ber_take, len.1 as usize)) >>
         ( {
-            let len : u64 = match len.0 {
-                0 => len.1 as u64,
+            let len = match len.0 {
+                0 => len.1 as usize,
                 _ => {
                     // if len is 0xff -> error (8.1.3.5)
-                    error_if!(&i[1..], len.1 == 0b0111_1111, ErrorKind::Custom(BER_INVALID_LENGTH))?;
+                    error_if!(&i[1..], len.1 == 0b0111_1111, BerError::InvalidLength.into_error_kind())?;
                     // if len.1 == 0b0111_1111 {
-                    //     return Err(::nom::Err::Error(error_position!(&i[1..], ErrorKind::Custom(BER_INVALID_LENGTH))));
+                    //     return Err(::nom::Err::Error(error_position!(&i[1..], BerError::InvalidLength.into_error_kind())));
                     // }
                     // DER(9.1) if len is 0 (indefinite form), obj must be constructed
                     der_constraint_fail_if!(&i[1..], len.1 == 0 && el.1 != 1);
                     // if len.1 == 0 && el.1 != 1 {
-                    //     return Err(::nom::Err::Error(error_position!(&i[1..], ErrorKind::Custom(BER_INVALID_LENGTH))));
+                    //     return Err(::nom::Err::Error(error_position!(&i[1..], BerError::InvalidLength.into_error_kind())));
                     // }
                     let llen = llen.unwrap(); // safe because we tested len.0 != 0
                     match bytes_to_u64(llen) {
                         Ok(l)  => {
                             // DER: should have been encoded in short form (< 127)
                             der_constraint_fail_if!(i, l < 127);
-                            l
+                            error_if!(llen, l > ::std::usize::MAX as u64, BerError::InvalidLength.into_error_kind())?;
+                            l as usize
                         },
-                        Err(_) => { return Err(::nom::Err::Error(error_position!(llen, ErrorKind::Custom(BER_TAG_ERROR)))); },
+                        Err(_) => { return Err(::nom::Err::Error(error_position!(llen, BerError::InvalidLength.into_error_kind()))); },
                     }
                 },
             };
@@ -411,7 +740,7 @@ pub fn der_read_element_header(i: &[u8]) -> IResult<&[u8], BerObjectHeader> {
                 class: el.0,
                 structured: el.1,
                 tag: BerTag(el.2),
-                len,
+                len: BerSize::Definite(len),
             }
         } )
     }
@@ -0,0 +1,292 @@
+use crate::ber::*;
+use crate::der::{
+    der_read_element_content_as, is_decoded_as_universal, is_minimal_length, is_string_tag, DerViolation,
+    DerViolationKind,
+};
+use crate::error::*;
+use nom::Err;
+
+/// Convert a BER object to DER
+///
+/// The input is parsed using [`parse_ber`](../ber/fn.parse_ber.html), and encoded again with the
+/// following normalizations:
+///
+/// - indefinite lengths are replaced by definite lengths
+/// - lengths and integers are encoded in the minimum number of octets
+/// - constructed strings are flattened into a single primitive string
+/// - boolean TRUE values are encoded as 0xff
+/// - unused bits of BIT STRING values are set to zero
+/// - REAL values use base 2 with an odd mantissa, or the canonical decimal form
+/// - elements of SET and SET OF values are sorted by tag (class, then number), and elements with
+///   the same tag by their (DER) encoding
+///
+/// The type of a SET value is not known, so a SET OF whose elements have different tags is sorted
+/// as a SET: this only differs from the SET OF order (X.690 section 11.6) if a constructed element
+/// has a greater tag number than a primitive element of the same class.
+///
+/// Private tags are converted as universal tags, since [`parse_der`](fn.parse_der.html) decodes
+/// them as universal types.
+///
+/// The content of each object is then verified as [`parse_der`](fn.parse_der.html) does. Other
+/// constraints (for ex. the character set of strings, or the canonical form of times) cannot be
+/// fixed without changing the value: the conversion fails, with the offset of the object in the
+/// input. The result can always be parsed with `parse_der`.
+///
+/// The input must contain exactly one object. Use
+/// [`ber_to_der_with_report`](fn.ber_to_der_with_report.html) to get the list of normalizations.
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # use der_parser::der::ber_to_der;
+/// # fn main() {
+/// // indefinite sequence, containing the boolean 0x01
+/// let bytes = [0x30, 0x80, 0x01, 0x01, 0x01, 0x00, 0x00];
+/// let der = ber_to_der(&bytes).expect("conversion failed");
+/// assert_eq!(der, vec![0x30, 0x03, 0x01, 0x01, 0xff]);
+/// # }
+/// ```
+pub fn ber_to_der(i: &[u8]) -> Result<Vec<u8>, ParseError> {
+    ber_to_der_with_report(i).map(|(v, _)| v)
+}
+
+/// Convert a BER object to DER, and report the normalizations applied
+///
+/// Each normalization is reported as the DER violation it fixes, with the offset of the object
+/// (or of the first element out of order in a SET) in the input. See
+/// [`ber_to_der`](fn.ber_to_der.html) for details.
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # use der_parser::der::{ber_to_der_with_report, DerViolationKind};
+/// # fn main() {
+/// let bytes = [0x30, 0x80, 0x01, 0x01, 0x01, 0x00, 0x00];
+/// let (_, report) = ber_to_der_with_report(&bytes).expect("conversion failed");
+/// assert_eq!(report.len(), 2);
+/// assert_eq!(report[0].kind, DerViolationKind::IndefiniteLength);
+/// assert_eq!(report[1].kind, DerViolationKind::NonCanonicalBoolean);
+/// assert_eq!(report[1].offset, 2);
+/// # }
+/// ```
+pub fn ber_to_der_with_report(i: &[u8]) -> Result<(Vec<u8>, Vec<DerViolation>), ParseError> {
+    let (rem, _) = parse_ber(i).map_err(into_parse_error)?;
+    if !rem.is_empty() {
+        let mut e = ParseError::new(BerError::InvalidLength);
+        e.offset = i.len() - rem.len();
+        return Err(e);
+    }
+    let mut transcoder = Transcoder {
+        input: i,
+        normalizations: Vec::new(),
+    };
+    let (_, (_, v)) = ber_locate_error(i, transcoder.element(i, 0)).map_err(into_parse_error)?;
+    Ok((v, transcoder.normalizations))
+}
+
+struct Transcoder<'a> {
+    input: &'a [u8],
+    normalizations: Vec<DerViolation>,
+}
+
+impl<'a> Transcoder<'a> {
+    fn report(&mut self, kind: DerViolationKind, at: &[u8]) {
+        let offset = at.as_ptr() as usize - self.input.as_ptr() as usize;
+        self.normalizations.push(DerViolation { kind, offset });
+    }
+
+    /// Convert the next element, and return the remaining input, the class and tag of the element,
+    /// and its DER encoding
+    fn element(&mut self, i: &'a [u8], depth: usize) -> BerResult<'a, ((u8, u32), Vec<u8>)> {
+        error_if!(i, depth > MAX_RECURSION, BerError::BerMaxDepth.into_error_kind())?;
+        let (rem, hdr) = ber_read_element_header(i)?;
+        let header = &i[..i.len() - rem.len()];
+        if !is_minimal_length(header) {
+            self.report(DerViolationKind::NonMinimalLength, i);
+        }
+        if hdr.len.is_indefinite() {
+            self.report(DerViolationKind::IndefiniteLength, i);
+        }
+        let universal = is_decoded_as_universal(hdr.class);
+        let mut structured = hdr.structured;
+        let (rem, content) = if hdr.is_constructed() {
+            if universal && is_string_tag(hdr.tag) {
+                self.report(DerViolationKind::ConstructedString, i);
+                structured = 0;
+                let mut data = Vec::new();
+                let mut ignored_bits = 0;
+                let (rem, _) = flatten_segments(rem, hdr.len, depth, &mut data, &mut ignored_bits)?;
+                if hdr.tag == BerTag::BitString {
+                    if clear_unused_bits(&mut data, ignored_bits) {
+                        self.report(DerViolationKind::BitStringPadding, i);
+                    }
+                    data.insert(0, ignored_bits);
+                }
+                (rem, data)
+            } else {
+                self.constructed(rem, hdr, depth)?
+            }
+        } else {
+            let (rem, content) = ber_take(rem, definite_length(i, hdr.len)?)?;
+            let content = if universal {
+                self.primitive(i, hdr.tag, content)
+            } else {
+                content.to_vec()
+            };
+            (rem, content)
+        };
+        // the elements of SEQUENCE and SET values are already checked
+        if universal && (structured == 0 || (hdr.tag != BerTag::Sequence && hdr.tag != BerTag::Set)) {
+            check_content(i, hdr.tag, structured != 0, &content, depth)?;
+        }
+        let mut v = ber_encode_identifier(hdr.class, structured, hdr.tag)
+            .map_err(|_| Err::Error(error_position!(i, BerError::InvalidClass.into_error_kind())))?;
+        v.extend(ber_encode_length(content.len()));
+        v.extend(content);
+        Ok((rem, ((hdr.class, hdr.tag.0), v)))
+    }
+
+    /// Convert the elements of a constructed object, and return the remaining input and the
+    /// concatenated encodings
+    fn constructed(&mut self, rem: &'a [u8], hdr: BerObjectHeader, depth: usize) -> BerResult<'a, Vec<u8>> {
+        let (content, after) = match hdr.len {
+            BerSize::Definite(l) => {
+                let (after, content) = ber_take(rem, l)?;
+                (content, Some(after))
+            }
+            BerSize::Indefinite => (rem, None),
+        };
+        let mut elements = Vec::new();
+        let mut c = content;
+        let rem = loop {
+            match after {
+                Some(after) if c.is_empty() => break after,
+                None if c.starts_with(&[0, 0]) => break &c[2..],
+                _ => (),
+            }
+            let (r, (tag, v)) = self.element(c, depth + 1)?;
+            elements.push((c, tag, v));
+            c = r;
+        };
+        if is_decoded_as_universal(hdr.class) && hdr.tag == BerTag::Set {
+            // sort as SET (10.3), and elements with the same tag as SET OF (11.6)
+            let unsorted = elements.windows(2).find(|pair| (pair[0].1, &pair[0].2) > (pair[1].1, &pair[1].2));
+            if let Some(pair) = unsorted {
+                self.report(DerViolationKind::UnsortedSet, pair[1].0);
+                elements.sort_by(|a, b| (a.1, &a.2).cmp(&(b.1, &b.2)));
+            }
+        }
+        Ok((rem, elements.into_iter().flat_map(|(_, _, v)| v).collect()))
+    }
+
+    /// Convert the content of a primitive object
+    fn primitive(&mut self, i: &[u8], tag: BerTag, content: &[u8]) -> Vec<u8> {
+        match tag {
+            BerTag::Boolean if content.len() == 1 && content[0] != 0 && content[0] != 0xff => {
+                self.report(DerViolationKind::NonCanonicalBoolean, i);
+                vec![0xff]
+            }
+            BerTag::Integer | BerTag::Enumerated if !content.is_empty() && !is_minimal_integer(content) => {
+                self.report(DerViolationKind::NonMinimalInteger, i);
+                let mut s = content;
+                // remove redundant sign extension octets
+                while !is_minimal_integer(s) {
+                    s = &s[1..];
+                }
+                s.to_vec()
+            }
+            BerTag::RealType if der_check_real(content).is_err() => match der_normalize_real(content) {
+                Some(v) => {
+                    self.report(DerViolationKind::NonCanonicalReal, i);
+                    v
+                }
+                None => content.to_vec(),
+            },
+            BerTag::BitString if !content.is_empty() => {
+                let mut v = content.to_vec();
+                if clear_unused_bits(&mut v[1..], content[0]) {
+                    self.report(DerViolationKind::BitStringPadding, i);
+                }
+                v
+            }
+            _ => content.to_vec(),
+        }
+    }
+}
+
+/// Set the `ignored_bits` unused bits of the last octet of `data` to zero (11.2.1), and return
+/// true if they were not already zero
+fn clear_unused_bits(data: &mut [u8], ignored_bits: u8) -> bool {
+    match data.last_mut() {
+        Some(last) if ignored_bits > 0 && ignored_bits < 8 => {
+            let mask = (1u8 << ignored_bits) - 1;
+            let padded = *last & mask != 0;
+            *last &= !mask;
+            padded
+        }
+        _ => false,
+    }
+}
+
+/// Verify the DER content of an object, using the rules of `parse_der`
+///
+/// Fails (at the object in the input) if the content is not valid DER, for ex. if a string
+/// contains characters outside of its character set, or if a time is not in canonical form.
+fn check_content<'a>(i: &'a [u8], tag: BerTag, constructed: bool, content: &[u8], depth: usize) -> BerResult<'a, ()> {
+    match der_read_element_content_as(content, tag, BerSize::Definite(content.len()), constructed, depth) {
+        Ok(_) => Ok((i, ())),
+        Err(e) => match into_parse_error(e).kind {
+            // unknown types are not decoded
+            BerError::UnknownTag => Ok((i, ())),
+            kind => Err(Err::Error(error_position!(i, kind.into_error_kind()))),
+        },
+    }
+}
+
+/// Read the segments of a constructed string, and append their content to `data`
+///
+/// For bit strings, `ignored_bits` is set to the number of unused bits of the last segment (the
+/// unused bits of the other segments are always 0).
+fn flatten_segments<'a>(
+    i: &'a [u8],
+    len: BerSize,
+    depth: usize,
+    data: &mut Vec<u8>,
+    ignored_bits: &mut u8,
+) -> BerResult<'a, ()> {
+    let (content, after) = match len {
+        BerSize::Definite(l) => {
+            let (after, content) = ber_take(i, l)?;
+            (content, Some(after))
+        }
+        BerSize::Indefinite => (i, None),
+    };
+    let mut c = content;
+    loop {
+        match after {
+            Some(after) if c.is_empty() => return Ok((after, ())),
+            None if c.starts_with(&[0, 0]) => return Ok((&c[2..], ())),
+            _ => (),
+        }
+        error_if!(c, depth + 1 > MAX_RECURSION, BerError::BerMaxDepth.into_error_kind())?;
+        let (rem, hdr) = ber_read_element_header(c)?;
+        if hdr.is_constructed() {
+            c = flatten_segments(rem, hdr.len, depth + 1, data, ignored_bits)?.0;
+            continue;
+        }
+        let (rem, s) = ber_take(rem, definite_length(c, hdr.len)?)?;
+        if hdr.tag == BerTag::BitString {
+            if let Some((&b, s)) = s.split_first() {
+                *ignored_bits = b;
+                data.extend_from_slice(s);
+            }
+        } else {
+            data.extend_from_slice(s);
+        }
+        c = rem;
+    }
+}
+
+#[inline]
+fn definite_length(i: &[u8], len: BerSize) -> Result<usize, Err<&[u8], ParseError>> {
+    len.primitive()
+        .map_err(|e| Err::Error(error_position!(i, e.into_error_kind())))
+}
@@ -0,0 +1,295 @@
+use crate::ber::*;
+use crate::der::parser::{is_set_sorted, DerSetOrder};
+use crate::error::*;
+use nom::Err;
+use std::fmt;
+
+/// Kind of deviation from the DER rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DerViolationKind {
+    /// Length is not encoded in the minimum number of octets
+    NonMinimalLength,
+    /// Length uses the indefinite form
+    IndefiniteLength,
+    /// Boolean TRUE value is not encoded as 0xff
+    NonCanonicalBoolean,
+    /// Elements of a SET or SET OF are not sorted
+    UnsortedSet,
+    /// Integer or enumerated value is not encoded in the minimum number of octets
+    NonMinimalInteger,
+    /// Unused bits of a BIT STRING are not zero
+    BitStringPadding,
+    /// REAL value does not use base 2 with an odd mantissa, or the canonical decimal form
+    NonCanonicalReal,
+    /// UTCTime or GeneralizedTime is not in the canonical form (for ex. not ending with `Z`)
+    NonCanonicalTime,
+    /// String type uses the constructed form
+    ConstructedString,
+    /// Input is not valid BER: validation stops at this point
+    Malformed(BerError),
+}
+
+impl DerViolationKind {
+    /// Reference of the violated clause of X.690
+    pub fn clause(&self) -> &'static str {
+        match *self {
+            DerViolationKind::NonMinimalLength => "10.1",
+            DerViolationKind::IndefiniteLength => "10.1",
+            DerViolationKind::NonCanonicalBoolean => "11.1",
+            DerViolationKind::UnsortedSet => "10.3, 11.6",
+            DerViolationKind::NonMinimalInteger => "8.3.2",
+            DerViolationKind::BitStringPadding => "11.2.1",
+            DerViolationKind::NonCanonicalReal => "11.3",
+            DerViolationKind::NonCanonicalTime => "11.7, 11.8",
+            DerViolationKind::ConstructedString => "10.2",
+            DerViolationKind::Malformed(_) => "8",
+        }
+    }
+}
+
+impl fmt::Display for DerViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DerViolationKind::NonMinimalLength => f.write_str("length is not minimally encoded"),
+            DerViolationKind::IndefiniteLength => f.write_str("indefinite length"),
+            DerViolationKind::NonCanonicalBoolean => f.write_str("boolean value is not 0x00 or 0xff"),
+            DerViolationKind::UnsortedSet => f.write_str("set elements are not sorted"),
+            DerViolationKind::NonMinimalInteger => f.write_str("integer is not minimally encoded"),
+            DerViolationKind::BitStringPadding => f.write_str("bit string unused bits are not zero"),
+            DerViolationKind::NonCanonicalReal => f.write_str("real value is not in canonical form"),
+            DerViolationKind::NonCanonicalTime => f.write_str("time is not in canonical form"),
+            DerViolationKind::ConstructedString => f.write_str("string is constructed"),
+            DerViolationKind::Malformed(e) => write!(f, "invalid BER encoding: {}", e),
+        }
+    }
+}
+
+/// A deviation from the DER rules, found by [`validate_der`](fn.validate_der.html)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerViolation {
+    pub kind: DerViolationKind,
+    /// Offset of the object (or of the element of a SET) in the input
+    pub offset: usize,
+}
+
+impl DerViolation {
+    /// Reference of the violated clause of X.690
+    pub fn clause(&self) -> &'static str {
+        self.kind.clause()
+    }
+}
+
+impl fmt::Display for DerViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "offset {}: {} (X.690 {})", self.offset, self.kind, self.clause())
+    }
+}
+
+/// Validate an object against the DER rules, and report all violations
+///
+/// Unlike [`parse_der`](fn.parse_der.html), which stops at the first error, the input is parsed
+/// as BER and every deviation from DER is reported, in the order of the input. If the input is
+/// not valid BER, a `Malformed` violation is reported and validation stops.
+///
+/// Only the first object of the input is validated. An empty result means the object is valid DER
+/// (for the checks listed in [`DerViolationKind`](enum.DerViolationKind.html)).
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # use der_parser::der::{validate_der, DerViolationKind};
+/// # fn main() {
+/// // sequence with a non-minimal length, containing the boolean 0x01
+/// let bytes = [0x30, 0x81, 0x03, 0x01, 0x01, 0x01];
+/// let violations = validate_der(&bytes);
+/// assert_eq!(violations.len(), 2);
+/// assert_eq!(violations[0].kind, DerViolationKind::NonMinimalLength);
+/// assert_eq!(violations[1].kind, DerViolationKind::NonCanonicalBoolean);
+/// assert_eq!(violations[1].offset, 3);
+/// assert_eq!(violations[1].clause(), "11.1");
+/// # }
+/// ```
+pub fn validate_der(i: &[u8]) -> Vec<DerViolation> {
+    let mut validator = Validator {
+        input: i,
+        violations: Vec::new(),
+    };
+    let _ = validator.element(i, 0);
+    validator.violations
+}
+
+struct Validator<'a> {
+    input: &'a [u8],
+    violations: Vec<DerViolation>,
+}
+
+impl<'a> Validator<'a> {
+    fn report(&mut self, kind: DerViolationKind, at: &[u8]) {
+        let offset = at.as_ptr() as usize - self.input.as_ptr() as usize;
+        self.violations.push(DerViolation { kind, offset });
+    }
+
+    fn malformed(&mut self, e: BerError, at: &[u8]) {
+        self.report(DerViolationKind::Malformed(e), at);
+    }
+
+    /// Validate the next element, and return the remaining input and the class and tag of the
+    /// element
+    fn element(&mut self, i: &'a [u8], depth: usize) -> Result<(&'a [u8], (u8, u32)), ()> {
+        if depth > MAX_RECURSION {
+            self.malformed(BerError::BerMaxDepth, i);
+            return Err(());
+        }
+        let (rem, hdr) = match ber_read_element_header(i) {
+            Ok(r) => r,
+            Err(e) => {
+                self.malformed(error_kind(e), i);
+                return Err(());
+            }
+        };
+        self.check_length(i, &i[..i.len() - rem.len()]);
+        let universal = is_decoded_as_universal(hdr.class);
+        let (content, after) = match hdr.len {
+            BerSize::Definite(l) => {
+                if rem.len() < l {
+                    self.malformed(BerError::InvalidLength, i);
+                    return Err(());
+                }
+                (&rem[..l], Some(&rem[l..]))
+            }
+            BerSize::Indefinite => {
+                self.report(DerViolationKind::IndefiniteLength, i);
+                if !hdr.is_constructed() {
+                    self.malformed(BerError::InvalidLength, i);
+                    return Err(());
+                }
+                (rem, None)
+            }
+        };
+        if hdr.is_constructed() {
+            if universal && is_string_tag(hdr.tag) {
+                self.report(DerViolationKind::ConstructedString, i);
+            }
+            let mut elements = Vec::new();
+            let mut c = content;
+            let after = loop {
+                match after {
+                    Some(after) if c.is_empty() => break after,
+                    None if c.starts_with(&[0, 0]) => break &c[2..],
+                    None if c.is_empty() => {
+                        self.malformed(BerError::ObjectTooShort, i);
+                        return Err(());
+                    }
+                    _ => (),
+                }
+                let (r, tag) = self.element(c, depth + 1)?;
+                elements.push((tag, &c[..c.len() - r.len()]));
+                c = r;
+            };
+            if universal && hdr.tag == BerTag::Set {
+                for pair in elements.windows(2) {
+                    if !is_set_sorted(DerSetOrder::Any, pair[0], pair[1]) {
+                        self.report(DerViolationKind::UnsortedSet, pair[1].1);
+                    }
+                }
+            }
+            Ok((after, (hdr.class, hdr.tag.0)))
+        } else {
+            if universal {
+                self.check_primitive(i, hdr.tag, content);
+            }
+            // primitive objects always have a definite length
+            Ok((after.unwrap_or(content), (hdr.class, hdr.tag.0)))
+        }
+    }
+
+    fn check_length(&mut self, i: &[u8], header: &[u8]) {
+        if !is_minimal_length(header) {
+            self.report(DerViolationKind::NonMinimalLength, i);
+        }
+    }
+
+    fn check_primitive(&mut self, i: &[u8], tag: BerTag, content: &[u8]) {
+        let kind = match tag {
+            BerTag::Boolean if content.len() == 1 && content[0] != 0 && content[0] != 0xff => {
+                DerViolationKind::NonCanonicalBoolean
+            }
+            BerTag::Integer | BerTag::Enumerated if !is_minimal_integer(content) => {
+                DerViolationKind::NonMinimalInteger
+            }
+            BerTag::BitString if content.len() > 1 && content[0] > 0 && content[0] < 8 => {
+                let mask = (1u8 << content[0]) - 1;
+                if content[content.len() - 1] & mask == 0 {
+                    return;
+                }
+                DerViolationKind::BitStringPadding
+            }
+            BerTag::RealType if der_check_real(content) == Err(BerError::DerConstraintFailed) => {
+                DerViolationKind::NonCanonicalReal
+            }
+            BerTag::UtcTime | BerTag::GeneralizedTime => {
+                let res = if tag == BerTag::UtcTime {
+                    BerDateTime::from_utctime(content, true)
+                } else {
+                    BerDateTime::from_generalizedtime(content, true)
+                };
+                if res != Err(BerError::DerConstraintFailed) {
+                    return;
+                }
+                DerViolationKind::NonCanonicalTime
+            }
+            _ => return,
+        };
+        self.report(kind, i);
+    }
+}
+
+/// Test if the length is minimally encoded (`header` is the identifier and length octets)
+///
+/// The indefinite form is not checked.
+pub(crate) fn is_minimal_length(header: &[u8]) -> bool {
+    // skip the identifier octets
+    let mut n = 1;
+    if header[0] & 0x1f == 0x1f {
+        while header[n] & 0x80 != 0 {
+            n += 1;
+        }
+        n += 1;
+    }
+    let len = &header[n..];
+    // long form: no leading zero, and only for lengths greater than 127
+    !(len[0] > 0x80 && (len[1] == 0 || (len.len() == 2 && len[1] < 0x80)))
+}
+
+/// Test if objects of this class are decoded as universal types by `parse_der`
+///
+/// This is the case for the universal and private classes.
+pub(crate) fn is_decoded_as_universal(class: u8) -> bool {
+    class == 0b00 || class == 0b11
+}
+
+/// String types, which can use the constructed form in BER
+pub(crate) fn is_string_tag(tag: BerTag) -> bool {
+    matches!(
+        tag,
+        BerTag::BitString
+            | BerTag::OctetString
+            | BerTag::Utf8String
+            | BerTag::NumericString
+            | BerTag::PrintableString
+            | BerTag::T61String
+            | BerTag::VideotexString
+            | BerTag::Ia5String
+            | BerTag::UtcTime
+            | BerTag::GeneralizedTime
+            | BerTag::GraphicString
+            | BerTag::VisibleString
+            | BerTag::GeneralString
+            | BerTag::UniversalString
+            | BerTag::BmpString
+            | BerTag::ObjDescriptor
+    )
+}
+
+fn error_kind(e: Err<&[u8], ParseError>) -> BerError {
+    into_parse_error(e).kind
+}
@@ -1,48 +1,267 @@
-#[derive(Debug, PartialEq)]
+//! Error types for BER/DER parsing
+//!
+//! All parsing functions return a [`BerResult`](type.BerResult.html). On failure, the nom error
+//! contains (as `ErrorKind::Custom`) a [`ParseError`](struct.ParseError.html), which holds the
+//! reason of the error, the byte offset in the input, and the path of the enclosing objects.
+
+use ber::{ber_read_element_header, BerObject, BerObjectHeader, BerSize, BerTag};
+use der::DerObject;
+use nom::{Context, Err, ErrorKind, IResult};
+use std::fmt;
+
+/// Holds the result of parsing functions
+///
+/// `O` is the output type, and defaults to a `BerObject`.
+pub type BerResult<'a, O = BerObject<'a>> = IResult<&'a [u8], O, ParseError>;
+
+/// Holds the result of DER parsing functions
+pub type DerResult<'a> = BerResult<'a, DerObject<'a>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BerError {
     /// Ber object does not have the expected type
     BerTypeError,
     BerValueError,
 
     InvalidTag,
+    /// Unexpected tag (`expected` is `None` if any tag except `actual` would be accepted)
+    UnexpectedTag { expected: Option<BerTag>, actual: BerTag },
+    /// Unknown or unsupported tag
+    UnknownTag,
+    InvalidClass,
+    /// Unexpected class
+    UnexpectedClass { expected: u8, actual: u8 },
     InvalidLength,
+    /// Invalid value for the object type
+    InvalidValue,
+    /// A constructed object was expected
+    ConstructExpected,
+    /// A primitive object was expected
+    ConstructUnexpected,
+    /// Items contained in a structured object do not fill the entire container object
+    ObjectTooShort,
 
     /// Ber integer is too large to fit in a native type. Use `as_bigint()`
     IntegerTooLarge,
+    /// Ber integer is negative, and cannot be read as an unsigned type
+    IntegerNegative,
+
+    /// String contains characters outside the character set of its type
+    StringInvalidCharset,
 
     BerMaxDepth,
+    /// A limit of the [`ParserOptions`](../ber/struct.ParserOptions.html) was exceeded
+    LimitExceeded,
 
     DerConstraintFailed,
+    /// A constraint of the Canonical Encoding Rules was not respected
+    CerConstraintFailed,
 
     Unsupported,
 }
 
 pub type DerError = BerError;
 
-/// Unexpected BER tag
-pub const BER_TAG_ERROR: u32 = 128;
-/// Unexpected BER class
-pub const BER_CLASS_ERROR: u32 = 129;
-/// Unexpected BER structured flag
-pub const BER_STRUCT_ERROR: u32 = 130;
+impl BerError {
+    /// Wrap the error in a nom `ErrorKind`, for use with `error_position!` or `error_if!`
+    #[inline]
+    pub fn into_error_kind(self) -> ErrorKind<ParseError> {
+        ErrorKind::Custom(ParseError::new(self))
+    }
+}
 
-/// Unknown or unsupported BER tag
-pub const BER_TAG_UNKNOWN: u32 = 131;
+impl fmt::Display for BerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BerError::BerTypeError => f.write_str("unexpected object type"),
+            BerError::BerValueError => f.write_str("unexpected object value"),
+            BerError::InvalidTag => f.write_str("invalid tag"),
+            BerError::UnexpectedTag { expected: Some(e), actual } => {
+                write!(f, "unexpected tag (expected {:?}, found {:?})", e, actual)
+            }
+            BerError::UnexpectedTag { expected: None, actual } => {
+                write!(f, "unexpected tag {:?}", actual)
+            }
+            BerError::UnknownTag => f.write_str("unknown or unsupported tag"),
+            BerError::InvalidClass => f.write_str("invalid class"),
+            BerError::UnexpectedClass { expected, actual } => {
+                write!(f, "unexpected class (expected {}, found {})", expected, actual)
+            }
+            BerError::InvalidLength => f.write_str("invalid length"),
+            BerError::InvalidValue => f.write_str("invalid value"),
+            BerError::ConstructExpected => f.write_str("constructed object expected"),
+            BerError::ConstructUnexpected => f.write_str("primitive object expected"),
+            BerError::ObjectTooShort => f.write_str("content does not fill the object"),
+            BerError::IntegerTooLarge => f.write_str("integer too large"),
+            BerError::IntegerNegative => f.write_str("negative integer"),
+            BerError::StringInvalidCharset => f.write_str("invalid character in string"),
+            BerError::BerMaxDepth => f.write_str("maximum recursion depth exceeded"),
+            BerError::LimitExceeded => f.write_str("parser limit exceeded"),
+            BerError::DerConstraintFailed => f.write_str("DER constraint failed"),
+            BerError::CerConstraintFailed => f.write_str("CER constraint failed"),
+            BerError::Unsupported => f.write_str("unsupported object"),
+        }
+    }
+}
 
-/// Invalid length for BER object
-pub const BER_INVALID_LENGTH: u32 = 132;
+impl ::std::error::Error for BerError {}
 
-/// Items contained in a structured object do not fill the entire container object
-pub const BER_OBJ_TOOSHORT: u32 = 133;
+/// Error returned by parsing functions
+///
+/// The error is created with an offset of 0 and an empty path, which are filled by the public
+/// parsing functions (relative to their input) when the error is returned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// Reason of the error
+    pub kind: BerError,
+    /// Offset of the error in the input, in bytes
+    pub offset: usize,
+    /// Headers of the objects containing the error, outermost first
+    pub path: Vec<BerObjectHeader>,
+}
 
-/// Integer too large
-pub const BER_INTEGER_TOO_LARGE: u32 = 134;
+impl ParseError {
+    /// Build an error with an offset of 0 and an empty path
+    pub fn new(kind: BerError) -> ParseError {
+        ParseError {
+            kind,
+            offset: 0,
+            path: Vec::new(),
+        }
+    }
+}
 
-/// Unsupported object (parsing error)
-pub const BER_UNSUPPORTED: u32 = 150;
+impl From<BerError> for ParseError {
+    fn from(kind: BerError) -> ParseError {
+        ParseError::new(kind)
+    }
+}
 
-/// Max recursion depth exceeded
-pub const BER_MAX_DEPTH : u32 = 151;
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.kind, self.offset)?;
+        for (idx, hdr) in self.path.iter().enumerate() {
+            f.write_str(if idx == 0 { " in " } else { " > " })?;
+            match hdr.class {
+                0b00 => write!(f, "{:?}", hdr.tag)?,
+                0b01 => write!(f, "[APPLICATION {}]", hdr.tag.0)?,
+                0b10 => write!(f, "[{}]", hdr.tag.0)?,
+                _ => write!(f, "[PRIVATE {}]", hdr.tag.0)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ::std::error::Error for ParseError {}
 
-/// DER constraint violation
-pub const DER_CONSTRAINT_FAIL: u32 = 160;
+/// Extract the `ParseError` of a parsing error
+///
+/// Errors not created by this crate are reported as `InvalidTag`, and incomplete input as
+/// `ObjectTooShort`.
+pub(crate) fn into_parse_error(e: Err<&[u8], ParseError>) -> ParseError {
+    match e {
+        Err::Error(Context::Code(_, ErrorKind::Custom(e)))
+        | Err::Failure(Context::Code(_, ErrorKind::Custom(e))) => e,
+        Err::Error(Context::List(mut l)) | Err::Failure(Context::List(mut l)) if !l.is_empty() => match l.swap_remove(0).1 {
+            ErrorKind::Custom(e) => e,
+            _ => ParseError::new(BerError::InvalidTag),
+        },
+        Err::Incomplete(_) => ParseError::new(BerError::ObjectTooShort),
+        _ => ParseError::new(BerError::InvalidTag),
+    }
+}
+
+/// Return the headers of the objects of `i` containing the byte at `offset`
+///
+/// Only the headers are read, in a single pass: the end of an indefinite length object is found
+/// by following its elements up to the end-of-content.
+fn ber_error_path(i: &[u8], offset: usize) -> Vec<BerObjectHeader> {
+    // enclosing objects, with the end of their content (or of the enclosing definite object)
+    let mut stack: Vec<(BerObjectHeader, usize)> = Vec::new();
+    // position of the next object in `i`
+    let mut pos = 0;
+    loop {
+        let limit = stack.last().map_or(i.len(), |&(_, limit)| limit);
+        let cur = &i[pos..limit];
+        let (rem, hdr) = match ber_read_element_header(cur) {
+            Ok(res) => res,
+            Err(_) => break,
+        };
+        let content_pos = pos + cur.len() - rem.len();
+        // the error is in the header of this object
+        if offset < content_pos {
+            break;
+        }
+        match hdr.len {
+            // skip objects located before the error
+            BerSize::Definite(l) if l <= rem.len() && content_pos + l <= offset => {
+                pos = content_pos + l;
+                let in_indefinite = stack.last().map_or(false, |&(h, _)| h.len.is_indefinite());
+                if in_indefinite && hdr.tag == BerTag::EndOfContent && l == 0 {
+                    stack.pop();
+                }
+            }
+            // the error is in a primitive object
+            _ if !hdr.is_constructed() => break,
+            // the error is in this object, or the length is larger than the input
+            BerSize::Definite(l) => {
+                let end = if l <= rem.len() { content_pos + l } else { limit };
+                stack.push((hdr, end));
+                pos = content_pos;
+            }
+            // the end is not known yet: read the elements, until the end-of-content
+            BerSize::Indefinite => {
+                stack.push((hdr, limit));
+                pos = content_pos;
+            }
+        }
+    }
+    stack.into_iter().map(|(hdr, _)| hdr).collect()
+}
+
+/// Fill the offset and path of the error of `res`, relative to the input `i`
+///
+/// The `parse_ber_xxx` and `parse_der_xxx` functions already call this function, so the offset
+/// is relative to their input. Parsers built with macros (for ex.
+/// [`parse_der_sequence_defined!`](../macro.parse_der_sequence_defined.html)) can use it to
+/// report offsets relative to the outer object.
+pub fn ber_locate_error<'a, O>(i: &'a [u8], res: BerResult<'a, O>) -> BerResult<'a, O> {
+    ber_locate_error_with(i, res, |e| ber_error_path(i, e.offset))
+}
+
+/// Fill the offset of the error of `res`, relative to the input `i`, and its path using `path`
+///
+/// `path` is called with the error, after setting the offset.
+pub(crate) fn ber_locate_error_with<'a, O, F>(i: &'a [u8], res: BerResult<'a, O>, path: F) -> BerResult<'a, O>
+where
+    F: Fn(&ParseError) -> Vec<BerObjectHeader>,
+{
+    let locate_at = |pos: &[u8], e: &mut ParseError| {
+        let start = i.as_ptr() as usize;
+        let p = pos.as_ptr() as usize;
+        if p >= start && p <= start + i.len() {
+            e.offset = p - start;
+            e.path = path(e);
+        }
+    };
+    let locate = |ctx: Context<&'a [u8], ParseError>| match ctx {
+        Context::Code(pos, ErrorKind::Custom(mut e)) => {
+            locate_at(pos, &mut e);
+            Context::Code(pos, ErrorKind::Custom(e))
+        }
+        // errors in the elements of indefinite length objects: the first entry is the original error
+        Context::List(mut l) => {
+            if let Some(&mut (pos, ErrorKind::Custom(ref mut e))) = l.first_mut() {
+                locate_at(pos, e);
+            }
+            Context::List(l)
+        }
+        ctx => ctx,
+    };
+    res.map_err(|e| match e {
+        Err::Error(ctx) => Err::Error(locate(ctx)),
+        Err::Failure(ctx) => Err::Failure(locate(ctx)),
+        e => e,
+    })
+}
@@ -44,10 +44,11 @@
 //! # #[macro_use] extern crate rusticata_macros;
 //! # #[macro_use] extern crate der_parser;
 //! use der_parser::ber::*;
-//! use nom::{IResult,Err,ErrorKind};
+//! use der_parser::error::BerResult;
+//! use nom::{Err,ErrorKind};
 //!
 //! # fn main() {
-//! fn localparse_seq(i:&[u8]) -> IResult<&[u8],BerObject> {
+//! fn localparse_seq(i:&[u8]) -> BerResult {
 //!     parse_der_sequence_defined!(i,
 //!         parse_ber_integer,
 //!         parse_ber_integer
@@ -61,12 +62,24 @@
 //! # }
 //! ```
 //!
-//! All functions return an `IResult` object from `nom`: the parsed
-//! [`BerObject`](ber/struct.BerObject.html), an `Incomplete` value, or an error.
+//! All functions return a [`BerResult`](error/type.BerResult.html) object (a nom `IResult`): the
+//! parsed [`BerObject`](ber/struct.BerObject.html), an `Incomplete` value, or an error. Errors
+//! contain a [`ParseError`](error/struct.ParseError.html), with the reason, the byte offset in the
+//! input and the path of the enclosing objects.
 //!
 //! # Notes
 //!
-//! - The DER constraints are verified if using `parse_der`.
+//! - The DER constraints are verified if using `parse_der`, including the ordering of SET values.
+//!   Use `parse_der_unordered` to accept non-canonical SET values.
+//! - `validate_der` parses an object as BER, and reports all its deviations from the DER rules.
+//!   `ber_to_der` converts a BER object to DER (definite and minimal lengths, minimal integers,
+//!   flattened strings, canonical booleans, zero unused bits in bit strings and sorted SET values).
+//! - The CER constraints (indefinite length for constructed values, strings split in segments of
+//!   1000 octets, sorted SET values) are verified if using `parse_cer`.
+//! - `parse_ber_with_options` and `parse_der_with_options` accept a `ParserOptions` object, to limit
+//!   the depth, size and number of elements of objects, and reject indefinite lengths or unknown tags.
+//! - `parse_ber_iterative` uses an explicit stack instead of recursion for SEQUENCE and SET values,
+//!   to parse deeply nested objects on small stacks.
 //! - `BerObject` and `DerObject` are the same objects (type alias). The only difference is the
 //!   verification of constraints *during parsing*.
 //! - DER integers can be of any size, so it is not possible to store them as simple integers (they
@@ -75,6 +88,20 @@
 //! return an error if the integer is too large), [`BerObject::as_u64`](ber/struct.BerObject.html#method.as_u64),
 //! or use the `bigint` feature of this crate and use
 //! [`BerObject::as_bigint`](ber/struct.BerObject.html#method.as_bigint).
+//! - UTCTime and GeneralizedTime values can be decoded using
+//!   [`BerObject::as_utctime`](ber/struct.BerObject.html#method.as_utctime) and
+//!   [`BerObject::as_generalizedtime`](ber/struct.BerObject.html#method.as_generalizedtime). The
+//!   `chrono` and `time` features add conversions to the types of these crates.
+//! - TIME, DATE, TIME-OF-DAY, DATE-TIME and DURATION values can be decoded using
+//!   [`BerObject::as_time`](ber/struct.BerObject.html#method.as_time) and similar methods.
+//! - EXTERNAL, EMBEDDED PDV and unrestricted CHARACTER STRING values are decoded into
+//!   [`BerExternal`](ber/struct.BerExternal.html), [`BerEmbeddedPdv`](ber/struct.BerEmbeddedPdv.html)
+//!   and [`BerCharacterString`](ber/struct.BerCharacterString.html).
+//! - OIDs borrow their encoding from the input, and arcs can be of any size. Constant OIDs can be
+//!   built at compile time using the [`oid!`](macro.oid.html) macro.
+//! - The `oid-registry` feature adds the [`oid_registry`](oid_registry/index.html) module, with constants and
+//!   names for well-known OIDs. Applications can register their own OIDs, and the registry can be
+//!   given to `PrettyBer` to show OID names.
 //!
 //! # References
 //!
@@ -102,9 +129,12 @@ extern crate rusticata_macros;
 mod macros;
 
 pub mod ber;
+pub mod cer;
 pub mod der;
 pub mod error;
 pub mod oid;
+#[cfg(feature = "oid-registry")]
+pub mod oid_registry;
 
 // compatibility: re-export at crate root
 pub use ber::parse_ber;
@@ -112,3 +142,9 @@ pub use der::parse_der;
 
 #[cfg(feature = "bigint")]
 extern crate num_bigint;
+
+#[cfg(feature = "chrono")]
+extern crate chrono;
+
+#[cfg(feature = "time")]
+extern crate time;
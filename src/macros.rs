@@ -25,6 +25,42 @@ macro_rules! flat_take (
     });
 );
 
+/// Same as nom's `complete!`, using the crate error type (internal helper)
+///
+/// Converts `Incomplete` into an error, so it can be used with `many0!` or `alt!`.
+/// nom's `complete!` cannot be used in parsers returning a `BerResult`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! ber_complete (
+    ($i:expr, $submac:ident!( $($args:tt)*)) => ({
+        let i_ = $i.clone();
+        match $submac!(i_, $($args)*) {
+            Err(::nom::Err::Incomplete(_)) => {
+                Err(::nom::Err::Error(error_position!($i, ::nom::ErrorKind::Complete)))
+            }
+            rest => rest,
+        }
+    });
+    ($i:expr, $f:expr) => (
+        $crate::ber_complete!($i, call!($f))
+    );
+);
+
+/// Same as rusticata's `empty!`, using the crate error type (internal helper)
+///
+/// Fails with `ObjectTooShort` if the input is not empty.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! ber_empty (
+    ($i:expr,) => ({
+        if $i.is_empty() {
+            Ok(($i, ()))
+        } else {
+            Err(::nom::Err::Error(error_position!($i, $crate::error::BerError::ObjectTooShort.into_error_kind())))
+        }
+    });
+);
+
 /// Internal parser, do not use directly
 #[doc(hidden)]
 #[macro_export]
@@ -76,16 +112,17 @@ macro_rules! fold_der_defined_m(
 #[doc(hidden)]
 #[macro_export]
 macro_rules! parse_ber_defined_m(
-    ($i:expr, $tag:expr, $($args:tt)*) => (
+    ($i:expr, $tag:expr, $order:expr, $($args:tt)*) => (
         {
             use $crate::ber::ber_read_element_header;
             do_parse!(
                 $i,
                 hdr:     ber_read_element_header >>
-                         error_if!(hdr.class != 0b00, ErrorKind::Custom($crate::error::BER_CLASS_ERROR)) >>
-                         error_if!(hdr.structured != 0b1, ErrorKind::Custom($crate::error::BER_STRUCT_ERROR)) >>
-                         error_if!(hdr.tag != $tag, ErrorKind::Custom($crate::error::BER_TAG_ERROR)) >>
-                content: flat_take!(hdr.len as usize, fold_der_defined_m!( $($args)* )) >>
+                         error_if!(hdr.class != 0b00, $crate::error::BerError::UnexpectedClass{ expected: 0, actual: hdr.class }.into_error_kind()) >>
+                         error_if!(hdr.structured != 0b1, $crate::error::BerError::ConstructExpected.into_error_kind()) >>
+                         error_if!(hdr.tag != $tag, $crate::error::BerError::UnexpectedTag{ expected: Some($tag), actual: hdr.tag }.into_error_kind()) >>
+                content: flat_map!(call!($crate::ber::ber_read_content_definite, hdr.len),
+                    preceded!(call!($crate::der::der_check_set_order, $order), fold_der_defined_m!( $($args)* ))) >>
                 (hdr,content)
             )
         }
@@ -105,10 +142,11 @@ macro_rules! parse_ber_defined_m(
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
-/// fn localparse_seq(i:&[u8]) -> IResult<&[u8],BerObject> {
+/// fn localparse_seq(i:&[u8]) -> BerResult {
 ///     parse_der_sequence_defined_m!(i,
 ///         parse_ber_integer >>
 ///         call!(parse_ber_integer)
@@ -131,7 +169,7 @@ macro_rules! parse_der_sequence_defined_m(
     ($i:expr, $($args:tt)*) => ({
         map!(
             $i,
-            parse_ber_defined_m!($crate::ber::BerTag::Sequence, $($args)*),
+            parse_ber_defined_m!($crate::ber::BerTag::Sequence, $crate::der::DerSetOrder::Unordered, $($args)*),
             |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Sequence(o))
         )
     });
@@ -145,15 +183,20 @@ macro_rules! parse_der_sequence_defined_m(
 /// Similar to [`parse_der_set_defined`](macro.parse_der_set_defined.html), but not using `fold`.
 /// This allow using macros.
 ///
+/// The components must be sorted by tag (X.690 section 10.3). Use
+/// [`parse_der_set_defined_m_unordered`](macro.parse_der_set_defined_m_unordered.html) to accept
+/// any ordering.
+///
 /// ```rust
 /// # #[macro_use] extern crate nom;
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
-/// fn localparse_set(i:&[u8]) -> IResult<&[u8],BerObject> {
+/// fn localparse_set(i:&[u8]) -> BerResult {
 ///     parse_der_set_defined_m!(i,
 ///         parse_ber_integer >>
 ///         call!(parse_ber_integer)
@@ -173,13 +216,25 @@ macro_rules! parse_der_sequence_defined_m(
 /// ```
 #[macro_export]
 macro_rules! parse_der_set_defined_m(
-    ($i:expr, $($args:tt)*) => ({
+    (__impl $i:expr, $order:expr, $($args:tt)*) => ({
         map!(
             $i,
-            parse_ber_defined_m!($crate::ber::BerTag::Set, $($args)*),
+            parse_ber_defined_m!($crate::ber::BerTag::Set, $order, $($args)*),
             |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Set(o))
         )
     });
+    ($i:expr, $($args:tt)*) => (
+        parse_der_set_defined_m!(__impl $i, $crate::der::DerSetOrder::Set, $($args)*)
+    );
+);
+
+/// Same as [`parse_der_set_defined_m`](macro.parse_der_set_defined_m.html), but does not check
+/// the ordering of the components
+#[macro_export]
+macro_rules! parse_der_set_defined_m_unordered(
+    ($i:expr, $($args:tt)*) => (
+        parse_der_set_defined_m!(__impl $i, $crate::der::DerSetOrder::Unordered, $($args)*)
+    );
 );
 
 
@@ -209,7 +264,7 @@ macro_rules! fold_parsers(
 #[doc(hidden)]
 #[macro_export]
 macro_rules! parse_der_defined(
-    ($i:expr, $ty:expr, $($args:tt)*) => (
+    ($i:expr, $ty:expr, $order:expr, $($args:tt)*) => (
         {
             use $crate::ber::ber_read_element_header;
             use nom::ErrorKind;
@@ -217,17 +272,17 @@ macro_rules! parse_der_defined(
             do_parse!(
                 $i,
                 hdr:     ber_read_element_header >>
-                         error_if!(hdr.class != 0b00, ErrorKind::Custom($crate::error::BER_CLASS_ERROR)) >>
-                         error_if!(hdr.structured != 0b1, ErrorKind::Custom($crate::error::BER_STRUCT_ERROR)) >>
-                         error_if!(hdr.tag != $ty, ErrorKind::Custom($crate::error::BER_TAG_ERROR)) >>
-                content: take!(hdr.len) >>
+                         error_if!(hdr.class != 0b00, $crate::error::BerError::UnexpectedClass{ expected: 0, actual: hdr.class }.into_error_kind()) >>
+                         error_if!(hdr.structured != 0b1, $crate::error::BerError::ConstructExpected.into_error_kind()) >>
+                         error_if!(hdr.tag != $ty, $crate::error::BerError::UnexpectedTag{ expected: Some($ty), actual: hdr.tag }.into_error_kind()) >>
+                content: call!($crate::ber::ber_read_content_definite, hdr.len) >>
                 (hdr,content)
             );
             match res {
                 Ok((_rem,o)) => {
-                    match fold_parsers!(o.1, $($args)* ) {
+                    match $crate::der::der_check_set_order(o.1, $order).and_then(|(i,_)| fold_parsers!(i, $($args)* )) {
                         Ok((rem,v)) => {
-                            if rem.len() != 0 { Err(::nom::Err::Error(error_position!($i, ErrorKind::Custom($crate::error::BER_OBJ_TOOSHORT)))) }
+                            if rem.len() != 0 { Err(::nom::Err::Error(error_position!($i, $crate::error::BerError::ObjectTooShort.into_error_kind()))) }
                             else { Ok((_rem,(o.0,v))) }
                         },
                         Err(e)      => Err(e)
@@ -253,10 +308,11 @@ macro_rules! parse_der_defined(
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
-/// fn localparse_seq(i:&[u8]) -> IResult<&[u8],BerObject> {
+/// fn localparse_seq(i:&[u8]) -> BerResult {
 ///     parse_der_sequence_defined!(i,
 ///         parse_ber_integer,
 ///         parse_ber_integer
@@ -279,7 +335,7 @@ macro_rules! parse_der_sequence_defined(
     ($i:expr, $($args:tt)*) => (
         map!(
             $i,
-            parse_der_defined!($crate::ber::BerTag::Sequence, $($args)*),
+            parse_der_defined!($crate::ber::BerTag::Sequence, $crate::der::DerSetOrder::Unordered, $($args)*),
             |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Sequence(o))
         )
     );
@@ -294,15 +350,20 @@ macro_rules! parse_der_sequence_defined(
 /// `fold` internally.
 /// Because of that, macros cannot be used as subparsers.
 ///
+/// The components must be sorted by tag (X.690 section 10.3). Use
+/// [`parse_der_set_defined_unordered`](macro.parse_der_set_defined_unordered.html) to accept any
+/// ordering.
+///
 /// ```rust
 /// # #[macro_use] extern crate nom;
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
-/// fn localparse_set(i:&[u8]) -> IResult<&[u8],BerObject> {
+/// fn localparse_set(i:&[u8]) -> BerResult {
 ///     parse_der_set_defined!(i,
 ///         parse_ber_integer,
 ///         parse_ber_integer
@@ -322,13 +383,25 @@ macro_rules! parse_der_sequence_defined(
 /// ```
 #[macro_export]
 macro_rules! parse_der_set_defined(
-    ($i:expr, $($args:tt)*) => (
+    (__impl $i:expr, $order:expr, $($args:tt)*) => (
         map!(
             $i,
-            parse_der_defined!($crate::ber::BerTag::Set, $($args)*),
+            parse_der_defined!($crate::ber::BerTag::Set, $order, $($args)*),
             |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Set(o))
         )
     );
+    ($i:expr, $($args:tt)*) => (
+        parse_der_set_defined!(__impl $i, $crate::der::DerSetOrder::Set, $($args)*)
+    );
+);
+
+/// Same as [`parse_der_set_defined`](macro.parse_der_set_defined.html), but does not check the
+/// ordering of the components
+#[macro_export]
+macro_rules! parse_der_set_defined_unordered(
+    ($i:expr, $($args:tt)*) => (
+        parse_der_set_defined!(__impl $i, $crate::der::DerSetOrder::Unordered, $($args)*)
+    );
 );
 
 /// Parse a sequence of identical DER elements
@@ -340,10 +413,11 @@ macro_rules! parse_der_set_defined(
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
-/// fn parser(i:&[u8]) -> IResult<&[u8],BerObject> {
+/// fn parser(i:&[u8]) -> BerResult {
 ///     parse_der_sequence_of!(i, parse_ber_integer)
 /// };
 /// let empty = &b""[..];
@@ -365,11 +439,11 @@ macro_rules! parse_der_sequence_of(
         do_parse!(
             $i,
             hdr:     ber_read_element_header >>
-                     error_if!(hdr.tag != $crate::ber::BerTag::Sequence, ErrorKind::Custom($crate::error::BER_TAG_ERROR)) >>
-            content: flat_take!(hdr.len as usize,
+                     error_if!(hdr.tag != $crate::ber::BerTag::Sequence, $crate::error::BerError::UnexpectedTag{ expected: Some($crate::ber::BerTag::Sequence), actual: hdr.tag }.into_error_kind()) >>
+            content: flat_map!(call!($crate::ber::ber_read_content_definite, hdr.len),
                 do_parse!(
-                    r: many0!(complete!($f)) >>
-                       empty!() >>
+                    r: many0!(ber_complete!($f)) >>
+                       ber_empty!() >>
                     ( r )
                 )
             ) >>
@@ -382,47 +456,64 @@ macro_rules! parse_der_sequence_of(
 ///
 /// Given a subparser for a DER type, parse a set of identical objects.
 ///
+/// The encodings of the elements must be sorted (X.690 section 11.6). Use
+/// [`parse_der_set_of_unordered`](macro.parse_der_set_of_unordered.html) to accept any ordering.
+///
 /// ```rust
 /// # #[macro_use] extern crate nom;
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
-/// fn parser(i:&[u8]) -> IResult<&[u8],BerObject> {
+/// fn parser(i:&[u8]) -> BerResult {
 ///     parse_der_set_of!(i, parse_ber_integer)
 /// };
 /// let empty = &b""[..];
 /// let bytes = [ 0x31, 0x0a,
-///               0x02, 0x03, 0x01, 0x00, 0x01,
 ///               0x02, 0x03, 0x01, 0x00, 0x00,
+///               0x02, 0x03, 0x01, 0x00, 0x01,
 /// ];
 /// let expected  = BerObject::from_set(vec![
-///     BerObject::from_int_slice(b"\x01\x00\x01"),
 ///     BerObject::from_int_slice(b"\x01\x00\x00"),
+///     BerObject::from_int_slice(b"\x01\x00\x01"),
 /// ]);
 /// assert_eq!(parser(&bytes), Ok((empty, expected)));
 /// # }
 /// ```
 #[macro_export]
 macro_rules! parse_der_set_of(
-    ($i:expr, $f:ident) => ({
+    (__impl $i:expr, $f:ident, $order:expr) => ({
         use $crate::ber::ber_read_element_header;
         do_parse!(
             $i,
             hdr:     ber_read_element_header >>
-                     error_if!(hdr.tag != $crate::ber::BerTag::Set, ErrorKind::Custom($crate::error::BER_TAG_ERROR)) >>
-            content: flat_take!(hdr.len as usize,
+                     error_if!(hdr.tag != $crate::ber::BerTag::Set, $crate::error::BerError::UnexpectedTag{ expected: Some($crate::ber::BerTag::Set), actual: hdr.tag }.into_error_kind()) >>
+            content: flat_map!(call!($crate::ber::ber_read_content_definite, hdr.len),
                 do_parse!(
-                    r: many0!(complete!($f)) >>
-                       empty!() >>
+                       call!($crate::der::der_check_set_order, $order) >>
+                    r: many0!(ber_complete!($f)) >>
+                       ber_empty!() >>
                     ( r )
                 )
             ) >>
             ( $crate::ber::BerObject::from_header_and_content(hdr, $crate::ber::BerObjectContent::Set(content)) )
         )
-    })
+    });
+    ($i:expr, $f:ident) => (
+        parse_der_set_of!(__impl $i, $f, $crate::der::DerSetOrder::SetOf)
+    );
+);
+
+/// Same as [`parse_der_set_of`](macro.parse_der_set_of.html), but does not check the ordering of
+/// the elements
+#[macro_export]
+macro_rules! parse_der_set_of_unordered(
+    ($i:expr, $f:ident) => (
+        parse_der_set_of!(__impl $i, $f, $crate::der::DerSetOrder::Unordered)
+    );
 );
 
 /// Parse an optional DER element
@@ -435,7 +526,8 @@ macro_rules! parse_der_set_of(
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
 /// let empty = &b""[..];
@@ -458,10 +550,10 @@ macro_rules! parse_der_set_of(
 ///     BerObject::from_int_slice(b"\x01\x00\x01"),
 /// ]);
 ///
-/// fn parse_optional_enum(i:&[u8]) -> IResult<&[u8],BerObject> {
+/// fn parse_optional_enum(i:&[u8]) -> BerResult {
 ///     parse_der_optional!(i, parse_ber_enum)
 /// }
-/// fn parser(i:&[u8]) -> IResult<&[u8],BerObject> {
+/// fn parser(i:&[u8]) -> BerResult {
 ///     parse_der_sequence_defined!(i,
 ///         parse_optional_enum,
 ///         parse_ber_integer
@@ -475,16 +567,16 @@ macro_rules! parse_der_set_of(
 #[macro_export]
 macro_rules! parse_der_optional(
     ($i:expr, $f:ident) => (
-        alt_complete!(
+        alt!(
             $i,
-            do_parse!(
+            ber_complete!(do_parse!(
                 content: call!($f) >>
                 (
                     $crate::ber::BerObject::from_obj(
                         $crate::ber::BerObjectContent::ContextSpecific($crate::ber::BerTag(0) /* XXX */,Some(Box::new(content)))
                     )
                 )
-            ) |
+            )) |
             apply!($crate::ber::parse_ber_explicit_failed,$crate::ber::BerTag(0) /* XXX */)
         )
     )
@@ -498,7 +590,7 @@ macro_rules! parse_der_optional(
 /// The returned object is a tuple containing a [`BerObjectHeader`](struct.BerObjectHeader.html)
 /// and the object returned by the subparser.
 ///
-/// To ensure the subparser consumes all bytes from the constructed object, add the `empty!()`
+/// To ensure the subparser consumes all bytes from the constructed object, add the `ber_empty!()`
 /// subparser as the last parsing item.
 ///
 /// To verify the tag of the constructed element, use the `TAG` version, for ex
@@ -517,7 +609,8 @@ macro_rules! parse_der_optional(
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
 /// #[derive(Debug, PartialEq)]
@@ -526,12 +619,12 @@ macro_rules! parse_der_optional(
 ///     b: BerObject<'a>,
 /// }
 ///
-/// fn parse_struct01(i: &[u8]) -> IResult<&[u8],(BerObjectHeader,MyStruct)> {
+/// fn parse_struct01(i: &[u8]) -> BerResult<(BerObjectHeader,MyStruct)> {
 ///     parse_der_struct!(
 ///         i,
 ///         a: parse_ber_integer >>
 ///         b: parse_ber_integer >>
-///            empty!() >>
+///            ber_empty!() >>
 ///         ( MyStruct{ a: a, b: b } )
 ///     )
 /// }
@@ -546,7 +639,7 @@ macro_rules! parse_der_optional(
 ///         class: 0,
 ///         structured: 1,
 ///         tag: BerTag::Sequence,
-///         len: 0xa,
+///         len: BerSize::Definite(0xa),
 ///     },
 ///     MyStruct {
 ///         a: BerObject::from_int_slice(b"\x01\x00\x01"),
@@ -565,20 +658,21 @@ macro_rules! parse_der_optional(
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// # use der_parser::ber::*;
-/// # use nom::{IResult,Err,ErrorKind};
+/// # use der_parser::error::BerResult;
+/// # use nom::{Err,ErrorKind};
 /// # fn main() {
 /// struct MyStruct<'a>{
 ///     a: BerObject<'a>,
 ///     b: BerObject<'a>,
 /// }
 ///
-/// fn parse_struct_with_tag(i: &[u8]) -> IResult<&[u8],(BerObjectHeader,MyStruct)> {
+/// fn parse_struct_with_tag(i: &[u8]) -> BerResult<(BerObjectHeader,MyStruct)> {
 ///     parse_der_struct!(
 ///         i,
 ///         TAG BerTag::Sequence,
 ///         a: parse_ber_integer >>
 ///         b: parse_ber_integer >>
-///            empty!() >>
+///            ber_empty!() >>
 ///         ( MyStruct{ a: a, b: b } )
 ///     )
 /// }
@@ -592,7 +686,7 @@ macro_rules! parse_der_struct(
             $i,
             hdr: verify!(ber_read_element_header, |ref hdr: BerObjectHeader|
                          hdr.structured == 1 && hdr.tag == $tag) >>
-            res: flat_take!(hdr.len as usize, do_parse!( $($rest)* )) >>
+            res: flat_map!(call!($crate::ber::ber_read_content_definite, hdr.len), do_parse!( $($rest)* )) >>
             (hdr,res)
         )
     });
@@ -601,7 +695,7 @@ macro_rules! parse_der_struct(
         do_parse!(
             $i,
             hdr: verify!(ber_read_element_header, |ref hdr: BerObjectHeader| hdr.structured == 1) >>
-            res: flat_take!(hdr.len as usize, do_parse!( $($rest)* )) >>
+            res: flat_map!(call!($crate::ber::ber_read_content_definite, hdr.len), do_parse!( $($rest)* )) >>
             (hdr,res)
         )
     });
@@ -631,10 +725,11 @@ macro_rules! parse_der_struct(
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
-/// fn parse_int_explicit(i:&[u8]) -> IResult<&[u8],u32> {
+/// fn parse_int_explicit(i:&[u8]) -> BerResult<u32> {
 ///     map_res!(
 ///         i,
 ///         parse_der_tagged!(EXPLICIT 2, parse_ber_integer),
@@ -660,10 +755,11 @@ macro_rules! parse_der_struct(
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
-/// fn parse_int_implicit(i:&[u8]) -> IResult<&[u8],u32> {
+/// fn parse_int_implicit(i:&[u8]) -> BerResult<u32> {
 ///     map_res!(
 ///         i,
 ///         parse_der_tagged!(IMPLICIT 2, BerTag::Integer),
@@ -688,7 +784,7 @@ macro_rules! parse_der_tagged(
         do_parse!(
             $i,
             hdr: verify!(ber_read_element_header, |ref hdr: BerObjectHeader| hdr.tag.0 == $tag) >>
-            res: flat_take!(hdr.len as usize, call!( $f )) >>
+            res: flat_map!(call!($crate::ber::ber_read_content_definite, hdr.len), call!( $f )) >>
             (res)
         )
     });
@@ -697,7 +793,7 @@ macro_rules! parse_der_tagged(
         do_parse!(
             $i,
             hdr: verify!(ber_read_element_header, |ref hdr: BerObjectHeader| hdr.tag.0 == $tag) >>
-            res: flat_take!(hdr.len as usize, $submac!( $($args)* )) >>
+            res: flat_map!(call!($crate::ber::ber_read_content_definite, hdr.len), $submac!( $($args)* )) >>
             (res)
         )
     });
@@ -706,7 +802,7 @@ macro_rules! parse_der_tagged(
         do_parse!(
             $i,
             hdr: verify!(ber_read_element_header, |ref hdr: BerObjectHeader| hdr.tag.0 == $tag) >>
-            res: call!(ber_read_element_content_as, $type, hdr.len as usize, hdr.is_constructed(), 0) >>
+            res: call!(ber_read_element_content_as, $type, hdr.len, hdr.is_constructed(), 0) >>
             (BerObject::from_obj(res))
         )
     });
@@ -721,7 +817,7 @@ macro_rules! parse_der_tagged(
 /// The returned object is a tuple containing a [`BerObjectHeader`](struct.BerObjectHeader.html)
 /// and the object returned by the subparser.
 ///
-/// To ensure the subparser consumes all bytes from the constructed object, add the `empty!()`
+/// To ensure the subparser consumes all bytes from the constructed object, add the `ber_empty!()`
 /// subparser as the last parsing item.
 ///
 /// # Examples
@@ -733,19 +829,20 @@ macro_rules! parse_der_tagged(
 /// # #[macro_use] extern crate rusticata_macros;
 /// # #[macro_use] extern crate der_parser;
 /// use der_parser::ber::*;
-/// use nom::{IResult,Err,ErrorKind};
+/// use der_parser::error::BerResult;
+/// use nom::{Err,ErrorKind};
 ///
 /// # fn main() {
 /// #[derive(Debug, PartialEq)]
 /// struct SimpleStruct {
 ///     a: u32,
 /// };
-/// fn parse_app01(i:&[u8]) -> IResult<&[u8],(BerObjectHeader,SimpleStruct)> {
+/// fn parse_app01(i:&[u8]) -> BerResult<(BerObjectHeader,SimpleStruct)> {
 ///     parse_der_application!(
 ///         i,
 ///         APPLICATION 2,
 ///         a: map_res!(parse_ber_integer,|x: BerObject| x.as_u32()) >>
-///            empty!() >>
+///            ber_empty!() >>
 ///         ( SimpleStruct{ a:a } )
 ///     )
 /// }
@@ -771,9 +868,96 @@ macro_rules! parse_der_application(
             $i,
             hdr: verify!(ber_read_element_header, |ref hdr: BerObjectHeader|
                          hdr.class == 0b01 && hdr.tag.0 == $tag) >>
-            res: flat_take!(hdr.len as usize, do_parse!( $($rest)* )) >>
+            res: flat_map!(call!($crate::ber::ber_read_content_definite, hdr.len), do_parse!( $($rest)* )) >>
             (hdr,res)
         )
     });
     ($i:expr, $tag:expr, $($rest:tt)*) => ( parse_der_application!($i, $tag, $($rest)*) );
 );
+
+/// Build a DER sequence object from a list of objects
+///
+/// The objects are moved into the sequence, so the returned object owns its content. Macros can
+/// be nested to build structured objects.
+///
+/// ```rust
+/// # #[macro_use] extern crate der_parser;
+/// use der_parser::ber::*;
+///
+/// # fn main() {
+/// let obj = ber_sequence![
+///     BerObject::from_u64(1),
+///     ber_set![
+///         BerObject::from_bool(true),
+///         BerObject::from_printable("abc"),
+///     ],
+/// ];
+/// assert_eq!(
+///     obj.to_der().expect("serialization failed"),
+///     vec![0x30, 0x0d, 0x02, 0x01, 0x01,
+///          0x31, 0x08, 0x01, 0x01, 0xff, 0x13, 0x03, 0x61, 0x62, 0x63]
+/// );
+/// # }
+/// ```
+#[macro_export]
+macro_rules! ber_sequence(
+    ($($obj:expr),* $(,)*) => (
+        $crate::ber::BerObject::from_seq(vec![ $($obj),* ])
+    );
+);
+
+/// Build a DER set object from a list of objects
+///
+/// The objects are moved into the set, in the same order. Note that DER requires the elements of
+/// a set to be sorted, and this macro does not sort them.
+///
+/// See [`ber_sequence!`](macro.ber_sequence.html) for an example.
+#[macro_export]
+macro_rules! ber_set(
+    ($($obj:expr),* $(,)*) => (
+        $crate::ber::BerObject::from_set(vec![ $($obj),* ])
+    );
+);
+
+/// Build an OID at compile time, from its dotted decimal form
+///
+/// The result is an `Oid<'static>` borrowing a constant encoding, so the macro can be used to
+/// define constants. Use `oid!(rel ...)` for a `RelativeOid`, and `oid!(raw ...)` to get the
+/// encoded content octets as an array. Arcs must fit in a `u64`.
+///
+/// Invalid OIDs are rejected when compiling.
+///
+/// ```rust
+/// # #[macro_use] extern crate der_parser;
+/// # use der_parser::oid::{Oid, RelativeOid};
+/// const OID_SHA256: Oid<'static> = oid!(2.16.840.1.101.3.4.2.1);
+///
+/// # fn main() {
+/// assert_eq!(OID_SHA256, Oid::from(&[2, 16, 840, 1, 101, 3, 4, 2, 1]).unwrap());
+/// assert_eq!(oid!(raw 1.2.840), [0x2a, 0x86, 0x48]);
+/// assert_eq!(oid!(rel 8571.3.2), RelativeOid::from(&[8571, 3, 2]));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! oid {
+    (raw $($arcs:tt)+) => {{
+        const S: &str = stringify!($($arcs)+);
+        const LEN: usize = $crate::oid::encoded_len(S, false);
+        const BYTES: [u8; LEN] = $crate::oid::encode_const::<LEN>(S, false);
+        BYTES
+    }};
+    (rel $($arcs:tt)+) => {{
+        const S: &str = stringify!($($arcs)+);
+        const LEN: usize = $crate::oid::encoded_len(S, true);
+        const BYTES: [u8; LEN] = $crate::oid::encode_const::<LEN>(S, true);
+        const OID: $crate::oid::RelativeOid<'static> =
+            $crate::oid::RelativeOid::new(::std::borrow::Cow::Borrowed(&BYTES));
+        OID
+    }};
+    ($($arcs:tt)+) => {{
+        const BYTES: &[u8] = &$crate::oid!(raw $($arcs)+);
+        const OID: $crate::oid::Oid<'static> =
+            $crate::oid::Oid::new(::std::borrow::Cow::Borrowed(BYTES));
+        OID
+    }};
+}
@@ -1,86 +1,857 @@
 //! Object ID (OID) representation
+//!
+//! An OID is stored as the content octets of its BER encoding (X.690 section 8.19), so arcs of any
+//! size can be represented. Arcs are decoded when iterating, using
+//! [`iter`](struct.Oid.html#method.iter) for arcs fitting in a `u64`, or `iter_bigint` (with the
+//! `bigint` feature) for arcs of any size.
+//!
+//! Parsed OIDs borrow their encoding from the input. Known OIDs can be built at compile time
+//! using the [`oid!`](../macro.oid.html) macro, so comparing a parsed OID to a constant does
+//! not allocate:
+//!
+//! ```rust
+//! # #[macro_use] extern crate der_parser;
+//! # use der_parser::oid::Oid;
+//! # use der_parser::parse_der;
+//! const OID_RSA_SHA256: Oid<'static> = oid!(1.2.840.113549.1.1.11);
+//!
+//! # fn main() {
+//! let bytes = [0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+//! let (_, obj) = parse_der(&bytes).expect("parsing failed");
+//! assert_eq!(obj.as_oid(), Ok(&OID_RSA_SHA256));
+//! # }
+//! ```
 
+use ber::ber_encode_length;
+use error::BerError;
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::fmt;
-use std::slice;
 
 use std::str::FromStr;
-use std::num::ParseIntError;
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
 
 /// Object ID (OID) representation
-#[derive(PartialEq,Eq,Clone)]
-pub struct Oid (Vec<u64>);
+#[derive(PartialEq,Eq,Clone,Hash)]
+pub struct Oid<'a> {
+    asn1: Cow<'a, [u8]>,
+}
+
+/// Relative object ID (RELATIVE-OID) representation
+///
+/// The arcs of a relative OID can be added to an OID using
+/// [`Oid::append`](struct.Oid.html#method.append).
+#[derive(PartialEq, Eq, Clone, Hash)]
+pub struct RelativeOid<'a> {
+    asn1: Cow<'a, [u8]>,
+}
+
+/// Error returned when building an OID from a string or a list of arcs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OidParseError {
+    /// The string is empty
+    Empty,
+    /// An absolute OID must have at least two arcs
+    TooShort,
+    /// The first arc must be 0, 1 or 2
+    InvalidFirstArc,
+    /// The second arc must be lower than 40 if the first arc is 0 or 1
+    SecondArcTooLarge,
+    /// The arc at this index (starting at 0) is empty, is not a decimal number, or has leading
+    /// zeros
+    InvalidArc(usize),
+}
+
+impl fmt::Display for OidParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OidParseError::Empty => f.write_str("empty OID"),
+            OidParseError::TooShort => f.write_str("OID must have at least two arcs"),
+            OidParseError::InvalidFirstArc => f.write_str("first arc of OID must be 0, 1 or 2"),
+            OidParseError::SecondArcTooLarge => {
+                f.write_str("second arc of OID must be lower than 40 under arcs 0 and 1")
+            }
+            OidParseError::InvalidArc(idx) => write!(f, "invalid arc at index {} in OID", idx),
+        }
+    }
+}
+
+impl ::std::error::Error for OidParseError {}
+
+/// Append `n` as a sequence of 7-bit groups, with bit 8 set on all but the last octet
+fn push_base128(v: &mut Vec<u8>, n: u128) {
+    let start = v.len();
+    v.push((n & 0x7f) as u8);
+    let mut n = n >> 7;
+    while n > 0 {
+        v.push(0x80 | (n & 0x7f) as u8);
+        n >>= 7;
+    }
+    v[start..].reverse();
+}
+
+/// Append the encoding of the decimal number `s` plus `add`, whatever its size
+///
+/// `s` must only contain decimal digits.
+fn push_decimal(v: &mut Vec<u8>, s: &str, add: u8) {
+    // 7-bit groups, least significant first
+    let mut groups = vec![0u8];
+    let mut mul_add = |mul: u32, add: u32| {
+        let mut carry = add;
+        for g in groups.iter_mut() {
+            let x = u32::from(*g) * mul + carry;
+            *g = (x & 0x7f) as u8;
+            carry = x >> 7;
+        }
+        while carry > 0 {
+            groups.push((carry & 0x7f) as u8);
+            carry >>= 7;
+        }
+    };
+    for c in s.bytes() {
+        mul_add(10, u32::from(c - b'0'));
+    }
+    mul_add(1, u32::from(add));
+    while groups.len() > 1 && groups[groups.len() - 1] == 0 {
+        groups.pop();
+    }
+    v.extend(groups.iter().rev().map(|g| g | 0x80));
+    let end = v.len() - 1;
+    v[end] &= 0x7f;
+}
+
+/// Decimal representation of a subidentifier minus `sub`, whatever its size
+///
+/// The value of the subidentifier must be greater or equal to `sub`.
+fn subidentifier_to_decimal(s: &[u8], sub: u8) -> String {
+    // 7-bit groups, least significant first
+    let mut groups: Vec<u8> = s.iter().rev().map(|b| b & 0x7f).collect();
+    let mut borrow = sub;
+    for g in groups.iter_mut() {
+        if *g >= borrow {
+            *g -= borrow;
+            break;
+        }
+        *g = *g + 0x80 - borrow;
+        borrow = 1;
+    }
+    // decimal digits, least significant first
+    let mut digits = vec![0u8];
+    for &g in groups.iter().rev() {
+        let mut carry = u32::from(g);
+        for d in digits.iter_mut() {
+            let x = u32::from(*d) * 128 + carry;
+            *d = (x % 10) as u8;
+            carry = x / 10;
+        }
+        while carry > 0 {
+            digits.push((carry % 10) as u8);
+            carry /= 10;
+        }
+    }
+    while digits.len() > 1 && digits[digits.len() - 1] == 0 {
+        digits.pop();
+    }
+    digits.iter().rev().map(|&d| char::from(b'0' + d)).collect()
+}
+
+/// Numeric types that can hold the value of an arc
+pub trait OidArc: Sized {
+    /// Decode a subidentifier (7-bit groups, most significant first), or return `None` if
+    /// the value does not fit
+    fn from_subidentifier(s: &[u8]) -> Option<Self>;
+
+    /// Split the first subidentifier of an absolute OID into the first two arcs (X.690 section
+    /// 8.19.4)
+    fn split_first(self) -> (Self, Self);
+}
+
+impl OidArc for u64 {
+    fn from_subidentifier(s: &[u8]) -> Option<u64> {
+        s.iter().try_fold(0u64, |acc, &b| {
+            if acc >> 57 != 0 {
+                None
+            } else {
+                Some((acc << 7) | u64::from(b & 0x7f))
+            }
+        })
+    }
+
+    fn split_first(self) -> (u64, u64) {
+        match self {
+            0..=39 => (0, self),
+            40..=79 => (1, self - 40),
+            _ => (2, self - 80),
+        }
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl OidArc for BigUint {
+    fn from_subidentifier(s: &[u8]) -> Option<BigUint> {
+        Some(
+            s.iter()
+                .fold(BigUint::from(0u8), |acc, &b| (acc << 7) | BigUint::from(b & 0x7f)),
+        )
+    }
+
+    fn split_first(self) -> (BigUint, BigUint) {
+        if self < BigUint::from(40u8) {
+            (BigUint::from(0u8), self)
+        } else if self < BigUint::from(80u8) {
+            (BigUint::from(1u8), self - BigUint::from(40u8))
+        } else {
+            (BigUint::from(2u8), self - BigUint::from(80u8))
+        }
+    }
+}
+
+/// Iterator on the subidentifiers of an encoded OID, as slices of 7-bit groups
+fn subidentifiers(s: &[u8]) -> impl Iterator<Item = &[u8]> {
+    s.split_inclusive(|b| b & 0x80 == 0)
+}
+
+/// Iterator on the arcs of an OID
+///
+/// See [`Oid::iter`](struct.Oid.html#method.iter).
+#[derive(Debug)]
+pub struct SubIdentifierIterator<'a, N: OidArc> {
+    data: &'a [u8],
+    /// `true` if the next subidentifier encodes the first two arcs
+    first: bool,
+    pending: Option<N>,
+}
 
-impl Oid {
+impl<'a, N: OidArc> Iterator for SubIdentifierIterator<'a, N> {
+    type Item = N;
+
+    fn next(&mut self) -> Option<N> {
+        if let Some(n) = self.pending.take() {
+            return Some(n);
+        }
+        let end = self.data.iter().position(|b| b & 0x80 == 0)?;
+        let (s, rem) = self.data.split_at(end + 1);
+        self.data = rem;
+        // `iter_u64` checks the size of all arcs before building the iterator, and big integers
+        // do not overflow, so an arc never ends the iteration early
+        let n = N::from_subidentifier(s).expect("arc does not fit");
+        if self.first {
+            self.first = false;
+            let (a, b) = n.split_first();
+            self.pending = Some(b);
+            Some(a)
+        } else {
+            Some(n)
+        }
+    }
+}
+
+/// Format the arcs of an encoded OID, whatever their size
+fn format_arcs(asn1: &[u8], relative: bool) -> String {
+    let mut v = Vec::new();
+    for (idx, s) in subidentifiers(asn1).enumerate() {
+        if idx > 0 || relative {
+            match u64::from_subidentifier(s) {
+                Some(n) => v.push(n.to_string()),
+                None => v.push(subidentifier_to_decimal(s, 0)),
+            }
+            continue;
+        }
+        match u64::from_subidentifier(s) {
+            Some(n) => {
+                let (a, b) = n.split_first();
+                v.push(a.to_string());
+                v.push(b.to_string());
+            }
+            None => {
+                v.push("2".to_owned());
+                v.push(subidentifier_to_decimal(s, 80));
+            }
+        }
+    }
+    v.join(".")
+}
+
+/// Compare two encoded OIDs arc by arc
+///
+/// Subidentifiers are encoded in the fewest possible octets, so a shorter encoding is a smaller
+/// value. The first subidentifier of an absolute OID preserves the order of the first two arcs.
+fn compare_arcs(a: &[u8], b: &[u8]) -> Ordering {
+    let mut it_a = subidentifiers(a);
+    let mut it_b = subidentifiers(b);
+    loop {
+        match (it_a.next(), it_b.next()) {
+            (Some(x), Some(y)) => match x.len().cmp(&y.len()).then_with(|| x.cmp(y)) {
+                Ordering::Equal => (),
+                o => return o,
+            },
+            (x, y) => return x.is_some().cmp(&y.is_some()),
+        }
+    }
+}
+
+/// Return the DER encoding of an OID, with the given tag
+fn der_encode(tag: u8, asn1: &[u8]) -> Vec<u8> {
+    let mut v = vec![tag];
+    v.extend(ber_encode_length(asn1.len()));
+    v.extend_from_slice(asn1);
+    v
+}
+
+/// Check the content octets of the BER encoding of an OID
+///
+/// Each subidentifier must be encoded in the fewest possible octets, and the last octet must
+/// terminate a subidentifier (X.690 section 8.19.2).
+fn check_ber_content(s: &[u8]) -> Result<(), BerError> {
+    if s.is_empty() || s[s.len() - 1] & 0x80 != 0 || subidentifiers(s).any(|i| i[0] == 0x80) {
+        return Err(BerError::InvalidValue);
+    }
+    Ok(())
+}
+
+/// Check that `s` is a decimal number without leading zeros (X.660 section A.3)
+fn check_arc(s: &str, index: usize) -> Result<(), OidParseError> {
+    let b = s.as_bytes();
+    if b.is_empty() || !b.iter().all(|c| c.is_ascii_digit()) || (b.len() > 1 && b[0] == b'0') {
+        return Err(OidParseError::InvalidArc(index));
+    }
+    Ok(())
+}
+
+/// Return an iterator on the arcs of an encoded OID, or `None` if an arc does not fit in a `u64`
+fn iter_u64(asn1: &[u8], relative: bool) -> Option<SubIdentifierIterator<'_, u64>> {
+    if subidentifiers(asn1).any(|s| u64::from_subidentifier(s).is_none()) {
+        return None;
+    }
+    Some(iter_arcs(asn1, relative))
+}
+
+fn iter_arcs<N: OidArc>(asn1: &[u8], relative: bool) -> SubIdentifierIterator<'_, N> {
+    SubIdentifierIterator {
+        data: asn1,
+        first: !relative,
+        pending: None,
+    }
+}
+
+impl<'a> Oid<'a> {
     /// Build an OID from an array of `u64` integers
-    pub fn from(s: &[u64]) -> Oid {
-        Oid(s.to_owned())
+    ///
+    /// An error is returned if there are less than two arcs, or if the first two arcs are invalid
+    /// (see [`OidParseError`](enum.OidParseError.html)).
+    pub fn from(s: &[u64]) -> Result<Oid<'static>, OidParseError> {
+        let first = match *s {
+            [a, b, ..] if a < 2 && b < 40 => u128::from(a * 40 + b),
+            [2, b, ..] => u128::from(b) + 80,
+            [a, _, ..] if a < 2 => return Err(OidParseError::SecondArcTooLarge),
+            [_, _, ..] => return Err(OidParseError::InvalidFirstArc),
+            _ => return Err(OidParseError::TooShort),
+        };
+        let mut asn1 = Vec::new();
+        push_base128(&mut asn1, first);
+        for &arc in &s[2..] {
+            push_base128(&mut asn1, u128::from(arc));
+        }
+        Ok(Oid::new(Cow::Owned(asn1)))
+    }
+
+    /// Build an OID from the content octets of its encoding
+    ///
+    /// The encoding is not checked.
+    pub const fn new(asn1: Cow<'a, [u8]>) -> Oid<'a> {
+        Oid { asn1 }
+    }
+
+    /// Convert to an owned OID, copying the encoding if it is borrowed
+    pub fn into_owned(self) -> Oid<'static> {
+        Oid::new(Cow::Owned(self.asn1.into_owned()))
+    }
+
+    /// Build an OID from the content octets of its BER encoding
+    pub(crate) fn from_ber_content(s: &'a [u8]) -> Result<Oid<'a>, BerError> {
+        check_ber_content(s)?;
+        Ok(Oid::new(Cow::Borrowed(s)))
+    }
+
+    /// Return the content octets of the BER encoding of the OID
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.asn1
+    }
+
+    /// Return the DER encoding of the OID, including the header
+    pub fn to_der_bytes(&self) -> Vec<u8> {
+        der_encode(0x06, &self.asn1)
     }
 
     /// Convert the OID to a string representation.
     /// The string contains the IDs separated by dots, for ex: "1.2.840.113549.1.1.5"
     pub fn to_string(&self) -> String {
-        if self.0.is_empty() { return String::new(); }
+        format_arcs(&self.asn1, false)
+    }
+
+    /// Return the number of arcs
+    pub fn len(&self) -> usize {
+        match subidentifiers(&self.asn1).count() {
+            0 => 0,
+            n => n + 1,
+        }
+    }
+
+    /// Return `true` if the OID has no arcs (only possible for an OID built using `new`)
+    pub fn is_empty(&self) -> bool {
+        self.asn1.is_empty()
+    }
 
-        let mut s = self.0[0].to_string();
+    /// Return `true` if the arcs of `prefix` are the first arcs of this OID
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate der_parser;
+    /// # fn main() {
+    /// let oid = oid!(1.2.840.113549.1.1.11);
+    /// assert!(oid.starts_with(&oid!(1.2.840.113549)));
+    /// assert!(!oid.starts_with(&oid!(1.2.840.11354)));
+    /// # }
+    /// ```
+    pub fn starts_with(&self, prefix: &Oid) -> bool {
+        // the last octet of an encoding always ends a subidentifier
+        self.asn1.starts_with(&prefix.asn1)
+    }
 
-        for it in self.0.iter().skip(1) {
-            s.push('.');
-            s = s + &it.to_string();
+    /// Return the OID without its last arc, or `None` if the OID has two arcs or less
+    pub fn parent(&self) -> Option<Oid<'a>> {
+        let last = subidentifiers(&self.asn1).last()?.len();
+        let len = self.asn1.len() - last;
+        if len == 0 {
+            return None;
         }
+        let asn1 = match self.asn1 {
+            Cow::Borrowed(s) => Cow::Borrowed(&s[..len]),
+            Cow::Owned(ref v) => Cow::Owned(v[..len].to_vec()),
+        };
+        Some(Oid::new(asn1))
+    }
 
-        s
+    /// Return a new OID, with `arc` added after the last arc
+    pub fn child(&self, arc: u64) -> Oid<'static> {
+        let mut asn1 = self.asn1.to_vec();
+        push_base128(&mut asn1, u128::from(arc));
+        Oid::new(Cow::Owned(asn1))
     }
 
-    /// Return an iterator on every ID
-    pub fn iter(&self) -> slice::Iter<u64> {
-        self.0.iter()
+    /// Return a new OID, with the arcs of `rel` added after the last arc
+    pub fn append(&self, rel: &RelativeOid) -> Oid<'static> {
+        let mut asn1 = self.asn1.to_vec();
+        asn1.extend_from_slice(&rel.asn1);
+        Oid::new(Cow::Owned(asn1))
+    }
+
+    /// Return an iterator on every arc, or `None` if an arc does not fit in a `u64`
+    pub fn iter(&self) -> Option<SubIdentifierIterator<'_, u64>> {
+        iter_u64(&self.asn1, false)
+    }
+
+    /// Return an iterator on every arc, as big integers
+    #[cfg(feature = "bigint")]
+    pub fn iter_bigint(&self) -> SubIdentifierIterator<'_, BigUint> {
+        iter_arcs(&self.asn1, false)
+    }
+}
+
+impl<'a> RelativeOid<'a> {
+    /// Build a relative OID from an array of `u64` integers
+    pub fn from(s: &[u64]) -> RelativeOid<'static> {
+        let mut asn1 = Vec::new();
+        for &arc in s {
+            push_base128(&mut asn1, u128::from(arc));
+        }
+        RelativeOid::new(Cow::Owned(asn1))
+    }
+
+    /// Build a relative OID from the content octets of its encoding
+    ///
+    /// The encoding is not checked.
+    pub const fn new(asn1: Cow<'a, [u8]>) -> RelativeOid<'a> {
+        RelativeOid { asn1 }
+    }
+
+    /// Convert to an owned relative OID, copying the encoding if it is borrowed
+    pub fn into_owned(self) -> RelativeOid<'static> {
+        RelativeOid::new(Cow::Owned(self.asn1.into_owned()))
+    }
+
+    /// Build a relative OID from the content octets of its BER encoding
+    pub(crate) fn from_ber_content(s: &'a [u8]) -> Result<RelativeOid<'a>, BerError> {
+        check_ber_content(s)?;
+        Ok(RelativeOid::new(Cow::Borrowed(s)))
+    }
+
+    /// Return the content octets of the BER encoding of the relative OID
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.asn1
+    }
+
+    /// Return the DER encoding of the relative OID, including the header
+    pub fn to_der_bytes(&self) -> Vec<u8> {
+        der_encode(0x0d, &self.asn1)
+    }
+
+    /// Return the number of arcs
+    pub fn len(&self) -> usize {
+        subidentifiers(&self.asn1).count()
+    }
+
+    /// Return `true` if the relative OID has no arcs
+    pub fn is_empty(&self) -> bool {
+        self.asn1.is_empty()
+    }
+
+    /// Return an iterator on every arc, or `None` if an arc does not fit in a `u64`
+    pub fn iter(&self) -> Option<SubIdentifierIterator<'_, u64>> {
+        iter_u64(&self.asn1, true)
+    }
+
+    /// Return an iterator on every arc, as big integers
+    #[cfg(feature = "bigint")]
+    pub fn iter_bigint(&self) -> SubIdentifierIterator<'_, BigUint> {
+        iter_arcs(&self.asn1, true)
+    }
+}
+
+impl<'a> PartialOrd for Oid<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// OIDs are ordered arc by arc
+impl<'a> Ord for Oid<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_arcs(&self.asn1, &other.asn1)
+    }
+}
+
+impl<'a> PartialOrd for RelativeOid<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Relative OIDs are ordered arc by arc
+impl<'a> Ord for RelativeOid<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_arcs(&self.asn1, &other.asn1)
     }
 }
 
-impl fmt::Display for Oid {
+impl<'a> fmt::Display for Oid<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(&self.to_string())
     }
 }
 
-impl fmt::Debug for Oid {
+impl<'a> fmt::Debug for Oid<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(&format!("OID({})", self.to_string()))
     }
 }
 
-impl FromStr for Oid {
-    type Err = ParseIntError;
+impl<'a> fmt::Display for RelativeOid<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&format_arcs(&self.asn1, true))
+    }
+}
+
+impl<'a> fmt::Debug for RelativeOid<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RelativeOID({})", self)
+    }
+}
 
+impl<'a> FromStr for Oid<'a> {
+    type Err = OidParseError;
+
+    /// Parse an absolute OID in dotted decimal form, for ex: "1.2.840.113549.1.1.5"
+    ///
+    /// Arcs may be of any size, but must not have leading zeros. The first arc must be 0, 1 or 2,
+    /// and the second arc must be lower than 40 under arcs 0 and 1 (X.660 section A.2).
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let v : Result<Vec<_>,ParseIntError> =s.split(".").map(|c| {
-                          c.parse::<u64>()
-        }).collect();
-        v.map(|v| Oid(v))
+        if s.is_empty() {
+            return Err(OidParseError::Empty);
+        }
+        let arcs: Vec<&str> = s.split('.').collect();
+        for (idx, arc) in arcs.iter().enumerate() {
+            check_arc(arc, idx)?;
+        }
+        if arcs.len() < 2 {
+            return Err(OidParseError::TooShort);
+        }
+        let mut asn1 = Vec::new();
+        match (arcs[0], arcs[1].parse::<u8>()) {
+            ("0", Ok(b)) if b < 40 => push_base128(&mut asn1, u128::from(b)),
+            ("1", Ok(b)) if b < 40 => push_base128(&mut asn1, u128::from(b) + 40),
+            ("0", _) | ("1", _) => return Err(OidParseError::SecondArcTooLarge),
+            ("2", _) => push_decimal(&mut asn1, arcs[1], 80),
+            _ => return Err(OidParseError::InvalidFirstArc),
+        }
+        for arc in &arcs[2..] {
+            push_decimal(&mut asn1, arc, 0);
+        }
+        Ok(Oid::new(Cow::Owned(asn1)))
+    }
+}
+
+impl<'a> FromStr for RelativeOid<'a> {
+    type Err = OidParseError;
+
+    /// Parse a relative OID in dotted decimal form, for ex: "8571.3.2"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(OidParseError::Empty);
+        }
+        let mut asn1 = Vec::new();
+        for (idx, arc) in s.split('.').enumerate() {
+            check_arc(arc, idx)?;
+            push_decimal(&mut asn1, arc, 0);
+        }
+        Ok(RelativeOid::new(Cow::Owned(asn1)))
+    }
+}
+
+/// Read the next arc of an OID written in dotted decimal form, starting at `pos`
+///
+/// Whitespace is ignored, since it may be inserted by `stringify!`. Returns the arc, and the
+/// position after the separator.
+const fn const_next_arc(s: &[u8], mut pos: usize) -> (u64, usize) {
+    let mut arc: u64 = 0;
+    let mut digits = 0;
+    while pos < s.len() {
+        let c = s[pos];
+        pos += 1;
+        if c == b'.' {
+            break;
+        }
+        if c == b' ' {
+            continue;
+        }
+        assert!(c >= b'0' && c <= b'9', "invalid character in OID");
+        arc = match arc.checked_mul(10) {
+            Some(a) => match a.checked_add((c - b'0') as u64) {
+                Some(a) => a,
+                None => panic!("OID arc too large"),
+            },
+            None => panic!("OID arc too large"),
+        };
+        digits += 1;
+    }
+    assert!(digits > 0, "empty arc in OID");
+    (arc, pos)
+}
+
+/// Number of octets of the encoding of `arc`
+const fn const_arc_len(mut arc: u64) -> usize {
+    let mut len = 1;
+    while arc >= 0x80 {
+        arc >>= 7;
+        len += 1;
     }
+    len
 }
 
+/// Call `f` with each subidentifier of an OID written in dotted decimal form
+macro_rules! const_for_each_subidentifier {
+    ($s:expr, $relative:expr, |$sub:ident| $body:block) => {{
+        let s = $s;
+        let mut pos = 0;
+        if !$relative {
+            let (a, p) = const_next_arc(s, pos);
+            assert!(p < s.len(), "OID must have at least two arcs");
+            let (b, p) = const_next_arc(s, p);
+            assert!(a < 2 && b < 40 || a == 2 && b <= u64::MAX - 80, "invalid first arcs in OID");
+            pos = p;
+            let $sub = a * 40 + b;
+            $body
+        }
+        while pos < s.len() {
+            let (arc, p) = const_next_arc(s, pos);
+            pos = p;
+            let $sub = arc;
+            $body
+        }
+    }};
+}
 
+/// Length of the encoding of an OID written in dotted decimal form (used by `oid!`)
+#[doc(hidden)]
+pub const fn encoded_len(s: &str, relative: bool) -> usize {
+    let mut len = 0;
+    const_for_each_subidentifier!(s.as_bytes(), relative, |sub| {
+        len += const_arc_len(sub);
+    });
+    len
+}
 
+/// Encode an OID written in dotted decimal form (used by `oid!`)
+#[doc(hidden)]
+pub const fn encode_const<const N: usize>(s: &str, relative: bool) -> [u8; N] {
+    let mut out = [0u8; N];
+    let mut idx = 0;
+    const_for_each_subidentifier!(s.as_bytes(), relative, |sub| {
+        let len = const_arc_len(sub);
+        let mut i = 0;
+        while i < len {
+            let group = ((sub >> (7 * (len - 1 - i))) & 0x7f) as u8;
+            out[idx + i] = if i + 1 < len { group | 0x80 } else { group };
+            i += 1;
+        }
+        idx += len;
+    });
+    out
+}
 
 #[cfg(test)]
 mod tests {
-    use oid::Oid;
+    use oid::{Oid, OidParseError, RelativeOid};
+    use std::collections::HashSet;
     use std::str::FromStr;
 
 #[test]
 fn test_oid_fmt() {
-    let oid = Oid::from(&[1, 2, 840, 113549, 1, 1, 5]);
+    let oid = Oid::from(&[1, 2, 840, 113549, 1, 1, 5]).unwrap();
     assert_eq!(format!("{}",oid), "1.2.840.113549.1.1.5".to_owned());
     assert_eq!(format!("{:?}",oid), "OID(1.2.840.113549.1.1.5)".to_owned());
 }
 
 #[test]
 fn test_oid_from_str() {
-    let oid_ref = Oid::from(&[1, 2, 840, 113549, 1, 1, 5]);
+    let oid_ref = Oid::from(&[1, 2, 840, 113549, 1, 1, 5]).unwrap();
     let oid = Oid::from_str("1.2.840.113549.1.1.5").unwrap();
     assert_eq!(oid_ref, oid);
+    assert_eq!(Oid::from_str(""), Err(OidParseError::Empty));
+    assert_eq!(Oid::from_str("1"), Err(OidParseError::TooShort));
+    assert_eq!(Oid::from_str("1.40"), Err(OidParseError::SecondArcTooLarge));
+    assert_eq!(Oid::from_str("5.99"), Err(OidParseError::InvalidFirstArc));
+    assert_eq!(Oid::from_str("1.2.x"), Err(OidParseError::InvalidArc(2)));
+    assert_eq!(Oid::from_str("1.2..3"), Err(OidParseError::InvalidArc(2)));
+    assert_eq!(Oid::from_str("1.2.03"), Err(OidParseError::InvalidArc(2)));
+    assert_eq!(Oid::from_str("1.2.3."), Err(OidParseError::InvalidArc(3)));
+    assert_eq!(Oid::from_str(" 1.2"), Err(OidParseError::InvalidArc(0)));
+    assert_eq!(RelativeOid::from_str("8571.3.2"), Ok(RelativeOid::from(&[8571, 3, 2])));
+    assert_eq!(RelativeOid::from_str(""), Err(OidParseError::Empty));
 }
 
+#[test]
+fn test_oid_first_arcs() {
+    let oid = Oid::from(&[2, 999, 3]).unwrap();
+    assert_eq!(oid.as_bytes(), &[0x88, 0x37, 0x03]);
+    assert_eq!(oid.iter().map(|it| it.collect::<Vec<_>>()), Some(vec![2, 999, 3]));
+    assert_eq!(Oid::from_str("2.999.3"), Ok(oid));
+    assert_eq!(Oid::from(&[1]), Err(OidParseError::TooShort));
+    assert_eq!(Oid::from(&[1, 40]), Err(OidParseError::SecondArcTooLarge));
+    assert_eq!(Oid::from(&[3, 1]), Err(OidParseError::InvalidFirstArc));
+    // the second arc under joint-iso-itu-t may not fit in a u64 once encoded
+    let oid = Oid::from(&[2, u64::max_value()]).unwrap();
+    assert_eq!(oid.to_string(), format!("2.{}", u64::max_value()));
+    assert!(oid.iter().is_none());
+}
+
+#[test]
+fn test_oid_big_arcs() {
+    // UUID-based OID (X.667)
+    let s = "2.25.329800735698586629295641978511506172918";
+    let oid = Oid::from_str(s).unwrap();
+    assert_eq!(oid.to_string(), s);
+    assert!(oid.iter().is_none());
+    let s = "2.340282366920938463463374607431768211456.1";
+    assert_eq!(Oid::from_str(s).map(|oid| oid.to_string()), Ok(s.to_owned()));
+    // the largest arc fitting in a u64, and the next one
+    let oid = Oid::from_str("1.2.18446744073709551615.3").unwrap();
+    assert_eq!(oid.iter().map(|it| it.collect::<Vec<_>>()), Some(vec![1, 2, u64::max_value(), 3]));
+    let oid = Oid::from_str("1.2.18446744073709551616.3").unwrap();
+    assert!(oid.iter().is_none());
+    let rel = RelativeOid::from_str("18446744073709551616.3").unwrap();
+    assert!(rel.iter().is_none());
+}
+
+#[test]
+fn test_oid_from_ber_content() {
+    assert!(Oid::from_ber_content(&[]).is_err());
+    // last subidentifier is truncated
+    assert!(Oid::from_ber_content(&[0x2a, 0x86]).is_err());
+    // subidentifier not encoded in the fewest possible octets
+    assert!(Oid::from_ber_content(&[0x2a, 0x80, 0x01]).is_err());
+    let oid = RelativeOid::from_ber_content(&[0xc2, 0x7b, 0x03, 0x02]).unwrap();
+    assert_eq!(oid, RelativeOid::from(&[8571, 3, 2]));
+    assert_eq!(oid.to_string(), "8571.3.2");
+    assert_eq!(format!("{:?}", oid), "RelativeOID(8571.3.2)");
+}
+
+#[test]
+fn test_oid_macro() {
+    const OID_RSA: Oid<'static> = oid!(1.2.840.113549.1.1.1);
+    assert_eq!(OID_RSA, Oid::from_str("1.2.840.113549.1.1.1").unwrap());
+    assert_eq!(oid!(raw 2.999.3), [0x88, 0x37, 0x03]);
+    assert_eq!(oid!(2.5.4.3).to_der_bytes(), vec![0x06, 0x03, 0x55, 0x04, 0x03]);
+    let oid = oid!(rel 8571.3.2);
+    assert_eq!(oid.len(), 3);
+    assert_eq!(oid.to_der_bytes(), vec![0x0d, 0x04, 0xc2, 0x7b, 0x03, 0x02]);
 }
 
+#[test]
+fn test_oid_borrowed() {
+    let bytes = [0x2a, 0x86, 0x48];
+    let oid = Oid::from_ber_content(&bytes).unwrap();
+    assert_eq!(oid.as_bytes().as_ptr(), bytes.as_ptr());
+    let owned = oid.clone().into_owned();
+    assert_eq!(owned, oid);
+    assert_eq!(owned.to_string(), "1.2.840");
+}
+
+#[test]
+fn test_oid_hierarchy() {
+    let oid = oid!(1.2.840.113549.1.1);
+    assert_eq!(oid.len(), 6);
+    assert!(oid.starts_with(&oid!(1.2)));
+    assert!(oid.starts_with(&oid));
+    assert!(!oid.starts_with(&oid!(1.3)));
+    assert!(!oid!(1.2).starts_with(&oid));
+    assert_eq!(oid.parent(), Some(oid!(1.2.840.113549.1)));
+    assert_eq!(oid!(1.2.3).parent(), Some(oid!(1.2)));
+    assert_eq!(oid!(1.2).parent(), None);
+    assert_eq!(oid.child(11), oid!(1.2.840.113549.1.1.11));
+    assert_eq!(oid!(1.2).append(&oid!(rel 840.113549)), oid!(1.2.840.113549));
+    // the first two arcs share a subidentifier
+    assert_eq!(oid!(2.999).child(3), Oid::from(&[2, 999, 3]).unwrap());
+}
+
+#[test]
+fn test_oid_ord() {
+    let mut v = vec![oid!(2.5), oid!(1.2.840), oid!(1.2.3.4), oid!(1.2.128), oid!(1.2), oid!(0.9)];
+    v.sort();
+    let s: Vec<_> = v.iter().map(|o| o.to_string()).collect();
+    assert_eq!(s, ["0.9", "1.2", "1.2.3.4", "1.2.128", "1.2.840", "2.5"]);
+    assert!(oid!(1.39) < oid!(2.0));
+    assert!(oid!(2.47) < oid!(2.48));
+    assert!(oid!(rel 5) < oid!(rel 5.1));
+    let set: HashSet<_> = v.into_iter().collect();
+    assert!(set.contains(&oid!(1.2.128)));
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn test_oid_iter_bigint() {
+    use num_bigint::BigUint;
+    let oid = Oid::from_str("2.25.329800735698586629295641978511506172918").unwrap();
+    let arcs: Vec<BigUint> = oid.iter_bigint().collect();
+    assert_eq!(arcs[0], BigUint::from(2u8));
+    assert_eq!(arcs[1], BigUint::from(25u8));
+    assert_eq!(arcs[2], BigUint::from(329800735698586629295641978511506172918u128));
+    // arcs not fitting in a u64 are returned by iter_bigint, not by iter
+    let rel = RelativeOid::from_str("18446744073709551616.3").unwrap();
+    assert!(rel.iter().is_none());
+    let arcs: Vec<BigUint> = rel.iter_bigint().collect();
+    assert_eq!(arcs, vec![BigUint::from(u64::max_value()) + 1u8, BigUint::from(3u8)]);
+}
+
+}
@@ -0,0 +1,324 @@
+//! Registry of well-known Object Identifiers
+//!
+//! This module (enabled by the `oid-registry` feature) provides constants for common PKIX, PKCS,
+//! X.500 attribute, ANSI X9.62, NIST and SNMP OIDs, and a registry mapping OIDs to a short name
+//! and a description.
+//!
+//! Applications can add their own entries to the registry. The registry can be given to the
+//! pretty-printer, so OIDs are displayed with their names.
+//!
+//! ```rust
+//! use der_parser::oid::Oid;
+//! use der_parser::oid_registry::*;
+//!
+//! let mut registry = OidRegistry::default().with_well_known();
+//! let entry = registry.get(&OID_PKCS1_SHA256WITHRSA).expect("unknown OID");
+//! assert_eq!(entry.sn(), "sha256WithRSAEncryption");
+//!
+//! let oid: Oid = "1.3.6.1.4.1.99999.1".parse().unwrap();
+//! registry.insert(&oid, OidEntry::new("myAlgorithm", "My Algorithm"));
+//! assert_eq!(registry.get_by_sn("myAlgorithm"), Some(oid));
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use oid::Oid;
+
+/// Short name and description of an OID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidEntry {
+    sn: Cow<'static, str>,
+    description: Cow<'static, str>,
+}
+
+impl OidEntry {
+    /// Build an entry from a short name and a description
+    pub fn new<S, D>(sn: S, description: D) -> OidEntry
+    where
+        S: Into<Cow<'static, str>>,
+        D: Into<Cow<'static, str>>,
+    {
+        OidEntry {
+            sn: sn.into(),
+            description: description.into(),
+        }
+    }
+
+    /// Short name of the OID (for ex. `commonName`)
+    pub fn sn(&self) -> &str {
+        &self.sn
+    }
+
+    /// Description of the OID (for ex. `Common Name`)
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// Registry mapping OIDs to their names
+///
+/// `OidRegistry::default()` returns an empty registry. Use
+/// [`with_well_known`](#method.with_well_known) to load the entries of this module.
+#[derive(Debug, Default, Clone)]
+pub struct OidRegistry {
+    map: HashMap<Vec<u8>, OidEntry>,
+}
+
+impl OidRegistry {
+    /// Add all the well-known OIDs of this module to the registry
+    pub fn with_well_known(mut self) -> Self {
+        for &(ref oid, sn, description) in WELL_KNOWN_OIDS {
+            self.insert(oid, OidEntry::new(sn, description));
+        }
+        self
+    }
+
+    /// Add an entry to the registry
+    ///
+    /// If the OID was already present, its previous entry is replaced and returned.
+    pub fn insert(&mut self, oid: &Oid, entry: OidEntry) -> Option<OidEntry> {
+        self.map.insert(oid.as_bytes().to_vec(), entry)
+    }
+
+    /// Remove an entry from the registry
+    pub fn remove(&mut self, oid: &Oid) -> Option<OidEntry> {
+        self.map.remove(oid.as_bytes())
+    }
+
+    /// Get the entry of an OID
+    pub fn get(&self, oid: &Oid) -> Option<&OidEntry> {
+        self.map.get(oid.as_bytes())
+    }
+
+    /// Find the OID registered with the short name `sn`
+    pub fn get_by_sn(&self, sn: &str) -> Option<Oid<'_>> {
+        self.iter().find(|&(_, entry)| entry.sn() == sn).map(|(oid, _)| oid)
+    }
+
+    /// Iterate over all (OID, entry) pairs of the registry, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Oid<'_>, &OidEntry)> {
+        self.map
+            .iter()
+            .map(|(k, entry)| (Oid::new(Cow::Borrowed(k.as_slice())), entry))
+    }
+
+    /// Number of entries in the registry
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Test if the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+macro_rules! well_known_oids {
+    ( $( $name:ident => $sn:expr, $description:literal, [ $($arcs:tt)+ ]; )* ) => {
+        $(
+            #[doc = $description]
+            pub const $name: Oid<'static> = oid!($($arcs)+);
+        )*
+
+        static WELL_KNOWN_OIDS: &[(Oid<'static>, &str, &str)] = &[
+            $( ($name, $sn, $description), )*
+        ];
+    };
+}
+
+well_known_oids! {
+    // PKCS #1
+    OID_PKCS1_RSAENCRYPTION      => "rsaEncryption", "RSA Encryption", [1.2.840.113549.1.1.1];
+    OID_PKCS1_MD2WITHRSA         => "md2WithRSAEncryption", "MD2 With RSA Encryption", [1.2.840.113549.1.1.2];
+    OID_PKCS1_MD5WITHRSA         => "md5WithRSAEncryption", "MD5 With RSA Encryption", [1.2.840.113549.1.1.4];
+    OID_PKCS1_SHA1WITHRSA        => "sha1WithRSAEncryption", "SHA1 With RSA Encryption", [1.2.840.113549.1.1.5];
+    OID_PKCS1_RSAESOAEP          => "RSAES-OAEP", "RSAES-OAEP", [1.2.840.113549.1.1.7];
+    OID_PKCS1_MGF1               => "mgf1", "Mask Generation Function 1", [1.2.840.113549.1.1.8];
+    OID_PKCS1_RSASSAPSS          => "RSASSA-PSS", "RSASSA-PSS", [1.2.840.113549.1.1.10];
+    OID_PKCS1_SHA256WITHRSA      => "sha256WithRSAEncryption", "SHA256 With RSA Encryption", [1.2.840.113549.1.1.11];
+    OID_PKCS1_SHA384WITHRSA      => "sha384WithRSAEncryption", "SHA384 With RSA Encryption", [1.2.840.113549.1.1.12];
+    OID_PKCS1_SHA512WITHRSA      => "sha512WithRSAEncryption", "SHA512 With RSA Encryption", [1.2.840.113549.1.1.13];
+    OID_PKCS1_SHA224WITHRSA      => "sha224WithRSAEncryption", "SHA224 With RSA Encryption", [1.2.840.113549.1.1.14];
+    // PKCS #5
+    OID_PKCS5_PBKDF2             => "PBKDF2", "Password-Based Key Derivation Function 2", [1.2.840.113549.1.5.12];
+    OID_PKCS5_PBES2              => "PBES2", "Password-Based Encryption Scheme 2", [1.2.840.113549.1.5.13];
+    // PKCS #7
+    OID_PKCS7_DATA               => "pkcs7-data", "PKCS #7 Data", [1.2.840.113549.1.7.1];
+    OID_PKCS7_SIGNED_DATA        => "pkcs7-signedData", "PKCS #7 Signed Data", [1.2.840.113549.1.7.2];
+    OID_PKCS7_ENVELOPED_DATA     => "pkcs7-envelopedData", "PKCS #7 Enveloped Data", [1.2.840.113549.1.7.3];
+    OID_PKCS7_SIGNED_ENVELOPED_DATA => "pkcs7-signedAndEnvelopedData", "PKCS #7 Signed And Enveloped Data", [1.2.840.113549.1.7.4];
+    OID_PKCS7_DIGESTED_DATA      => "pkcs7-digestedData", "PKCS #7 Digested Data", [1.2.840.113549.1.7.5];
+    OID_PKCS7_ENCRYPTED_DATA     => "pkcs7-encryptedData", "PKCS #7 Encrypted Data", [1.2.840.113549.1.7.6];
+    // PKCS #9
+    OID_PKCS9_EMAIL_ADDRESS      => "emailAddress", "Email Address", [1.2.840.113549.1.9.1];
+    OID_PKCS9_UNSTRUCTURED_NAME  => "unstructuredName", "Unstructured Name", [1.2.840.113549.1.9.2];
+    OID_PKCS9_CONTENT_TYPE       => "contentType", "Content Type", [1.2.840.113549.1.9.3];
+    OID_PKCS9_MESSAGE_DIGEST     => "messageDigest", "Message Digest", [1.2.840.113549.1.9.4];
+    OID_PKCS9_SIGNING_TIME       => "signingTime", "Signing Time", [1.2.840.113549.1.9.5];
+    OID_PKCS9_CHALLENGE_PASSWORD => "challengePassword", "Challenge Password", [1.2.840.113549.1.9.7];
+    OID_PKCS9_EXTENSION_REQUEST  => "extensionRequest", "Extension Request", [1.2.840.113549.1.9.14];
+    OID_PKCS9_SMIME_CAPABILITIES => "smimeCapabilities", "S/MIME Capabilities", [1.2.840.113549.1.9.15];
+    OID_PKCS9_FRIENDLY_NAME      => "friendlyName", "Friendly Name", [1.2.840.113549.1.9.20];
+    OID_PKCS9_LOCAL_KEY_ID       => "localKeyID", "Local Key ID", [1.2.840.113549.1.9.21];
+    // RSADSI digest algorithms
+    OID_DIGEST_MD5               => "md5", "MD5", [1.2.840.113549.2.5];
+    OID_HMAC_SHA1                => "hmacWithSHA1", "HMAC With SHA1", [1.2.840.113549.2.7];
+    OID_HMAC_SHA256              => "hmacWithSHA256", "HMAC With SHA256", [1.2.840.113549.2.9];
+    // OIW
+    OID_HASH_SHA1                => "sha1", "SHA-1", [1.3.14.3.2.26];
+    // ANSI X9.57
+    OID_X957_DSA                 => "dsa", "DSA", [1.2.840.10040.4.1];
+    OID_X957_DSA_WITH_SHA1       => "dsa-with-sha1", "DSA With SHA1", [1.2.840.10040.4.3];
+    // ANSI X9.62
+    OID_KEY_TYPE_EC_PUBLIC_KEY   => "id-ecPublicKey", "Elliptic Curve Public Key", [1.2.840.10045.2.1];
+    OID_EC_P192                  => "prime192v1", "NIST P-192 Curve", [1.2.840.10045.3.1.1];
+    OID_EC_P256                  => "prime256v1", "NIST P-256 Curve", [1.2.840.10045.3.1.7];
+    OID_SIG_ECDSA_WITH_SHA1      => "ecdsa-with-SHA1", "ECDSA With SHA1", [1.2.840.10045.4.1];
+    OID_SIG_ECDSA_WITH_SHA224    => "ecdsa-with-SHA224", "ECDSA With SHA224", [1.2.840.10045.4.3.1];
+    OID_SIG_ECDSA_WITH_SHA256    => "ecdsa-with-SHA256", "ECDSA With SHA256", [1.2.840.10045.4.3.2];
+    OID_SIG_ECDSA_WITH_SHA384    => "ecdsa-with-SHA384", "ECDSA With SHA384", [1.2.840.10045.4.3.3];
+    OID_SIG_ECDSA_WITH_SHA512    => "ecdsa-with-SHA512", "ECDSA With SHA512", [1.2.840.10045.4.3.4];
+    // Certicom
+    OID_EC_SECP256K1             => "secp256k1", "SECG secp256k1 Curve", [1.3.132.0.10];
+    OID_EC_P384                  => "secp384r1", "NIST P-384 Curve", [1.3.132.0.34];
+    OID_EC_P521                  => "secp521r1", "NIST P-521 Curve", [1.3.132.0.35];
+    // RFC 8410
+    OID_X25519                   => "X25519", "X25519", [1.3.101.110];
+    OID_X448                     => "X448", "X448", [1.3.101.111];
+    OID_SIG_ED25519              => "Ed25519", "Ed25519", [1.3.101.112];
+    OID_SIG_ED448                => "Ed448", "Ed448", [1.3.101.113];
+    // NIST
+    OID_NIST_AES128_CBC          => "aes128-CBC", "AES-128 in CBC mode", [2.16.840.1.101.3.4.1.2];
+    OID_NIST_AES128_GCM          => "aes128-GCM", "AES-128 in GCM mode", [2.16.840.1.101.3.4.1.6];
+    OID_NIST_AES192_CBC          => "aes192-CBC", "AES-192 in CBC mode", [2.16.840.1.101.3.4.1.22];
+    OID_NIST_AES256_CBC          => "aes256-CBC", "AES-256 in CBC mode", [2.16.840.1.101.3.4.1.42];
+    OID_NIST_AES256_GCM          => "aes256-GCM", "AES-256 in GCM mode", [2.16.840.1.101.3.4.1.46];
+    OID_NIST_HASH_SHA256         => "sha256", "SHA-256", [2.16.840.1.101.3.4.2.1];
+    OID_NIST_HASH_SHA384         => "sha384", "SHA-384", [2.16.840.1.101.3.4.2.2];
+    OID_NIST_HASH_SHA512         => "sha512", "SHA-512", [2.16.840.1.101.3.4.2.3];
+    OID_NIST_HASH_SHA224         => "sha224", "SHA-224", [2.16.840.1.101.3.4.2.4];
+    OID_NIST_HASH_SHA3_256       => "sha3-256", "SHA3-256", [2.16.840.1.101.3.4.2.8];
+    OID_NIST_HASH_SHA3_384       => "sha3-384", "SHA3-384", [2.16.840.1.101.3.4.2.9];
+    OID_NIST_HASH_SHA3_512       => "sha3-512", "SHA3-512", [2.16.840.1.101.3.4.2.10];
+    OID_NIST_DSA_WITH_SHA256     => "dsa-with-sha256", "DSA With SHA256", [2.16.840.1.101.3.4.3.2];
+    // X.500 attribute types
+    OID_X509_COMMON_NAME         => "commonName", "Common Name", [2.5.4.3];
+    OID_X509_SURNAME             => "surname", "Surname", [2.5.4.4];
+    OID_X509_SERIALNUMBER        => "serialNumber", "Serial Number", [2.5.4.5];
+    OID_X509_COUNTRY_NAME        => "countryName", "Country Name", [2.5.4.6];
+    OID_X509_LOCALITY_NAME       => "localityName", "Locality Name", [2.5.4.7];
+    OID_X509_STATE_OR_PROVINCE_NAME => "stateOrProvinceName", "State Or Province Name", [2.5.4.8];
+    OID_X509_STREET_ADDRESS      => "streetAddress", "Street Address", [2.5.4.9];
+    OID_X509_ORGANIZATION_NAME   => "organizationName", "Organization Name", [2.5.4.10];
+    OID_X509_ORGANIZATIONAL_UNIT => "organizationalUnitName", "Organizational Unit Name", [2.5.4.11];
+    OID_X509_TITLE               => "title", "Title", [2.5.4.12];
+    OID_X509_DESCRIPTION         => "description", "Description", [2.5.4.13];
+    OID_X509_POSTAL_CODE         => "postalCode", "Postal Code", [2.5.4.17];
+    OID_X509_NAME                => "name", "Name", [2.5.4.41];
+    OID_X509_GIVEN_NAME          => "givenName", "Given Name", [2.5.4.42];
+    OID_X509_INITIALS            => "initials", "Initials", [2.5.4.43];
+    OID_X509_GENERATION_QUALIFIER => "generationQualifier", "Generation Qualifier", [2.5.4.44];
+    OID_X509_DN_QUALIFIER        => "dnQualifier", "DN Qualifier", [2.5.4.46];
+    OID_X509_PSEUDONYM           => "pseudonym", "Pseudonym", [2.5.4.65];
+    OID_DOMAIN_COMPONENT         => "domainComponent", "Domain Component", [0.9.2342.19200300.100.1.25];
+    OID_USERID                   => "userId", "User ID", [0.9.2342.19200300.100.1.1];
+    // X.509 certificate extensions
+    OID_X509_EXT_SUBJECT_DIRECTORY_ATTRIBUTES => "subjectDirectoryAttributes", "Subject Directory Attributes", [2.5.29.9];
+    OID_X509_EXT_SUBJECT_KEY_IDENTIFIER => "subjectKeyIdentifier", "Subject Key Identifier", [2.5.29.14];
+    OID_X509_EXT_KEY_USAGE       => "keyUsage", "Key Usage", [2.5.29.15];
+    OID_X509_EXT_PRIVATE_KEY_USAGE_PERIOD => "privateKeyUsagePeriod", "Private Key Usage Period", [2.5.29.16];
+    OID_X509_EXT_SUBJECT_ALT_NAME => "subjectAltName", "Subject Alternative Name", [2.5.29.17];
+    OID_X509_EXT_ISSUER_ALT_NAME => "issuerAltName", "Issuer Alternative Name", [2.5.29.18];
+    OID_X509_EXT_BASIC_CONSTRAINTS => "basicConstraints", "Basic Constraints", [2.5.29.19];
+    OID_X509_EXT_CRL_NUMBER      => "cRLNumber", "CRL Number", [2.5.29.20];
+    OID_X509_EXT_REASON_CODE     => "cRLReason", "CRL Reason", [2.5.29.21];
+    OID_X509_EXT_INVALIDITY_DATE => "invalidityDate", "Invalidity Date", [2.5.29.24];
+    OID_X509_EXT_DELTA_CRL_INDICATOR => "deltaCRLIndicator", "Delta CRL Indicator", [2.5.29.27];
+    OID_X509_EXT_ISSUER_DISTRIBUTION_POINT => "issuingDistributionPoint", "Issuing Distribution Point", [2.5.29.28];
+    OID_X509_EXT_CERTIFICATE_ISSUER => "certificateIssuer", "Certificate Issuer", [2.5.29.29];
+    OID_X509_EXT_NAME_CONSTRAINTS => "nameConstraints", "Name Constraints", [2.5.29.30];
+    OID_X509_EXT_CRL_DISTRIBUTION_POINTS => "cRLDistributionPoints", "CRL Distribution Points", [2.5.29.31];
+    OID_X509_EXT_CERTIFICATE_POLICIES => "certificatePolicies", "Certificate Policies", [2.5.29.32];
+    OID_X509_EXT_ANY_POLICY      => "anyPolicy", "Any Policy", [2.5.29.32.0];
+    OID_X509_EXT_POLICY_MAPPINGS => "policyMappings", "Policy Mappings", [2.5.29.33];
+    OID_X509_EXT_AUTHORITY_KEY_IDENTIFIER => "authorityKeyIdentifier", "Authority Key Identifier", [2.5.29.35];
+    OID_X509_EXT_POLICY_CONSTRAINTS => "policyConstraints", "Policy Constraints", [2.5.29.36];
+    OID_X509_EXT_EXTENDED_KEY_USAGE => "extendedKeyUsage", "Extended Key Usage", [2.5.29.37];
+    OID_X509_EXT_FRESHEST_CRL    => "freshestCRL", "Freshest CRL", [2.5.29.46];
+    OID_X509_EXT_INHIBIT_ANY_POLICY => "inhibitAnyPolicy", "Inhibit Any Policy", [2.5.29.54];
+    // PKIX
+    OID_PKIX_AUTHORITY_INFO_ACCESS => "authorityInfoAccess", "Authority Information Access", [1.3.6.1.5.5.7.1.1];
+    OID_PKIX_SUBJECT_INFO_ACCESS => "subjectInfoAccess", "Subject Information Access", [1.3.6.1.5.5.7.1.11];
+    OID_PKIX_QT_CPS              => "id-qt-cps", "Policy Qualifier CPS", [1.3.6.1.5.5.7.2.1];
+    OID_PKIX_QT_UNOTICE          => "id-qt-unotice", "Policy Qualifier User Notice", [1.3.6.1.5.5.7.2.2];
+    OID_PKIX_KP_SERVER_AUTH      => "serverAuth", "TLS Web Server Authentication", [1.3.6.1.5.5.7.3.1];
+    OID_PKIX_KP_CLIENT_AUTH      => "clientAuth", "TLS Web Client Authentication", [1.3.6.1.5.5.7.3.2];
+    OID_PKIX_KP_CODE_SIGNING     => "codeSigning", "Code Signing", [1.3.6.1.5.5.7.3.3];
+    OID_PKIX_KP_EMAIL_PROTECTION => "emailProtection", "E-mail Protection", [1.3.6.1.5.5.7.3.4];
+    OID_PKIX_KP_TIME_STAMPING    => "timeStamping", "Time Stamping", [1.3.6.1.5.5.7.3.8];
+    OID_PKIX_KP_OCSP_SIGNING     => "OCSPSigning", "OCSP Signing", [1.3.6.1.5.5.7.3.9];
+    OID_PKIX_ACCESS_DESCRIPTOR_OCSP => "OCSP", "OCSP", [1.3.6.1.5.5.7.48.1];
+    OID_PKIX_OCSP_BASIC          => "basicOCSPResponse", "Basic OCSP Response", [1.3.6.1.5.5.7.48.1.1];
+    OID_PKIX_OCSP_NONCE          => "Nonce", "OCSP Nonce", [1.3.6.1.5.5.7.48.1.2];
+    OID_PKIX_ACCESS_DESCRIPTOR_CA_ISSUERS => "caIssuers", "CA Issuers", [1.3.6.1.5.5.7.48.2];
+    // Netscape
+    OID_NS_CERT_TYPE             => "nsCertType", "Netscape Cert Type", [2.16.840.1.113730.1.1];
+    OID_NS_COMMENT               => "nsComment", "Netscape Comment", [2.16.840.1.113730.1.13];
+    // SNMP
+    OID_SNMP_INTERNET            => "internet", "Internet", [1.3.6.1];
+    OID_SNMP_MGMT                => "mgmt", "Management", [1.3.6.1.2];
+    OID_SNMP_MIB2                => "mib-2", "MIB-II", [1.3.6.1.2.1];
+    OID_SNMP_SYSTEM              => "system", "MIB-II System Group", [1.3.6.1.2.1.1];
+    OID_SNMP_SYS_DESCR           => "sysDescr", "System Description", [1.3.6.1.2.1.1.1];
+    OID_SNMP_SYS_OBJECT_ID       => "sysObjectID", "System Object ID", [1.3.6.1.2.1.1.2];
+    OID_SNMP_SYS_UPTIME          => "sysUpTime", "System Up Time", [1.3.6.1.2.1.1.3];
+    OID_SNMP_SYS_CONTACT         => "sysContact", "System Contact", [1.3.6.1.2.1.1.4];
+    OID_SNMP_SYS_NAME            => "sysName", "System Name", [1.3.6.1.2.1.1.5];
+    OID_SNMP_SYS_LOCATION        => "sysLocation", "System Location", [1.3.6.1.2.1.1.6];
+    OID_SNMP_SYS_SERVICES        => "sysServices", "System Services", [1.3.6.1.2.1.1.7];
+    OID_SNMP_INTERFACES          => "interfaces", "MIB-II Interfaces Group", [1.3.6.1.2.1.2];
+    OID_SNMP_IF_NUMBER           => "ifNumber", "Number of Interfaces", [1.3.6.1.2.1.2.1];
+    OID_SNMP_IF_TABLE            => "ifTable", "Interfaces Table", [1.3.6.1.2.1.2.2];
+    OID_SNMP_IP                  => "ip", "MIB-II IP Group", [1.3.6.1.2.1.4];
+    OID_SNMP_PRIVATE             => "private", "Private", [1.3.6.1.4];
+    OID_SNMP_ENTERPRISES         => "enterprises", "Private Enterprises", [1.3.6.1.4.1];
+    OID_SNMP_V2                  => "snmpV2", "SNMPv2", [1.3.6.1.6];
+    OID_SNMP_MODULES             => "snmpModules", "SNMPv2 Modules", [1.3.6.1.6.3];
+    OID_SNMP_TRAP_OID            => "snmpTrapOID", "SNMP Trap OID", [1.3.6.1.6.3.1.1.4.1];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+#[test]
+fn test_well_known_oids() {
+    let registry = OidRegistry::default().with_well_known();
+    assert_eq!(registry.len(), WELL_KNOWN_OIDS.len());
+    assert_eq!(OID_PKCS1_SHA256WITHRSA.to_string(), "1.2.840.113549.1.1.11");
+    let entry = registry.get(&"2.5.4.3".parse::<Oid>().unwrap()).unwrap();
+    assert_eq!(entry.sn(), "commonName");
+    assert_eq!(entry.description(), "Common Name");
+    // short names are unique
+    let names: HashSet<_> = WELL_KNOWN_OIDS.iter().map(|&(_, sn, _)| sn).collect();
+    assert_eq!(names.len(), WELL_KNOWN_OIDS.len());
+}
+
+#[test]
+fn test_registry_insert() {
+    let mut registry = OidRegistry::default();
+    assert!(registry.is_empty());
+    let oid = Oid::from(&[1, 3, 6, 1, 4, 1, 99999, 1]).unwrap();
+    assert!(registry.get(&oid).is_none());
+    assert_eq!(registry.insert(&oid, OidEntry::new("test", "Test OID")), None);
+    let previous = registry.insert(&oid, OidEntry::new(String::from("test2"), "Test OID 2"));
+    assert_eq!(previous, Some(OidEntry::new("test", "Test OID")));
+    assert_eq!(registry.get(&oid).map(OidEntry::sn), Some("test2"));
+    assert_eq!(registry.get_by_sn("test2"), Some(oid.clone()));
+    assert_eq!(registry.get_by_sn("test"), None);
+    assert!(registry.remove(&oid).is_some());
+    assert!(registry.is_empty());
+}
+
+}
@@ -5,12 +5,16 @@ extern crate pretty_assertions;
 extern crate hex_literal;
 #[macro_use]
 extern crate nom;
+#[macro_use]
+extern crate rusticata_macros;
+#[macro_use]
 extern crate der_parser;
 
 use der_parser::ber::*;
 use der_parser::error::*;
 use der_parser::oid::*;
-use nom::{Err, ErrorKind};
+use nom::{Context, Err, ErrorKind};
+use std::borrow::Cow;
 
 #[test]
 fn test_ber_bool() {
@@ -27,7 +31,11 @@ fn test_ber_bool() {
         parse_ber_bool(&[0x01, 0x02, 0x12, 0x34]),
         Err(Err::Error(error_position!(
             &[0x12, 0x34][..],
-            ErrorKind::Custom(BER_INVALID_LENGTH)
+            ErrorKind::Custom(ParseError {
+                kind: BerError::InvalidLength,
+                offset: 2,
+                path: vec![],
+            })
         )))
     );
 }
@@ -41,7 +49,7 @@ fn test_seq_indefinite_length() {
         Ok((
             &data[9..],
             BerObject::from_seq(vec![BerObject::from_obj(BerObjectContent::OctetString(
-                &data[4..=6]
+                Cow::Borrowed(&data[4..=6])
             )),])
         ))
     );
@@ -51,7 +59,7 @@ fn test_seq_indefinite_length() {
         Ok((
             &data[9..],
             BerObject::from_seq(vec![BerObject::from_obj(BerObjectContent::OctetString(
-                &data[4..=6]
+                Cow::Borrowed(&data[4..=6])
             )),])
         ))
     );
@@ -66,7 +74,7 @@ fn test_set_indefinite_length() {
         Ok((
             &data[9..],
             BerObject::from_set(vec![BerObject::from_obj(BerObjectContent::OctetString(
-                &data[4..=6]
+                Cow::Borrowed(&data[4..=6])
             )),])
         ))
     );
@@ -76,7 +84,7 @@ fn test_set_indefinite_length() {
         Ok((
             &data[9..],
             BerObject::from_set(vec![BerObject::from_obj(BerObjectContent::OctetString(
-                &data[4..=6]
+                Cow::Borrowed(&data[4..=6])
             )),])
         ))
     );
@@ -86,7 +94,7 @@ fn test_set_indefinite_length() {
 fn test_ber_int() {
     let empty = &b""[..];
     let bytes = [0x02, 0x03, 0x01, 0x00, 0x01];
-    let expected = BerObject::from_obj(BerObjectContent::Integer(b"\x01\x00\x01"));
+    let expected = BerObject::from_obj(BerObjectContent::Integer(Cow::Borrowed(b"\x01\x00\x01")));
     assert_eq!(parse_ber_integer(&bytes), Ok((empty, expected)));
 }
 
@@ -96,7 +104,7 @@ fn test_ber_bitstring_primitive() {
     let bytes = &[0x03, 0x07, 0x04, 0x0a, 0x3b, 0x5f, 0x29, 0x1c, 0xd0];
     let expected = BerObject::from_obj(BerObjectContent::BitString(
         4,
-        BitStringObject { data: &bytes[3..] },
+        BitStringObject { data: Cow::Borrowed(&bytes[3..]) },
     ));
     assert_eq!(parse_ber_bitstring(bytes), Ok((empty, expected)));
     //
@@ -105,7 +113,7 @@ fn test_ber_bitstring_primitive() {
     let bytes = &[0x03, 0x04, 0x06, 0x6e, 0x5d, 0xe0];
     let expected = BerObject::from_obj(BerObjectContent::BitString(
         6,
-        BitStringObject { data: &bytes[3..] },
+        BitStringObject { data: Cow::Borrowed(&bytes[3..]) },
     ));
     assert_eq!(parse_ber_bitstring(bytes), Ok((empty, expected)));
     //
@@ -114,31 +122,119 @@ fn test_ber_bitstring_primitive() {
     let bytes = &[0x03, 0x81, 0x04, 0x06, 0x6e, 0x5d, 0xc0];
     let expected = BerObject::from_obj(BerObjectContent::BitString(
         6,
-        BitStringObject { data: &bytes[4..] },
+        BitStringObject { data: Cow::Borrowed(&bytes[4..]) },
     ));
     assert_eq!(parse_ber_bitstring(bytes), Ok((empty, expected)));
 }
 
 #[test]
 fn test_ber_bitstring_constructed() {
+    let empty = &b""[..];
     let bytes = &[
         0x23, 0x80, 0x03, 0x03, 0x00, 0x0a, 0x3b, 0x03, 0x05, 0x04, 0x5f, 0x29, 0x1c, 0xd0, 0x00,
         0x00,
     ];
+    let expected = BerObject {
+        class: 0,
+        structured: 1,
+        tag: BerTag::BitString,
+        content: BerObjectContent::BitString(
+            4,
+            BitStringObject { data: Cow::Borrowed(&[0x0a, 0x3b, 0x5f, 0x29, 0x1c, 0xd0]) },
+        ),
+    };
+    assert_eq!(parse_ber_bitstring(bytes), Ok((empty, expected)));
+    //
+    // only the last segment can have unused bits
+    //
+    let bytes = hex!("23 09 03 03 04 0a 30 03 02 00 5f");
     assert_eq!(
-        parse_ber_bitstring(bytes),
+        parse_ber_bitstring(&bytes),
         Err(Err::Error(error_position!(
             &bytes[2..],
-            ErrorKind::Custom(BER_UNSUPPORTED)
+            ErrorKind::Custom(ParseError {
+                kind: BerError::InvalidValue,
+                offset: 2,
+                path: vec![BerObjectHeader {
+                    class: 0,
+                    structured: 1,
+                    tag: BerTag::BitString,
+                    len: BerSize::Definite(9),
+                }],
+            })
         )))
-    ); // XXX valid encoding
+    );
+}
+
+#[test]
+fn test_ber_octetstring_constructed() {
+    let empty = &b""[..];
+    let expected = BerObject {
+        class: 0,
+        structured: 1,
+        tag: BerTag::OctetString,
+        content: BerObjectContent::OctetString(Cow::Borrowed(b"ABCD")),
+    };
+    // definite length
+    let bytes = hex!("24 08 04 02 41 42 04 02 43 44");
+    assert_eq!(parse_ber_octetstring(&bytes), Ok((empty, expected.clone())));
+    // indefinite length
+    let bytes = hex!("24 80 04 02 41 42 04 02 43 44 00 00");
+    assert_eq!(parse_ber_octetstring(&bytes), Ok((empty, expected.clone())));
+    // nested constructed segment
+    let bytes = hex!("24 80 04 01 41 24 80 04 01 42 04 02 43 44 00 00 00 00");
+    assert_eq!(parse_ber(&bytes), Ok((empty, expected)));
+    // segments must be octet strings
+    let bytes = hex!("24 06 04 01 41 02 01 42");
+    assert!(parse_ber_octetstring(&bytes).is_err());
+}
+
+#[test]
+fn test_ber_string_constructed() {
+    let empty = &b""[..];
+    // segments of character strings are encoded as octet strings
+    let bytes = hex!("33 80 04 02 46 52 04 01 41 00 00");
+    let expected = BerObject {
+        class: 0,
+        structured: 1,
+        tag: BerTag::PrintableString,
+        content: BerObjectContent::PrintableString(Cow::Borrowed(b"FRA")),
+    };
+    assert_eq!(parse_ber(&bytes), Ok((empty, expected)));
+    let bytes = hex!("33 06 13 01 46 13 01 52");
+    assert!(parse_ber_printablestring(&bytes).is_err());
+}
+
+#[test]
+fn test_ber_string_segments() {
+    let empty = &b""[..];
+    let bytes = hex!("24 80 04 01 41 24 80 04 01 42 04 02 43 44 00 00 00 00");
+    let (rem, (obj, segments)) = parse_ber_string_segments(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(obj.as_slice(), Ok(&b"ABCD"[..]));
+    assert_eq!(
+        segments,
+        vec![
+            BerObject::from_obj(BerObjectContent::OctetString(Cow::Borrowed(b"A"))),
+            BerObject {
+                class: 0,
+                structured: 1,
+                tag: BerTag::OctetString,
+                content: BerObjectContent::OctetString(Cow::Borrowed(b"BCD")),
+            },
+        ]
+    );
+    // primitive form
+    let bytes = hex!("04 02 41 42");
+    let expected = BerObject::from_obj(BerObjectContent::OctetString(Cow::Borrowed(b"AB")));
+    assert_eq!(parse_ber_string_segments(&bytes), Ok((empty, (expected, vec![]))));
 }
 
 #[test]
 fn test_ber_octetstring_primitive() {
     let empty = &b""[..];
     let bytes = [0x04, 0x05, 0x41, 0x41, 0x41, 0x41, 0x41];
-    let expected = BerObject::from_obj(BerObjectContent::OctetString(b"AAAAA"));
+    let expected = BerObject::from_obj(BerObjectContent::OctetString(Cow::Borrowed(b"AAAAA")));
     assert_eq!(parse_ber_octetstring(&bytes), Ok((empty, expected)));
 }
 
@@ -157,7 +253,7 @@ fn test_ber_oid() {
     ];
     let expected = BerObject::from_obj(BerObjectContent::OID(Oid::from(&[
         1, 2, 840, 113549, 1, 1, 5,
-    ])));
+    ]).unwrap()));
     assert_eq!(parse_ber_oid(&bytes), Ok((empty, expected)));
 }
 
@@ -166,6 +262,14 @@ fn test_ber_enum() {
     let empty = &b""[..];
     let expected = BerObject::from_obj(BerObjectContent::Enum(2));
     assert_eq!(parse_ber_enum(&[0x0a, 0x01, 0x02]), Ok((empty, expected)));
+    // the value is a signed integer
+    let expected = BerObject::from_obj(BerObjectContent::Enum(-1));
+    assert_eq!(parse_ber_enum(&[0x0a, 0x01, 0xff]), Ok((empty, expected)));
+    let (_, obj) = parse_ber_enum(&[0x0a, 0x02, 0x00, 0x80]).expect("parsing failed");
+    assert_eq!(obj.as_u32(), Ok(128));
+    let (_, obj) = parse_ber_enum(&[0x0a, 0x01, 0x80]).expect("parsing failed");
+    assert_eq!(obj.as_i64(), Ok(-128));
+    assert_eq!(obj.as_u32(), Err(BerError::IntegerNegative));
 }
 
 #[test]
@@ -174,7 +278,7 @@ fn test_ber_utf8string() {
     let bytes = [
         0x0c, 0x0a, 0x53, 0x6f, 0x6d, 0x65, 0x2d, 0x53, 0x74, 0x61, 0x74, 0x65,
     ];
-    let expected = BerObject::from_obj(BerObjectContent::UTF8String(b"Some-State"));
+    let expected = BerObject::from_obj(BerObjectContent::UTF8String(Cow::Borrowed(b"Some-State")));
     assert_eq!(parse_ber_utf8string(&bytes), Ok((empty, expected)));
 }
 
@@ -182,7 +286,7 @@ fn test_ber_utf8string() {
 fn test_ber_relativeoid() {
     let empty = &b""[..];
     let bytes = hex!("0d 04 c2 7b 03 02");
-    let expected = BerObject::from_obj(BerObjectContent::RelativeOID(Oid::from(&[8571, 3, 2])));
+    let expected = BerObject::from_obj(BerObjectContent::RelativeOID(RelativeOid::from(&[8571, 3, 2])));
     assert_eq!(parse_ber_relative_oid(&bytes), Ok((empty, expected)));
 }
 
@@ -190,6 +294,452 @@ fn test_ber_relativeoid() {
 fn test_ber_bmpstring() {
     let empty = &b""[..];
     let bytes = hex!("1e 08 00 55 00 73 00 65 00 72");
-    let expected = BerObject::from_obj(BerObjectContent::BmpString(b"\x00U\x00s\x00e\x00r"));
-    assert_eq!(parse_ber_bmpstring(&bytes), Ok((empty, expected)));
+    let expected = BerObject::from_obj(BerObjectContent::BmpString(Cow::Borrowed(b"\x00U\x00s\x00e\x00r")));
+    assert_eq!(parse_ber_bmpstring(&bytes), Ok((empty, expected.clone())));
+    assert_eq!(expected.as_str(), Ok(Cow::Borrowed("User")));
+}
+
+#[test]
+fn test_ber_string_decoding() {
+    let obj = BerObject::from_obj(BerObjectContent::BmpString(Cow::Borrowed(b"\x00\xe9\x00t\x00\xe9")));
+    assert_eq!(obj.as_str(), Ok(Cow::Borrowed("été")));
+    let obj = BerObject::from_obj(BerObjectContent::T61String(Cow::Borrowed(b"\xc2ete\xc2")));
+    assert_eq!(obj.as_str(), Err(BerError::StringInvalidCharset));
+    assert_eq!(obj.to_string_lossy(), Ok(Cow::Borrowed("e\u{301}te\u{fffd}")));
+    let obj = BerObject::from_obj(BerObjectContent::IA5String(Cow::Borrowed(b"caf\xe9")));
+    assert_eq!(obj.as_str(), Err(BerError::StringInvalidCharset));
+    assert_eq!(obj.to_string_lossy(), Ok(Cow::Borrowed("caf\u{fffd}")));
+    let obj = BerObject::from_obj(BerObjectContent::UTF8String(Cow::Borrowed("café".as_bytes())));
+    assert_eq!(obj.as_str(), Ok(Cow::Borrowed("café")));
+    assert_eq!(BerObject::from_u64(1).as_str(), Err(BerError::BerTypeError));
+    // BER does not check the alphabet of PrintableString
+    let bytes = hex!("13 03 61 2a 62");
+    let (_, obj) = parse_ber_printablestring(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok(Cow::Borrowed("a*b")));
+}
+
+#[test]
+fn test_ber_more_string_types() {
+    let empty = &b""[..];
+    let bytes = hex!("1a 03 61 40 62");
+    let (rem, obj) = parse_ber_visiblestring(&bytes).expect("parsing failed");
+    assert_eq!(rem, empty);
+    assert_eq!(obj.tag, BerTag::VisibleString);
+    assert_eq!(obj.as_str(), Ok(Cow::Borrowed("a@b")));
+    let bytes = hex!("1c 08 00 00 00 61 00 01 f6 00");
+    let (_, obj) = parse_ber_universalstring(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok(Cow::Borrowed("a\u{1f600}")));
+    let bytes = hex!("19 02 61 62");
+    let (_, obj) = parse_ber_graphicstring(&bytes).expect("parsing failed");
+    assert_eq!(obj.content, BerObjectContent::GraphicString(Cow::Borrowed(b"ab")));
+    let bytes = hex!("15 02 c2 65");
+    let (_, obj) = parse_ber_videotexstring(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok(Cow::Borrowed("e\u{301}")));
+    // constructed ObjectDescriptor
+    let bytes = hex!("27 80 04 01 61 04 01 62 00 00");
+    let (rem, obj) = parse_ber_objectdescriptor(&bytes).expect("parsing failed");
+    assert_eq!(rem, empty);
+    assert_eq!(obj.as_str(), Ok(Cow::Borrowed("ab")));
+}
+
+#[test]
+fn test_ber_external() {
+    let empty = &b""[..];
+    // direct-reference, indirect-reference, data-value-descriptor, octet-aligned
+    let bytes = hex!("28 11 06 03 2a 03 04 02 01 05 07 03 61 62 63 81 02 01 02");
+    let (rem, obj) = parse_ber_external(&bytes).expect("parsing failed");
+    assert_eq!(rem, empty);
+    let expected = BerExternal {
+        direct_reference: Some(Oid::from(&[1, 2, 3, 4]).unwrap()),
+        indirect_reference: Some(5),
+        data_value_descriptor: Some(Cow::Borrowed(b"abc")),
+        encoding: ExternalEncoding::OctetAligned(Cow::Borrowed(b"\x01\x02")),
+    };
+    assert_eq!(obj.as_external(), Ok(&expected));
+    assert_eq!(obj.to_der().expect("serialization failed"), bytes.to_vec());
+    // single-ASN1-type
+    let bytes = hex!("28 07 a0 05 02 03 01 00 01");
+    let (_, obj) = parse_ber_external(&bytes).expect("parsing failed");
+    let ext = obj.as_external().expect("not an EXTERNAL");
+    assert_eq!(ext.direct_reference, None);
+    match ext.encoding {
+        ExternalEncoding::SingleAsn1Type(ref o) => assert_eq!(o.as_u32(), Ok(65537)),
+        _ => panic!("wrong encoding"),
+    }
+    assert_eq!(obj.to_der().expect("serialization failed"), bytes.to_vec());
+    // arbitrary
+    let bytes = hex!("28 04 82 02 07 80");
+    let (_, obj) = parse_ber_external(&bytes).expect("parsing failed");
+    let encoding = ExternalEncoding::Arbitrary(7, BitStringObject { data: Cow::Borrowed(&[0x80]) });
+    assert_eq!(obj.as_external().map(|e| &e.encoding), Ok(&encoding));
+    // missing encoding, and primitive form
+    parse_ber_external(&hex!("28 03 02 01 05")).err().expect("expected error");
+    parse_ber_external(&hex!("08 03 02 01 05")).err().expect("expected error");
+}
+
+#[test]
+fn test_ber_embedded_pdv() {
+    let empty = &b""[..];
+    // identification: syntax
+    let bytes = hex!("2b 0b a0 05 81 03 2a 03 04 82 02 de ad");
+    let (rem, obj) = parse_ber_embedded_pdv(&bytes).expect("parsing failed");
+    assert_eq!(rem, empty);
+    let expected = BerEmbeddedPdv {
+        identification: PdvIdentification::Syntax(Oid::from(&[1, 2, 3, 4]).unwrap()),
+        data_value_descriptor: None,
+        data_value: Cow::Borrowed(&[0xde, 0xad]),
+    };
+    assert_eq!(obj.as_embedded_pdv(), Ok(&expected));
+    assert_eq!(obj.to_der().expect("serialization failed"), bytes.to_vec());
+    // identification: syntaxes
+    let bytes = hex!("2b 0e a0 08 a0 06 80 01 2a 81 01 2b 82 02 de ad");
+    let (_, obj) = parse_ber_embedded_pdv(&bytes).expect("parsing failed");
+    let identification = PdvIdentification::Syntaxes {
+        abstract_syntax: Oid::from(&[1, 2]).unwrap(),
+        transfer_syntax: Oid::from(&[1, 3]).unwrap(),
+    };
+    assert_eq!(obj.as_embedded_pdv().map(|p| &p.identification), Ok(&identification));
+    assert_eq!(obj.to_der().expect("serialization failed"), bytes.to_vec());
+    // data-value with a universal tag
+    parse_ber_embedded_pdv(&hex!("2b 0b a0 05 81 03 2a 03 04 04 02 de ad"))
+        .err()
+        .expect("expected error");
+}
+
+#[test]
+fn test_ber_characterstring() {
+    let empty = &b""[..];
+    // identification: presentation-context-id
+    let bytes = hex!("3d 09 a0 03 82 01 07 82 02 68 69");
+    let (rem, obj) = parse_ber_characterstring(&bytes).expect("parsing failed");
+    assert_eq!(rem, empty);
+    let expected = BerCharacterString {
+        identification: PdvIdentification::PresentationContextId(7),
+        data_value_descriptor: None,
+        string_value: Cow::Borrowed(b"hi"),
+    };
+    assert_eq!(obj.as_character_string(), Ok(&expected));
+    assert_eq!(obj.to_der().expect("serialization failed"), bytes.to_vec());
+    // indefinite length, identification: fixed
+    let bytes = hex!("3d 80 a0 02 85 00 82 01 61 00 00");
+    let (rem, obj) = parse_ber_characterstring(&bytes).expect("parsing failed");
+    assert_eq!(rem, empty);
+    let s = obj.as_character_string().expect("not a CHARACTER STRING");
+    assert_eq!(s.identification, PdvIdentification::Fixed);
+    assert_eq!(s.string_value, Cow::Borrowed(b"a"));
+    let bytes = hex!("1d 01 00");
+    parse_ber_characterstring(&bytes).err().expect("expected error");
+}
+
+#[test]
+fn test_ber_into_owned() {
+    fn parse_owned(bytes: Vec<u8>) -> BerObject<'static> {
+        let (rem, obj) = parse_ber(&bytes).expect("parsing failed");
+        assert!(rem.is_empty());
+        obj.into_owned()
+    }
+    let obj = parse_owned(hex!("30 0d 02 03 01 00 01 03 02 07 80 0c 02 61 62").to_vec());
+    let expected = BerObject::from_seq(vec![
+        BerObject::from_int_slice(b"\x01\x00\x01"),
+        BerObject::from_obj(BerObjectContent::BitString(
+            7,
+            BitStringObject { data: Cow::Borrowed(b"\x80") },
+        )),
+        BerObject::from_obj(BerObjectContent::UTF8String(Cow::Borrowed(b"ab"))),
+    ]);
+    let obj = ::std::thread::spawn(move || obj)
+        .join()
+        .expect("thread failed");
+    assert_eq!(obj, expected);
+    assert_eq!(obj[2].as_slice(), Ok(&b"ab"[..]));
+}
+
+#[test]
+fn test_ber_tagged_indefinite_length() {
+    let empty = &b""[..];
+    // context-specific, nested indefinite sequence
+    let bytes = hex!("a0 80 30 80 02 01 01 00 00 00 00 02 01 02");
+    let expected = BerObject {
+        class: 2,
+        structured: 1,
+        tag: BerTag(0),
+        content: BerObjectContent::Unknown(BerTag(0), Cow::Borrowed(&bytes[2..9])),
+    };
+    assert_eq!(parse_ber(&bytes), Ok((&bytes[11..], expected)));
+    // application and private classes
+    let bytes = hex!("61 80 02 01 01 00 00");
+    let (rem, obj) = parse_ber(&bytes).expect("parsing failed");
+    assert_eq!(rem, empty);
+    assert_eq!(obj.content, BerObjectContent::Unknown(BerTag(1), Cow::Borrowed(&bytes[2..5])));
+    let bytes = hex!("e2 80 00 00");
+    let (rem, obj) = parse_ber(&bytes).expect("parsing failed");
+    assert_eq!(rem, empty);
+    assert_eq!(obj.content, BerObjectContent::Unknown(BerTag(2), Cow::Borrowed(empty)));
+    // definite zero length is not indefinite
+    let bytes = hex!("a0 00 00 00");
+    assert_eq!(parse_ber(&bytes).map(|(rem, _)| rem), Ok(&bytes[2..]));
+    // missing end-of-content
+    let bytes = hex!("a0 80 02 01 01");
+    assert!(parse_ber(&bytes).is_err());
+    // indefinite form is not allowed for primitive objects
+    let bytes = hex!("80 80 00 00");
+    assert!(parse_ber(&bytes).is_err());
+    // explicit tagging
+    let bytes = hex!("a0 80 02 01 01 00 00");
+    let (rem, obj) = parse_ber_explicit(&bytes, BerTag(0), parse_ber_integer).expect("parsing failed");
+    assert_eq!(rem, empty);
+    assert_eq!(obj.as_context_specific(), Ok((BerTag(0), Some(Box::new(BerObject::from_int_slice(b"\x01"))))));
+}
+
+#[test]
+fn test_ber_header_length() {
+    let (_, hdr) = ber_read_element_header(&hex!("30 00")).expect("parsing failed");
+    assert_eq!(hdr.len, BerSize::Definite(0));
+    let (_, hdr) = ber_read_element_header(&hex!("30 80")).expect("parsing failed");
+    assert_eq!(hdr.len, BerSize::Indefinite);
+    assert!(hdr.len.is_indefinite());
+    assert_eq!(hdr.len.primitive(), Err(BerError::InvalidLength));
+    let (_, hdr) = ber_read_element_header(&hex!("30 81 80")).expect("parsing failed");
+    assert_eq!(hdr.len, BerSize::Definite(0x80));
+}
+
+#[test]
+fn test_ber_empty_sequence() {
+    let empty = &b""[..];
+    let bytes = hex!("30 00 02 01 01");
+    assert_eq!(parse_ber(&bytes), Ok((&bytes[2..], BerObject::from_seq(vec![]))));
+    let bytes = hex!("31 00");
+    assert_eq!(parse_ber_set(&bytes), Ok((empty, BerObject::from_set(vec![]))));
+    // macros require the definite form
+    fn parse_int_seq(i: &[u8]) -> BerResult {
+        parse_der_sequence_of!(i, parse_ber_integer)
+    }
+    let bytes = hex!("30 80 02 01 01 00 00");
+    assert_eq!(
+        parse_int_seq(&bytes),
+        Err(Err::Error(error_position!(
+            &bytes[2..],
+            BerError::InvalidLength.into_error_kind()
+        )))
+    );
+    assert_eq!(
+        parse_int_seq(&hex!("30 00")),
+        Ok((empty, BerObject::from_seq(vec![])))
+    );
+}
+
+#[test]
+fn test_ber_error_location() {
+    // SEQUENCE { SEQUENCE { INTEGER 1, BOOLEAN (invalid length) } }
+    let bytes = hex!("30 09 30 07 02 01 01 01 02 12 34");
+    let err = match parse_ber(&bytes) {
+        Err(Err::Error(Context::Code(_, ErrorKind::Custom(e)))) => e,
+        e => panic!("unexpected result {:?}", e),
+    };
+    assert_eq!(err.kind, BerError::InvalidLength);
+    assert_eq!(err.offset, 9);
+    let tags: Vec<_> = err.path.iter().map(|hdr| (hdr.tag, hdr.len)).collect();
+    assert_eq!(
+        tags,
+        vec![
+            (BerTag::Sequence, BerSize::Definite(9)),
+            (BerTag::Sequence, BerSize::Definite(7))
+        ]
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid length at offset 9 in Sequence > Sequence"
+    );
+    // offset is relative to the input of the parsing function
+    let err = match parse_ber_sequence(&bytes[2..]) {
+        Err(Err::Error(Context::Code(_, ErrorKind::Custom(e)))) => e,
+        e => panic!("unexpected result {:?}", e),
+    };
+    assert_eq!(err.offset, 7);
+    assert_eq!(err.path.len(), 1);
+    // unexpected tag
+    let err = match parse_ber_integer(&hex!("01 01 ff")) {
+        Err(Err::Error(Context::Code(_, ErrorKind::Custom(e)))) => e,
+        e => panic!("unexpected result {:?}", e),
+    };
+    assert_eq!(
+        err.to_string(),
+        "unexpected tag (expected Integer, found Boolean) at offset 2"
+    );
+    // indefinite length objects before the error are skipped
+    let bytes = hex!("30 80 30 80 02 01 01 00 00 01 02 12 34 00 00");
+    let err = match parse_ber(&bytes) {
+        Err(Err::Error(Context::List(mut l))) => match l.remove(0).1 {
+            ErrorKind::Custom(e) => e,
+            e => panic!("unexpected error {:?}", e),
+        },
+        e => panic!("unexpected result {:?}", e),
+    };
+    assert_eq!(err.to_string(), "invalid length at offset 11 in Sequence");
+    // lengths larger than the input
+    for bytes in &[hex!("30 88 ff ff ff ff ff ff ff ff"), hex!("04 88 ff ff ff ff ff ff ff ff")] {
+        assert!(parse_ber(bytes).is_err());
+        assert!(der_parser::der::parse_der(bytes).is_err());
+    }
+}
+
+#[test]
+fn test_ber_nested_indefinite() {
+    // errors in indefinite length objects are returned with the errors of the enclosing objects
+    fn parse_error(res: BerResult) -> ParseError {
+        match res {
+            Err(Err::Error(Context::Code(_, ErrorKind::Custom(e)))) => e,
+            Err(Err::Error(Context::List(mut l))) => match l.remove(0).1 {
+                ErrorKind::Custom(e) => e,
+                e => panic!("unexpected error {:?}", e),
+            },
+            e => panic!("unexpected result {:?}", e),
+        }
+    }
+    // end-of-content probes must not parse the input again: this used to take exponential time
+    let nested = |depth: usize, inner: &[u8]| {
+        let mut v = vec![0x30, 0x80].repeat(depth);
+        v.extend_from_slice(inner);
+        v.extend(vec![0x00, 0x00].repeat(depth));
+        v
+    };
+    let bytes = nested(MAX_RECURSION, &hex!("02 01 01"));
+    let (rem, _) = parse_ber(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    let err = parse_error(parse_ber(&nested(MAX_RECURSION + 1, &hex!("02 01 01"))));
+    assert_eq!(err.kind, BerError::BerMaxDepth);
+    assert_eq!(err.offset, 2 * (MAX_RECURSION + 1));
+    // invalid object in the innermost sequence
+    let err = parse_error(parse_ber(&nested(MAX_RECURSION, &hex!("01 02 ff ff"))));
+    assert_eq!(err.kind, BerError::InvalidLength);
+    assert_eq!(err.offset, 2 * MAX_RECURSION + 2);
+    assert_eq!(err.path.len(), MAX_RECURSION);
+}
+
+#[test]
+fn test_ber_parser_options() {
+    fn error_kind(res: BerResult) -> (BerError, usize) {
+        match res {
+            Err(Err::Error(Context::Code(_, ErrorKind::Custom(e)))) => (e.kind, e.offset),
+            e => panic!("unexpected result {:?}", e),
+        }
+    }
+    let default = ParserOptions::default();
+    let bytes = hex!("30 80 30 80 02 01 01 06 03 2a 03 04 00 00 0f 01 00 00 00");
+    assert_eq!(parse_ber_with_options(&bytes, &default), parse_ber(&bytes));
+    let options = ParserOptions { allow_indefinite: false, ..default };
+    assert_eq!(error_kind(parse_ber_with_options(&bytes, &options)), (BerError::InvalidLength, 0));
+    let options = ParserOptions { max_depth: 1, ..default };
+    assert_eq!(error_kind(parse_ber_with_options(&bytes, &options)), (BerError::BerMaxDepth, 4));
+    // the depth is limited by MAX_RECURSION
+    let options = ParserOptions { max_depth: MAX_RECURSION + 1, ..default };
+    assert_eq!(error_kind(parse_ber_with_options(&bytes, &options)), (BerError::Unsupported, 0));
+    let options = ParserOptions { max_size: 18, ..default };
+    assert_eq!(error_kind(parse_ber_with_options(&bytes, &options)), (BerError::LimitExceeded, 0));
+    let options = ParserOptions { max_size: 19, ..default };
+    assert!(parse_ber_with_options(&bytes, &options).is_ok());
+    let options = ParserOptions { max_elements: 1, ..default };
+    assert_eq!(error_kind(parse_ber_with_options(&bytes, &options)), (BerError::LimitExceeded, 7));
+    let options = ParserOptions { max_oid_arcs: 3, ..default };
+    assert_eq!(error_kind(parse_ber_with_options(&bytes, &options)), (BerError::LimitExceeded, 7));
+    let options = ParserOptions { max_oid_arcs: 4, ..default };
+    assert!(parse_ber_with_options(&bytes, &options).is_ok());
+    let options = ParserOptions { allow_unknown_tags: false, ..default };
+    assert_eq!(error_kind(parse_ber_with_options(&bytes, &options)), (BerError::UnknownTag, 14));
+    // a definite length larger than the limit is rejected before reading the content
+    let options = ParserOptions { max_size: 1024, ..default };
+    assert_eq!(
+        error_kind(parse_ber_with_options(&hex!("04 84 10 00 00 00 41"), &options)),
+        (BerError::LimitExceeded, 0)
+    );
+    // the content of tagged objects is not decoded by parse_ber, and is not checked either
+    let bytes = hex!("a0 01 ff");
+    assert_eq!(parse_ber_with_options(&bytes, &default), parse_ber(&bytes));
+    let bytes = hex!("e0 01 ff");
+    assert_eq!(parse_ber_with_options(&bytes, &default), parse_ber(&bytes));
+}
+
+#[test]
+fn test_ber_iterative() {
+    let inputs: &[&[u8]] = &[
+        &hex!("02 01 01"),
+        &hex!("30 80 30 80 02 01 01 06 03 2a 03 04 00 00 0f 01 00 00 00"),
+        &hex!("30 0d 31 06 02 01 01 02 01 02 30 00 31 80 00 00"),
+        &hex!("30 0a a0 80 30 03 02 01 01 00 00 05 00"),
+        &hex!("31 80 24 80 04 01 41 00 00 00 00 02 01 01"),
+        &hex!("30 03 02 01 01 02 01 02"),
+        // invalid objects
+        &hex!("30 04 02 03 01 00 01"),
+        &hex!("30 80 02 01 01"),
+        &hex!("30 03 01 02 ff"),
+        &hex!("10 00"),
+    ];
+    for &bytes in inputs {
+        let expected = parse_ber(bytes).map(|(rem, obj)| (rem.len(), obj));
+        let res = parse_ber_iterative(bytes, &ParserOptions::default()).map(|(rem, obj)| (rem.len(), obj));
+        assert_eq!(res.is_ok(), expected.is_ok(), "input {:x?}", bytes);
+        if expected.is_ok() {
+            assert_eq!(res, expected);
+        }
+    }
+    // the limits are the same as parse_ber_with_options
+    let bytes = hex!("30 80 30 80 02 01 01 06 03 2a 03 04 00 00 0f 01 00 00 00");
+    let default = ParserOptions::default();
+    let limits = [
+        ParserOptions { allow_indefinite: false, ..default },
+        ParserOptions { max_depth: 1, ..default },
+        ParserOptions { max_size: 18, ..default },
+        ParserOptions { max_size: 19, ..default },
+        ParserOptions { max_elements: 1, ..default },
+        ParserOptions { max_oid_arcs: 3, ..default },
+        ParserOptions { allow_unknown_tags: false, ..default },
+    ];
+    for options in &limits {
+        assert_eq!(parse_ber_iterative(&bytes, options), parse_ber_with_options(&bytes, options));
+    }
+    // other constructed objects are read by the recursive parser, so they are still limited to
+    // MAX_RECURSION levels
+    let depth = MAX_RECURSION + 10;
+    let mut bytes = vec![0xa0, 0x80].repeat(depth);
+    bytes.extend(vec![0x00, 0x00].repeat(depth));
+    let options = ParserOptions { max_depth: 2 * depth, ..default };
+    match parse_ber_iterative(&bytes, &options) {
+        Err(Err::Error(Context::Code(_, ErrorKind::Custom(e)))) => assert_eq!(e.kind, BerError::BerMaxDepth),
+        e => panic!("unexpected result {:?}", e),
+    }
+    let mut bytes = vec![0x30, 0x80].repeat(depth);
+    bytes.extend(vec![0x00, 0x00].repeat(depth));
+    assert!(parse_ber_iterative(&bytes, &options).is_ok());
+    // deep nesting on a small stack
+    let depth = 5000;
+    let mut bytes = vec![0x30, 0x80].repeat(depth);
+    bytes.extend_from_slice(&hex!("02 01 01"));
+    bytes.extend(vec![0x00, 0x00].repeat(depth));
+    let child = ::std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || {
+            let options = ParserOptions { max_depth: depth, ..Default::default() };
+            let (rem, obj) = parse_ber_iterative(&bytes, &options).expect("parsing failed");
+            assert!(rem.is_empty());
+            // count the levels, and drop the tree iteratively
+            let mut levels = 0;
+            let mut obj = obj;
+            while let BerObjectContent::Sequence(mut l) = obj.content {
+                levels += 1;
+                obj = l.pop().expect("empty sequence");
+            }
+            assert_eq!(levels, depth);
+            assert_eq!(obj.as_u32(), Ok(1));
+            // the error is located without parsing the input again
+            let options = ParserOptions { max_depth: depth - 1, ..Default::default() };
+            match parse_ber_iterative(&bytes, &options) {
+                Err(Err::Error(Context::Code(_, ErrorKind::Custom(e)))) => {
+                    assert_eq!((e.kind, e.offset), (BerError::BerMaxDepth, 2 * depth));
+                    assert_eq!(e.path.len(), depth);
+                }
+                e => panic!("unexpected result {:?}", e.map(|_| ())),
+            }
+        })
+        .expect("could not spawn thread");
+    child.join().expect("parsing thread failed");
 }
@@ -0,0 +1,280 @@
+#[macro_use]
+extern crate pretty_assertions;
+
+#[macro_use]
+extern crate hex_literal;
+#[macro_use]
+extern crate der_parser;
+
+use der_parser::ber::*;
+use der_parser::der::*;
+use der_parser::error::BerError;
+use der_parser::oid::Oid;
+
+fn der_roundtrip(bytes: &[u8]) {
+    let (rem, obj) = parse_der(bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    let v = obj.to_der().expect("serialization failed");
+    assert_eq!(&v[..], bytes);
+}
+
+#[test]
+fn test_serialize_primitive() {
+    der_roundtrip(&hex!("01 01 ff"));
+    der_roundtrip(&hex!("01 01 00"));
+    der_roundtrip(&hex!("02 03 01 00 01"));
+    der_roundtrip(&hex!("03 04 06 6e 5d c0"));
+    der_roundtrip(&hex!("04 05 41 41 41 41 41"));
+    der_roundtrip(&hex!("05 00"));
+    der_roundtrip(&hex!("06 09 2a 86 48 86 f7 0d 01 01 05"));
+    der_roundtrip(&hex!("0a 01 02"));
+    der_roundtrip(&hex!("0a 02 00 80"));
+    // enumerated values are signed
+    der_roundtrip(&hex!("0a 01 ff"));
+    der_roundtrip(&hex!("0a 01 80"));
+    der_roundtrip(&hex!("0c 0a 53 6f 6d 65 2d 53 74 61 74 65"));
+    der_roundtrip(&hex!("0d 04 c2 7b 03 02"));
+    der_roundtrip(&hex!("17 0d 30 32 31 32 31 33 31 34 32 39 32 33 5a"));
+}
+
+#[test]
+fn test_serialize_real() {
+    der_roundtrip(&hex!("09 00"));
+    der_roundtrip(&hex!("09 03 80 fe 03"));
+    der_roundtrip(&hex!("09 01 41"));
+    der_roundtrip(&hex!("09 01 43"));
+    assert_eq!(BerObject::from_f64(-1.5).to_der().unwrap(), hex!("09 03 c0 ff 03"));
+    assert_eq!(BerObject::from_f64(1024.0).to_der().unwrap(), hex!("09 03 80 0a 01"));
+    // decimal encodings are written in binary form
+    let (_, obj) = parse_der(&hex!("09 07 03 31 35 2e 45 2d 31")).expect("parsing failed");
+    assert_eq!(obj.to_der().unwrap(), hex!("09 03 80 ff 03"));
+    // so they do not round trip
+    let (_, obj) = parse_der(&hex!("09 06 03 31 2e 45 2b 30")).expect("parsing failed");
+    assert_eq!(obj.content, BerObjectContent::Real(1.0));
+    assert_eq!(obj.to_der().unwrap(), hex!("09 03 80 00 01"));
+    // mantissas wider than 53 bits are rounded
+    let (_, obj) = parse_der(&hex!("09 09 80 00 7f ff ff ff ff ff ff")).expect("parsing failed");
+    assert_eq!(obj.content, BerObjectContent::Real(36_028_797_018_963_968.0));
+    assert_eq!(obj.to_der().unwrap(), hex!("09 03 80 37 01"));
+}
+
+#[test]
+fn test_serialize_constructed() {
+    der_roundtrip(&hex!("30 0a 02 03 01 00 01 02 03 01 00 00"));
+    der_roundtrip(&hex!("31 0a 02 03 01 00 00 02 03 01 00 01"));
+    der_roundtrip(&hex!("30 07 31 05 02 03 01 00 01"));
+    der_roundtrip(&hex!("a0 03 02 01 02"));
+    der_roundtrip(&hex!("9f a2 22 01 00"));
+}
+
+#[test]
+fn test_serialize_long_length() {
+    let mut bytes = vec![0x04, 0x81, 0x80];
+    bytes.extend(vec![0x41; 0x80]);
+    der_roundtrip(&bytes);
+    let mut bytes = vec![0x04, 0x82, 0x01, 0x00];
+    bytes.extend(vec![0x41; 0x100]);
+    der_roundtrip(&bytes);
+}
+
+#[test]
+fn test_serialize_modified() {
+    let bytes = hex!("30 0a 02 03 01 00 01 02 03 01 00 00");
+    let (_, obj) = parse_der(&bytes).expect("parsing failed");
+    let mut l = obj.as_sequence().expect("not a sequence").clone();
+    l.push(BerObject::from_obj(BerObjectContent::Boolean(true)));
+    let obj = BerObject::from_seq(l);
+    assert_eq!(
+        obj.to_der().expect("serialization failed"),
+        hex!("30 0d 02 03 01 00 01 02 03 01 00 00 01 01 ff").to_vec()
+    );
+}
+
+#[test]
+fn test_serialize_oid() {
+    let obj = BerObject::from(Oid::from(&[2, 999, 3]).unwrap());
+    assert_eq!(obj.to_der().expect("serialization failed"), hex!("06 03 88 37 03").to_vec());
+    // OIDs which cannot be encoded cannot be built
+    assert!(Oid::from(&[1]).is_err());
+    assert!(Oid::from(&[1, 40]).is_err());
+}
+
+#[test]
+fn test_serialize_explicit() {
+    let obj = BerObject {
+        class: 2,
+        structured: 1,
+        tag: BerTag(0),
+        content: BerObjectContent::ContextSpecific(
+            BerTag(0),
+            Some(Box::new(BerObject::from_int_slice(b"\x02"))),
+        ),
+    };
+    assert_eq!(obj.to_der().expect("serialization failed"), hex!("a0 03 02 01 02").to_vec());
+    let obj = BerObject::from_seq(vec![
+        BerObject::from_obj(BerObjectContent::ContextSpecific(BerTag(0), None)),
+        BerObject::from_int_slice(b"\x02"),
+    ]);
+    assert_eq!(obj.to_der().expect("serialization failed"), hex!("30 03 02 01 02").to_vec());
+}
+
+#[test]
+fn test_write_der() {
+    let obj = BerObject::from_int_slice(b"\x01\x00\x01");
+    let mut v = Vec::new();
+    let sz = obj.write_der(&mut v).expect("serialization failed");
+    assert_eq!(sz, 5);
+    assert_eq!(v, hex!("02 03 01 00 01").to_vec());
+    assert_eq!(obj.to_ber().expect("serialization failed"), v);
+}
+
+#[test]
+fn test_build_integer() {
+    assert_eq!(BerObject::from_u64(0).to_der().unwrap(), hex!("02 01 00").to_vec());
+    assert_eq!(BerObject::from_u64(0x80).to_der().unwrap(), hex!("02 02 00 80").to_vec());
+    assert_eq!(
+        BerObject::from_u64(::std::u64::MAX).to_der().unwrap(),
+        hex!("02 09 00 ff ff ff ff ff ff ff ff").to_vec()
+    );
+    assert_eq!(BerObject::from_i64(-1).to_der().unwrap(), hex!("02 01 ff").to_vec());
+    assert_eq!(BerObject::from_i64(-129).to_der().unwrap(), hex!("02 02 ff 7f").to_vec());
+    assert_eq!(BerObject::from_i64(0x10001), BerObject::from_int_slice(b"\x01\x00\x01"));
+}
+
+#[test]
+fn test_build_strings() {
+    let s = String::from("Some-State");
+    assert_eq!(
+        BerObject::from_str_utf8(s).to_der().unwrap(),
+        hex!("0c 0a 53 6f 6d 65 2d 53 74 61 74 65").to_vec()
+    );
+    assert_eq!(BerObject::from_printable("FR").to_der().unwrap(), hex!("13 02 46 52").to_vec());
+    assert_eq!(
+        BerObject::from_octetstring(vec![0x41, 0x41]).to_der().unwrap(),
+        hex!("04 02 41 41").to_vec()
+    );
+    assert_eq!(
+        BerObject::from_bitstring(&[0x6e, 0x5d, 0xc0][..], 6).to_der().unwrap(),
+        hex!("03 04 06 6e 5d c0").to_vec()
+    );
+}
+
+#[test]
+fn test_build_tagged() {
+    let obj = BerObject::from_explicit(BerTag(0), BerObject::from_u64(2));
+    assert_eq!(obj.to_der().unwrap(), hex!("a0 03 02 01 02").to_vec());
+    let obj = BerObject::from_implicit(BerTag(1), BerObject::from_bool(true));
+    assert_eq!(obj.to_der().unwrap(), hex!("81 01 ff").to_vec());
+    let obj = BerObject::from_implicit(BerTag(1), ber_sequence![BerObject::from_bool(true)]);
+    assert_eq!(obj.to_der().unwrap(), hex!("a1 03 01 01 ff").to_vec());
+}
+
+#[test]
+fn test_build_macros() {
+    fn build() -> BerObject<'static> {
+        let name = String::from("abc");
+        ber_sequence![
+            BerObject::from_u64(1),
+            ber_set![BerObject::from_bool(false), BerObject::from_str_utf8(name)],
+        ]
+    }
+    let bytes = build().to_der().expect("serialization failed");
+    assert_eq!(bytes, hex!("30 0d 02 01 01 31 08 01 01 00 0c 03 61 62 63").to_vec());
+    let (_, obj) = parse_der(&bytes).expect("parsing failed");
+    assert_eq!(obj, build());
+    let bytes = ber_sequence![].to_der().expect("serialization failed");
+    assert_eq!(bytes, hex!("30 00").to_vec());
+    assert_eq!(parse_der(&bytes).map(|(_, obj)| obj), Ok(ber_sequence![]));
+}
+
+#[test]
+fn test_ber_to_der() {
+    let bytes = hex!(
+        "30 80
+            01 01 01
+            02 02 00 01
+            24 80 04 01 41 04 02 42 43 00 00
+            31 06 04 01 42 04 01 41
+            a0 80 02 81 01 05 00 00
+            23 80 03 02 00 ff 03 02 04 f0 00 00
+         00 00"
+    );
+    let (der, report) = ber_to_der_with_report(&bytes).expect("conversion failed");
+    let expected = hex!(
+        "30 1d
+            01 01 ff
+            02 01 01
+            04 03 41 42 43
+            31 06 04 01 41 04 01 42
+            a0 03 02 01 05
+            03 03 04 ff f0"
+    );
+    assert_eq!(der, expected.to_vec());
+    let found: Vec<_> = report.iter().map(|v| (v.kind, v.offset)).collect();
+    assert_eq!(
+        found,
+        vec![
+            (DerViolationKind::IndefiniteLength, 0),
+            (DerViolationKind::NonCanonicalBoolean, 2),
+            (DerViolationKind::NonMinimalInteger, 5),
+            (DerViolationKind::IndefiniteLength, 9),
+            (DerViolationKind::ConstructedString, 9),
+            (DerViolationKind::UnsortedSet, 25),
+            (DerViolationKind::IndefiniteLength, 28),
+            (DerViolationKind::NonMinimalLength, 30),
+            (DerViolationKind::IndefiniteLength, 36),
+            (DerViolationKind::ConstructedString, 36),
+        ]
+    );
+    assert!(validate_der(&der).is_empty());
+    assert!(parse_der(&der).is_ok());
+    // DER input is not modified
+    assert_eq!(ber_to_der_with_report(&der), Ok((der.clone(), vec![])));
+    // SET sorted by tag is kept
+    let bytes = hex!("31 08 a0 03 02 01 01 81 01 00");
+    assert_eq!(ber_to_der(&bytes), Ok(bytes.to_vec()));
+    // SET components are sorted by tag, even if the encodings are sorted
+    let (der, report) = ber_to_der_with_report(&hex!("31 08 81 01 00 a0 03 02 01 01")).expect("conversion failed");
+    assert_eq!(der, bytes.to_vec());
+    assert_eq!((report[0].kind, report[0].offset), (DerViolationKind::UnsortedSet, 5));
+    // unused bits of bit strings are cleared
+    let (der, report) = ber_to_der_with_report(&hex!("03 02 04 ff")).expect("conversion failed");
+    assert_eq!(der, hex!("03 02 04 f0").to_vec());
+    assert_eq!((report[0].kind, report[0].offset), (DerViolationKind::BitStringPadding, 0));
+    let (der, report) = ber_to_der_with_report(&hex!("23 80 03 02 00 ff 03 02 04 ff 00 00")).expect("conversion failed");
+    assert_eq!(der, hex!("03 03 04 ff f0").to_vec());
+    assert!(report.iter().any(|v| v.kind == DerViolationKind::BitStringPadding));
+    assert!(validate_der(&der).is_empty());
+    // REAL values are normalized without changing the value
+    let (der, report) = ber_to_der_with_report(&hex!("09 03 80 cf fe")).expect("conversion failed");
+    assert_eq!(der, hex!("09 03 80 d0 7f").to_vec());
+    assert_eq!((report[0].kind, report[0].offset), (DerViolationKind::NonCanonicalReal, 0));
+    assert_eq!(ber_to_der(&hex!("09 04 02 31 2e 30")), Ok(hex!("09 06 03 31 2e 45 2b 30").to_vec()));
+    // private tags are decoded as universal types by parse_der
+    assert_eq!(ber_to_der(&hex!("d1 04 00 63 61 d8")).unwrap_err().kind, BerError::ConstructExpected);
+    assert_eq!(ber_to_der(&hex!("c1 01 01")), Ok(hex!("c1 01 ff").to_vec()));
+    // other constraints cannot be fixed
+    let e = ber_to_der(&hex!("30 04 13 02 87 80")).unwrap_err();
+    assert_eq!((e.kind, e.offset), (BerError::StringInvalidCharset, 2));
+    assert!(ber_to_der(b"\x17\x11991231235959+0100").is_err());
+    assert!(ber_to_der(b"\x18\x1220191231235959.50Z").is_err());
+    assert!(ber_to_der(b"\x1f\x1f\x0a2019-02-30").is_err());
+    assert!(ber_to_der(b"\x1f\x22\x03P1Y").is_ok());
+    assert!(ber_to_der(b"\x1f\x22\x02PY").is_err());
+    // the result is valid DER
+    let inputs: &[&[u8]] = &[
+        &hex!("31 80 09 03 90 02 03 24 80 04 01 41 00 00 c1 01 01 00 00"),
+        &hex!("a0 80 23 80 03 02 04 ff 00 00 00 00"),
+        &hex!("30 80 17 0d 39 39 31 32 33 31 32 33 35 39 35 39 5a 00 00"),
+    ];
+    for &bytes in inputs {
+        let der = ber_to_der(bytes).expect("conversion failed");
+        assert!(parse_der(&der).is_ok(), "input {:x?}", bytes);
+        assert!(validate_der(&der).is_empty(), "input {:x?}", bytes);
+    }
+    // invalid BER, or trailing data
+    let e = ber_to_der(&hex!("30 80 02 01 01")).unwrap_err();
+    assert_eq!(e.kind, BerError::ObjectTooShort);
+    let e = ber_to_der(&hex!("02 01 01 00")).unwrap_err();
+    assert_eq!((e.kind, e.offset), (BerError::InvalidLength, 3));
+}
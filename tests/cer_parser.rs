@@ -0,0 +1,88 @@
+extern crate der_parser;
+#[macro_use]
+extern crate hex_literal;
+
+use der_parser::ber::*;
+use der_parser::cer::*;
+
+#[test]
+fn test_cer_constructed() {
+    // constructed objects use the indefinite length
+    let bytes = hex!("30 80 02 01 01 01 01 ff 00 00");
+    let (rem, obj) = parse_cer_sequence(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(
+        obj.content,
+        BerObjectContent::Sequence(vec![BerObject::from_int_slice(b"\x01"), BerObject::from_bool(true)])
+    );
+    assert!(parse_cer(&hex!("30 06 02 01 01 01 01 ff")).is_err());
+    // nested objects are checked
+    assert!(parse_cer(&hex!("30 80 30 03 02 01 01 00 00")).is_err());
+    // primitive objects use the minimal length
+    assert!(parse_cer(&hex!("02 81 01 01")).is_err());
+    // deeply nested objects
+    let nested = |inner: &[u8]| {
+        let mut v = vec![0x30, 0x80].repeat(MAX_RECURSION);
+        v.extend_from_slice(inner);
+        v.extend(vec![0x00, 0x00].repeat(MAX_RECURSION));
+        v
+    };
+    let bytes = nested(&hex!("02 01 01"));
+    let (rem, _) = parse_cer(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert!(parse_cer(&nested(&hex!("02 02 00 01"))).is_err());
+}
+
+#[test]
+fn test_cer_primitive() {
+    assert!(parse_cer_bool(&hex!("01 01 ff")).is_ok());
+    assert!(parse_cer_bool(&hex!("01 01 01")).is_err());
+    assert!(parse_cer_integer(&hex!("02 02 00 01")).is_err());
+    assert_eq!(parse_cer_u32(&hex!("02 02 00 80")), Ok((&[][..], 128)));
+    assert!(parse_cer_bitstring(&hex!("03 02 04 f0")).is_ok());
+    assert!(parse_cer_bitstring(&hex!("03 02 04 f1")).is_err());
+    assert!(parse_cer_utctime(b"\x17\x0d991231235959Z").is_ok());
+    assert!(parse_cer_utctime(b"\x17\x0f991231235959+0100").is_err());
+}
+
+#[test]
+fn test_cer_set_order() {
+    assert!(parse_cer_set(&hex!("31 80 01 01 ff 02 01 00 00 00")).is_ok());
+    assert!(parse_cer_set(&hex!("31 80 02 01 00 01 01 ff 00 00")).is_err());
+    assert!(parse_cer_set(&hex!("31 80 04 01 41 04 02 41 41 00 00")).is_ok());
+    assert!(parse_cer_set(&hex!("31 80 04 02 41 41 04 01 41 00 00")).is_err());
+}
+
+#[test]
+fn test_cer_segmented_string() {
+    // up to 1000 octets, strings use the primitive form
+    let mut bytes = vec![0x04, 0x82, 0x03, 0xe8];
+    bytes.extend_from_slice(&[0x41; 1000]);
+    assert!(parse_cer_octetstring(&bytes).is_ok());
+    let mut bytes = vec![0x04, 0x82, 0x03, 0xe9];
+    bytes.extend_from_slice(&[0x41; 1001]);
+    assert!(parse_cer_octetstring(&bytes).is_err());
+    // longer strings are split in segments of 1000 octets
+    let segmented = |sizes: &[usize]| {
+        let mut v = vec![0x24, 0x80];
+        for &sz in sizes {
+            if sz > 255 {
+                v.extend_from_slice(&[0x04, 0x82, (sz >> 8) as u8, sz as u8]);
+            } else if sz > 127 {
+                v.extend_from_slice(&[0x04, 0x81, sz as u8]);
+            } else {
+                v.extend_from_slice(&[0x04, sz as u8]);
+            }
+            v.extend(std::iter::repeat(0x41).take(sz));
+        }
+        v.extend_from_slice(&[0x00, 0x00]);
+        v
+    };
+    let bytes = segmented(&[1000, 1000, 10]);
+    let (rem, _) = parse_cer_octetstring(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert!(parse_cer(&bytes).is_ok());
+    assert!(parse_cer_octetstring(&segmented(&[10])).is_err());
+    assert!(parse_cer_octetstring(&segmented(&[999, 10])).is_err());
+    assert!(parse_cer_octetstring(&segmented(&[1000, 0])).is_err());
+}
@@ -10,7 +10,8 @@ use der_parser::*;
 use der_parser::ber::*;
 use der_parser::error::*;
 use oid::Oid;
-use nom::{IResult,Err,ErrorKind,Needed,be_u16,be_u32};
+use nom::{Err,ErrorKind,Needed,be_u16,be_u32};
+use std::borrow::Cow;
 
 #[derive(Debug, PartialEq)]
 struct MyStruct<'a>{
@@ -18,7 +19,7 @@ struct MyStruct<'a>{
     b: BerObject<'a>,
 }
 
-fn parse_struct01(i: &[u8]) -> IResult<&[u8],(BerObjectHeader,MyStruct)> {
+fn parse_struct01(i: &[u8]) -> BerResult<(BerObjectHeader,MyStruct)> {
     parse_der_struct!(
         i,
         a: parse_ber_integer >>
@@ -27,19 +28,19 @@ fn parse_struct01(i: &[u8]) -> IResult<&[u8],(BerObjectHeader,MyStruct)> {
     )
 }
 
-fn parse_struct01_complete(i: &[u8]) -> IResult<&[u8],(BerObjectHeader,MyStruct)> {
+fn parse_struct01_complete(i: &[u8]) -> BerResult<(BerObjectHeader,MyStruct)> {
     parse_der_struct!(
         i,
         a: parse_ber_integer >>
         b: parse_ber_integer >>
-           empty!() >>
+           ber_empty!() >>
         ( MyStruct{ a: a, b: b } )
     )
 }
 
 // calling user function
 #[allow(dead_code)]
-fn parse_struct02(i: &[u8]) -> IResult<&[u8],(BerObjectHeader,())> {
+fn parse_struct02(i: &[u8]) -> BerResult<(BerObjectHeader,())> {
     parse_der_struct!(
         i,
         _a: parse_ber_integer >>
@@ -50,7 +51,7 @@ fn parse_struct02(i: &[u8]) -> IResult<&[u8],(BerObjectHeader,())> {
 
 // embedded DER structs
 #[allow(dead_code)]
-fn parse_struct03(i: &[u8]) -> IResult<&[u8],(BerObjectHeader,())> {
+fn parse_struct03(i: &[u8]) -> BerResult<(BerObjectHeader,())> {
     parse_der_struct!(
         i,
         _a: parse_ber_integer >>
@@ -63,13 +64,13 @@ fn parse_struct03(i: &[u8]) -> IResult<&[u8],(BerObjectHeader,())> {
 }
 
 // verifying tag
-fn parse_struct04(i: &[u8], tag:BerTag) -> IResult<&[u8],(BerObjectHeader,MyStruct)> {
+fn parse_struct04(i: &[u8], tag:BerTag) -> BerResult<(BerObjectHeader,MyStruct)> {
     parse_der_struct!(
         i,
         TAG tag,
         a: parse_ber_integer >>
         b: parse_ber_integer >>
-           empty!() >>
+           ber_empty!() >>
         ( MyStruct{ a: a, b: b } )
     )
 }
@@ -86,7 +87,7 @@ fn struct01() {
             class: 0,
             structured: 1,
             tag: BerTag::Sequence,
-            len: 0xa,
+            len: BerSize::Definite(0xa),
         },
         MyStruct {
             a: BerObject::from_int_slice(b"\x01\x00\x01"),
@@ -110,7 +111,7 @@ fn struct02() {
     ];
     #[derive(Debug, PartialEq)]
     struct Attr<'a> {
-        oid: Oid,
+        oid: Oid<'a>,
         val: BerObject<'a>,
     };
     #[derive(Debug, PartialEq)]
@@ -125,43 +126,43 @@ fn struct02() {
         l: vec![
             Rdn{
                 a: Attr{
-                    oid: Oid::from(&[2, 5, 4, 6]), // countryName
-                    val: BerObject::from_obj(BerObjectContent::PrintableString(b"FR")),
+                    oid: Oid::from(&[2, 5, 4, 6]).unwrap(), // countryName
+                    val: BerObject::from_obj(BerObjectContent::PrintableString(Cow::Borrowed(b"FR"))),
                 }
             },
             Rdn{
                 a: Attr{
-                    oid: Oid::from(&[2, 5, 4, 8]), // stateOrProvinceName
-                    val: BerObject::from_obj(BerObjectContent::UTF8String(b"Some-State")),
+                    oid: Oid::from(&[2, 5, 4, 8]).unwrap(), // stateOrProvinceName
+                    val: BerObject::from_obj(BerObjectContent::UTF8String(Cow::Borrowed(b"Some-State"))),
                 }
             },
             Rdn{
                 a: Attr{
-                    oid: Oid::from(&[2, 5, 4, 10]), // organizationName
-                    val: BerObject::from_obj(BerObjectContent::UTF8String(b"Internet Widgits Pty Ltd")),
+                    oid: Oid::from(&[2, 5, 4, 10]).unwrap(), // organizationName
+                    val: BerObject::from_obj(BerObjectContent::UTF8String(Cow::Borrowed(b"Internet Widgits Pty Ltd"))),
                 }
             },
         ]
     };
-    fn parse_directory_string(i:&[u8]) -> IResult<&[u8],BerObject> {
+    fn parse_directory_string(i:&[u8]) -> BerResult {
         alt!(i, parse_ber_utf8string | parse_ber_printablestring | parse_ber_ia5string)
     }
-    fn parse_attr_type_and_value(i:&[u8]) -> IResult<&[u8],Attr> {
+    fn parse_attr_type_and_value<'a>(i: &'a [u8]) -> BerResult<'a, Attr<'a>> {
         parse_der_struct!(i,
-            o: map_res!(parse_ber_oid,|x: BerObject| x.as_oid().map(|o| o.clone())) >>
+            o: map_res!(parse_ber_oid, |x: BerObject<'a>| x.as_oid_val()) >>
             s: parse_directory_string >>
             ( Attr{oid: o, val: s} )
         ).map(|(rem,x)| (rem,x.1))
     };
-    fn parse_rdn(i:&[u8]) -> IResult<&[u8],Rdn> {
+    fn parse_rdn(i:&[u8]) -> BerResult<Rdn> {
         parse_der_struct!(i,
             a: parse_attr_type_and_value >>
             ( Rdn{a: a} )
         ).map(|(rem,x)| (rem,x.1))
     }
-    fn parse_name(i:&[u8]) -> IResult<&[u8],Name> {
+    fn parse_name(i:&[u8]) -> BerResult<Name> {
         parse_der_struct!(i,
-            l: many0!(complete!(parse_rdn)) >>
+            l: many0!(ber_complete!(parse_rdn)) >>
             ( Name{l: l} )
         ).map(|(rem,x)| (rem,x.1))
     }
@@ -181,7 +182,7 @@ fn struct_with_garbage() {
             class: 0,
             structured: 1,
             tag: BerTag::Sequence,
-            len: 0xc,
+            len: BerSize::Definite(0xc),
         },
         MyStruct {
             a: BerObject::from_int_slice(b"\x01\x00\x01"),
@@ -189,7 +190,7 @@ fn struct_with_garbage() {
         }
     );
     assert_eq!(parse_struct01(&bytes), Ok((empty, expected)));
-    assert_eq!(parse_struct01_complete(&bytes), Err(Err::Error(error_position!(&bytes[12..], ErrorKind::Eof))));
+    assert_eq!(parse_struct01_complete(&bytes), Err(Err::Error(error_position!(&bytes[12..], BerError::ObjectTooShort.into_error_kind()))));
 }
 
 #[test]
@@ -204,7 +205,7 @@ fn struct_verify_tag() {
             class: 0,
             structured: 1,
             tag: BerTag::Sequence,
-            len: 0xa,
+            len: BerSize::Definite(0xa),
         },
         MyStruct {
             a: BerObject::from_int_slice(b"\x01\x00\x01"),
@@ -219,14 +220,14 @@ fn struct_verify_tag() {
 
 #[test]
 fn tagged_explicit() {
-    fn parse_int_explicit(i:&[u8]) -> IResult<&[u8],u32> {
+    fn parse_int_explicit(i:&[u8]) -> BerResult<u32> {
         map_res!(
             i,
             parse_der_tagged!(EXPLICIT 2, parse_ber_integer),
             |x: BerObject| x.as_u32()
         )
     }
-    fn parse_int_noexplicit(i:&[u8]) -> IResult<&[u8],u32> {
+    fn parse_int_noexplicit(i:&[u8]) -> BerResult<u32> {
         map_res!(
             i,
             parse_der_tagged!(2, parse_ber_integer),
@@ -255,13 +256,17 @@ fn tagged_explicit() {
     // wrong type
     assert_eq!(
         parse_der_tagged!(bytes as &[u8],2,parse_ber_bool),
-        Err(Err::Error(error_position!(&bytes[4..],ErrorKind::Custom(BER_TAG_ERROR))))
+        Err(Err::Error(error_position!(&bytes[4..],ErrorKind::Custom(ParseError{
+            kind: BerError::UnexpectedTag{ expected: Some(BerTag::Boolean), actual: BerTag::Integer },
+            offset: 2,
+            path: vec![],
+        }))))
     );
 }
 
 #[test]
 fn tagged_implicit() {
-    fn parse_int_implicit(i:&[u8]) -> IResult<&[u8],u32> {
+    fn parse_int_implicit(i:&[u8]) -> BerResult<u32> {
         map_res!(
             i,
             parse_der_tagged!(IMPLICIT 2, BerTag::Integer),
@@ -291,7 +296,7 @@ fn application() {
     struct SimpleStruct {
         a: u32,
     };
-    fn parse_app01(i:&[u8]) -> IResult<&[u8],(BerObjectHeader,SimpleStruct)> {
+    fn parse_app01(i:&[u8]) -> BerResult<(BerObjectHeader,SimpleStruct)> {
         parse_der_application!(
             i,
             APPLICATION 2,
@@ -10,11 +10,16 @@ extern crate hex_literal;
 #[macro_use]
 extern crate rusticata_macros;
 
-use der_parser::ber::{ber_read_element_content_as, BerObjectContent, BerTag, BitStringObject};
+use der_parser::ber::{
+    ber_read_element_content_as, parse_ber, parse_ber_date, parse_ber_generalizedtime, parse_ber_integer, parse_ber_real,
+    parse_ber_enum, parse_ber_utctime, BerDate, BerObjectContent, BerSize, BerTag, BerTime, BerTimeZone, ParserOptions,
+    BitStringObject, MAX_RECURSION,
+};
 use der_parser::der::*;
 use der_parser::error::*;
 use der_parser::oid::*;
-use nom::{Err, ErrorKind, IResult};
+use nom::{Context, Err, ErrorKind};
+use std::borrow::Cow;
 
 #[test]
 fn test_der_bool() {
@@ -27,7 +32,11 @@ fn test_der_bool() {
         parse_der_bool(&[0x01, 0x01, 0x7f]),
         Err(Err::Error(error_position!(
             &[0x7f][..],
-            ErrorKind::Custom(DER_CONSTRAINT_FAIL)
+            ErrorKind::Custom(ParseError {
+                kind: BerError::DerConstraintFailed,
+                offset: 2,
+                path: vec![],
+            })
         )))
     );
 }
@@ -36,7 +45,7 @@ fn test_der_bool() {
 fn test_der_int() {
     let empty = &b""[..];
     let bytes = hex!("02 03 01 00 01");
-    let expected = DerObject::from_obj(BerObjectContent::Integer(b"\x01\x00\x01"));
+    let expected = DerObject::from_obj(BerObjectContent::Integer(Cow::Borrowed(b"\x01\x00\x01")));
     assert_eq!(parse_der_integer(&bytes), Ok((empty, expected)));
     let res = parse_der_u64(&bytes);
     assert_eq!(res.expect("integer").1, 0x10001);
@@ -52,6 +61,31 @@ fn test_der_int() {
     assert!(res.is_ok());
     let res = parse_der_u64(&bytes);
     assert!(res.is_err());
+    // non-minimal encodings (X.690 8.3.2)
+    for bytes in &[&[0x02, 0x00][..], &[0x02, 0x02, 0x00, 0x7f], &[0x02, 0x02, 0xff, 0x80]] {
+        assert_eq!(
+            parse_der_integer(bytes),
+            Err(Err::Error(error_position!(
+                &bytes[2..],
+                ErrorKind::Custom(ParseError {
+                    kind: BerError::DerConstraintFailed,
+                    offset: 2,
+                    path: vec![],
+                })
+            )))
+        );
+        assert!(parse_der_u64(bytes).is_err());
+        // accepted in BER
+        let (_, obj) = parse_ber_integer(bytes).expect("BER integer");
+        assert_eq!(obj.is_minimal(), Ok(false));
+    }
+    let (_, obj) = parse_der_integer(&[0x02, 0x02, 0x00, 0x80]).expect("DER integer");
+    assert_eq!(obj.is_minimal(), Ok(true));
+    // the encoding of enumerated values is not kept
+    let bytes = &[0x0a, 0x02, 0x00, 0x01];
+    let (_, obj) = parse_ber_enum(bytes).expect("BER enumerated");
+    assert_eq!(obj.is_minimal(), Err(BerError::BerTypeError));
+    assert!(parse_der_enum(bytes).is_err());
 }
 
 #[test]
@@ -63,7 +97,7 @@ fn test_der_bitstring_primitive() {
     let bytes = &[0x03, 0x04, 0x06, 0x6e, 0x5d, 0xc0];
     let expected = DerObject::from_obj(BerObjectContent::BitString(
         6,
-        BitStringObject { data: &bytes[3..] },
+        BitStringObject { data: Cow::Borrowed(&bytes[3..]) },
     ));
     assert_eq!(parse_der_bitstring(bytes), Ok((empty, expected)));
     //
@@ -74,7 +108,11 @@ fn test_der_bitstring_primitive() {
         parse_der_bitstring(bytes),
         Err(Err::Error(error_position!(
             &bytes[2..],
-            ErrorKind::Custom(DER_CONSTRAINT_FAIL)
+            ErrorKind::Custom(ParseError {
+                kind: BerError::DerConstraintFailed,
+                offset: 2,
+                path: vec![],
+            })
         )))
     );
     //
@@ -85,7 +123,7 @@ fn test_der_bitstring_primitive() {
         parse_der_bitstring(bytes),
         Err(Err::Error(error_position!(
             &bytes[..],
-            ErrorKind::Custom(DER_CONSTRAINT_FAIL)
+            ErrorKind::Custom(ParseError::new(BerError::DerConstraintFailed))
         )))
     );
 }
@@ -100,7 +138,7 @@ fn test_der_bitstring_constructed() {
         parse_der_bitstring(bytes),
         Err(Err::Error(error_position!(
             &bytes[..],
-            ErrorKind::Custom(DER_CONSTRAINT_FAIL)
+            ErrorKind::Custom(ParseError::new(BerError::DerConstraintFailed))
         )))
     );
 }
@@ -109,7 +147,7 @@ fn test_der_bitstring_constructed() {
 fn test_der_octetstring_primitive() {
     let empty = &b""[..];
     let bytes = [0x04, 0x05, 0x41, 0x41, 0x41, 0x41, 0x41];
-    let expected = DerObject::from_obj(BerObjectContent::OctetString(b"AAAAA"));
+    let expected = DerObject::from_obj(BerObjectContent::OctetString(Cow::Borrowed(b"AAAAA")));
     assert_eq!(parse_der_octetstring(&bytes), Ok((empty, expected)));
 }
 
@@ -128,7 +166,7 @@ fn test_der_oid() {
     ];
     let expected = DerObject::from_obj(BerObjectContent::OID(Oid::from(&[
         1, 2, 840, 113549, 1, 1, 5,
-    ])));
+    ]).unwrap()));
     assert_eq!(parse_der_oid(&bytes), Ok((empty, expected)));
 }
 
@@ -137,6 +175,8 @@ fn test_der_enum() {
     let empty = &b""[..];
     let expected = DerObject::from_obj(BerObjectContent::Enum(2));
     assert_eq!(parse_der_enum(&[0x0a, 0x01, 0x02]), Ok((empty, expected)));
+    assert!(parse_der_enum(&[0x0a, 0x02, 0x00, 0x02]).is_err());
+    assert!(parse_der_enum(&[0x0a, 0x00]).is_err());
 }
 
 #[test]
@@ -145,7 +185,7 @@ fn test_der_utf8string() {
     let bytes = [
         0x0c, 0x0a, 0x53, 0x6f, 0x6d, 0x65, 0x2d, 0x53, 0x74, 0x61, 0x74, 0x65,
     ];
-    let expected = DerObject::from_obj(BerObjectContent::UTF8String(b"Some-State"));
+    let expected = DerObject::from_obj(BerObjectContent::UTF8String(Cow::Borrowed(b"Some-State")));
     assert_eq!(parse_der_utf8string(&bytes), Ok((empty, expected)));
 }
 
@@ -153,7 +193,7 @@ fn test_der_utf8string() {
 fn test_der_relativeoid() {
     let empty = &b""[..];
     let bytes = [0x0d, 0x04, 0xc2, 0x7b, 0x03, 0x02];
-    let expected = DerObject::from_obj(BerObjectContent::RelativeOID(Oid::from(&[8571, 3, 2])));
+    let expected = DerObject::from_obj(BerObjectContent::RelativeOID(RelativeOid::from(&[8571, 3, 2])));
     assert_eq!(parse_der_relative_oid(&bytes), Ok((empty, expected)));
 }
 
@@ -183,7 +223,7 @@ fn test_der_seq_defined() {
         DerObject::from_int_slice(b"\x01\x00\x01"),
         DerObject::from_int_slice(b"\x01\x00\x00"),
     ]);
-    fn parser(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parser(i: &[u8]) -> DerResult {
         parse_der_sequence_defined!(i, parse_der_integer, parse_der_integer)
     };
     assert_eq!(parser(&bytes), Ok((empty, expected)));
@@ -199,7 +239,7 @@ fn test_der_set_defined() {
         DerObject::from_int_slice(b"\x01\x00\x01"),
         DerObject::from_int_slice(b"\x01\x00\x00"),
     ]);
-    fn parser(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parser(i: &[u8]) -> DerResult {
         parse_der_set_defined!(i, parse_der_integer, parse_der_integer)
     };
     assert_eq!(parser(&bytes), Ok((empty, expected)));
@@ -215,7 +255,7 @@ fn test_der_seq_of() {
         DerObject::from_int_slice(b"\x01\x00\x01"),
         DerObject::from_int_slice(b"\x01\x00\x00"),
     ]);
-    fn parser(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parser(i: &[u8]) -> DerResult {
         parse_der_sequence_of!(i, parse_der_integer)
     };
     assert_eq!(parser(&bytes), Ok((empty, expected)));
@@ -224,12 +264,15 @@ fn test_der_seq_of() {
 #[test]
 fn test_der_seq_of_incomplete() {
     let bytes = [0x30, 0x07, 0x02, 0x03, 0x01, 0x00, 0x01, 0x00, 0x00];
-    fn parser(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parser(i: &[u8]) -> DerResult {
         parse_der_sequence_of!(i, parse_der_integer)
     };
     assert_eq!(
         parser(&bytes),
-        Err(Err::Error(error_position!(&bytes[7..], ErrorKind::Eof)))
+        Err(Err::Error(error_position!(
+            &bytes[7..],
+            BerError::ObjectTooShort.into_error_kind()
+        )))
     );
 }
 
@@ -243,37 +286,243 @@ fn test_der_set_of() {
         DerObject::from_int_slice(b"\x01\x00\x01"),
         DerObject::from_int_slice(b"\x01\x00\x00"),
     ]);
-    fn parser(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parser(i: &[u8]) -> DerResult {
         parse_der_set_of!(i, parse_der_integer)
     };
+    fn parser_unordered(i: &[u8]) -> DerResult {
+        parse_der_set_of_unordered!(i, parse_der_integer)
+    };
+    // elements are not sorted
+    assert_eq!(
+        parser(&bytes),
+        Err(Err::Error(error_position!(
+            &bytes[7..],
+            BerError::DerConstraintFailed.into_error_kind()
+        )))
+    );
+    assert_eq!(parser_unordered(&bytes), Ok((empty, expected)));
+    let bytes = [
+        0x31, 0x0a, 0x02, 0x03, 0x01, 0x00, 0x00, 0x02, 0x03, 0x01, 0x00, 0x01,
+    ];
+    let expected = DerObject::from_set(vec![
+        DerObject::from_int_slice(b"\x01\x00\x00"),
+        DerObject::from_int_slice(b"\x01\x00\x01"),
+    ]);
     assert_eq!(parser(&bytes), Ok((empty, expected)));
 }
 
+#[test]
+fn test_der_set_order() {
+    // SET OF: sorted by encoding, shorter encodings first
+    let bytes = hex!("31 07 04 01 41 04 02 41 41");
+    assert!(parse_der_set(&bytes).is_ok());
+    assert!(parse_der(&bytes).is_ok());
+    let bytes = hex!("31 07 04 02 41 41 04 01 41");
+    assert!(parse_der_set(&bytes).is_err());
+    assert!(parse_der_set_unordered(&bytes).is_ok());
+    // SET: sorted by tag, even if [0] constructed is encoded as a greater octet than [1]
+    let bytes = hex!("31 08 a0 03 02 01 01 81 01 00");
+    assert!(parse_der_set(&bytes).is_ok());
+    fn parser(i: &[u8]) -> DerResult {
+        parse_der_set_defined!(i, parse_der_bool, parse_der_integer)
+    };
+    fn parser_unordered(i: &[u8]) -> DerResult {
+        parse_der_set_defined_unordered!(i, parse_der_integer, parse_der_bool)
+    };
+    assert!(parser(&hex!("31 06 01 01 ff 02 01 00")).is_ok());
+    assert!(parser_unordered(&hex!("31 06 02 01 00 01 01 ff")).is_ok());
+    let bytes = hex!("31 06 02 01 00 01 01 ff");
+    assert_eq!(
+        der_check_set_order(&bytes[2..], DerSetOrder::Set),
+        Err(Err::Error(error_position!(
+            &bytes[5..],
+            BerError::DerConstraintFailed.into_error_kind()
+        )))
+    );
+    // nested sets are checked (X.501 Name)
+    let bytes = hex!("30 0e 31 0c 06 03 55 04 03 06 03 55 04 0a 05 00");
+    assert!(parse_der(&bytes).is_err());
+    assert!(parse_der_unordered(&bytes).is_ok());
+    assert!(parse_ber(&bytes).is_ok());
+}
+
 #[test]
 fn test_der_utctime() {
     let empty = &b""[..];
     let bytes = hex!("17 0D 30 32 31 32 31 33 31 34 32 39 32 33 5A");
-    let expected = DerObject::from_obj(BerObjectContent::UTCTime(&bytes[2..]));
+    let expected = DerObject::from_obj(BerObjectContent::UTCTime(Cow::Borrowed(&bytes[2..])));
     assert_eq!(parse_der_utctime(&bytes), Ok((empty, expected)));
     let bytes = hex!("17 0c 30 32 31 32 31 33 31 34 32 39 32 33");
     parse_der_utctime(&bytes).err().expect("expected error");
+    // invalid date
+    let bytes = hex!("17 0D 30 32 31 33 31 33 31 34 32 39 32 33 5A");
+    parse_der_utctime(&bytes).err().expect("expected error");
+    // DER: seconds are required
+    let bytes = hex!("17 0B 30 32 31 32 31 33 31 34 32 39 5A");
+    parse_der_utctime(&bytes).err().expect("expected error");
+    let (_, obj) = parse_ber_utctime(&bytes).expect("parsing failed");
+    let t = obj.as_utctime().expect("invalid time");
+    assert_eq!((t.year, t.month, t.day, t.hour, t.minute, t.second), (2002, 12, 13, 14, 29, 0));
 }
 
 #[test]
 fn test_der_generalizedtime() {
     let empty = &b""[..];
-    let bytes = [
-        0x18, 0x0D, 0x30, 0x32, 0x31, 0x32, 0x31, 0x33, 0x31, 0x34, 0x32, 0x39, 0x32, 0x33, 0x5A,
-    ];
-    let expected = DerObject::from_obj(BerObjectContent::GeneralizedTime(&bytes[2..]));
-    assert_eq!(parse_der_generalizedtime(&bytes), Ok((empty, expected)));
+    let bytes = hex!("18 0F 32 30 30 32 31 32 31 33 31 34 32 39 32 33 5A");
+    let expected = DerObject::from_obj(BerObjectContent::GeneralizedTime(Cow::Borrowed(&bytes[2..])));
+    assert_eq!(parse_der_generalizedtime(&bytes), Ok((empty, expected.clone())));
+    let t = expected.as_generalizedtime().expect("invalid time");
+    assert_eq!((t.year, t.month, t.day, t.hour, t.minute, t.second), (2002, 12, 13, 14, 29, 23));
+    // DER: fraction without trailing zeros
+    let bytes = hex!("18 11 32 30 30 32 31 32 31 33 31 34 32 39 32 33 2e 35 5A");
+    let (_, obj) = parse_der_generalizedtime(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_generalizedtime().map(|t| t.nanosecond), Ok(500_000_000));
+    let bytes = hex!("18 12 32 30 30 32 31 32 31 33 31 34 32 39 32 33 2e 35 30 5A");
+    assert_eq!(
+        parse_der_generalizedtime(&bytes),
+        Err(Err::Error(error_position!(
+            &bytes[2..],
+            ErrorKind::Custom(ParseError {
+                kind: BerError::DerConstraintFailed,
+                offset: 2,
+                path: vec![],
+            })
+        )))
+    );
+    // DER: UTC time only
+    let bytes = hex!("18 13 32 30 30 32 31 32 31 33 31 34 32 39 32 33 2b 30 31 30 30");
+    parse_der_generalizedtime(&bytes).err().expect("expected error");
+    // BER accepts local time and time zones
+    let (_, obj) = parse_ber_generalizedtime(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_generalizedtime().map(|t| t.tz), Ok(BerTimeZone::Offset(60)));
+}
+
+#[test]
+fn test_der_real() {
+    let empty = &b""[..];
+    let bytes = hex!("09 03 80 fe 03");
+    assert_eq!(parse_der_real(&bytes), Ok((empty, DerObject::from_f64(0.75))));
+    let bytes = hex!("09 00");
+    assert_eq!(parse_der_real(&bytes), Ok((empty, DerObject::from_f64(0.0))));
+    let bytes = hex!("09 07 03 31 35 2e 45 2d 31");
+    assert_eq!(parse_der_real(&bytes), Ok((empty, DerObject::from_f64(1.5))));
+    // base 8 is valid in BER, not in DER
+    let bytes = hex!("09 03 90 02 03");
+    let (_, obj) = parse_ber_real(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_f64(), Ok(192.0));
+    assert_eq!(
+        parse_der_real(&bytes),
+        Err(Err::Error(error_position!(
+            &bytes[2..],
+            ErrorKind::Custom(ParseError {
+                kind: BerError::DerConstraintFailed,
+                offset: 2,
+                path: vec![],
+            })
+        )))
+    );
+    // even mantissa, and NR2 form
+    let bytes = hex!("09 03 80 00 02");
+    parse_der_real(&bytes).err().expect("expected error");
+    let bytes = hex!("09 04 02 31 2e 35");
+    parse_der_real(&bytes).err().expect("expected error");
+    // invalid special value
+    let bytes = hex!("09 01 44");
+    parse_ber_real(&bytes).err().expect("expected error");
+}
+
+#[test]
+fn test_der_iso8601_types() {
+    let empty = &b""[..];
+    let bytes = hex!("1f 1f 08 32 30 31 32 31 32 32 31");
+    let (rem, obj) = parse_der_date(&bytes).expect("parsing failed");
+    assert_eq!(rem, empty);
+    assert_eq!(obj.tag, BerTag::Date);
+    assert_eq!(obj.as_date(), Ok(BerDate { year: 2012, month: 12, day: 21 }));
+    let bytes = hex!("1f 20 06 31 35 33 30 30 30");
+    let (_, obj) = parse_der_timeofday(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_timeofday().map(|t| (t.hour, t.minute, t.second)), Ok((15, 30, 0)));
+    let bytes = hex!("1f 21 0e 32 30 31 32 31 32 32 31 31 35 33 30 30 30");
+    let (_, obj) = parse_der_datetime(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_datetime().map(|t| (t.year, t.hour)), Ok((2012, 15)));
+    let bytes = hex!("1f 22 04 50 54 31 48");
+    let (_, obj) = parse_der_duration(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_duration().map(|d| d.hours), Ok(1));
+    let bytes = hex!("0e 0a 32 30 31 32 2d 31 32 2d 32 31");
+    let (_, obj) = parse_der_time(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_time(), Ok(BerTime::Date(BerDate { year: 2012, month: 12, day: 21 })));
+    // intervals are not decoded, but accepted
+    let bytes = hex!("0e 07 50 31 44 2f 50 31 44");
+    let (_, obj) = parse_der_time(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_time(), Err(BerError::Unsupported));
+    // extended format is only accepted in BER
+    let bytes = hex!("1f 1f 0a 32 30 31 32 2d 31 32 2d 32 31");
+    let (_, obj) = parse_ber_date(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_date(), Ok(BerDate { year: 2012, month: 12, day: 21 }));
+    assert_eq!(
+        parse_der_date(&bytes),
+        Err(Err::Error(error_position!(
+            &bytes[3..],
+            ErrorKind::Custom(ParseError {
+                kind: BerError::DerConstraintFailed,
+                offset: 3,
+                path: vec![],
+            })
+        )))
+    );
+    let bytes = hex!("1f 20 06 32 35 33 30 30 30");
+    parse_der_timeofday(&bytes).err().expect("expected error");
+    let bytes = hex!("1f 22 01 50");
+    parse_der_duration(&bytes).err().expect("expected error");
+}
+
+#[test]
+fn test_der_string_charset() {
+    let empty = &b""[..];
+    let bytes = hex!("13 03 61 2d 62");
+    let expected = DerObject::from_obj(BerObjectContent::PrintableString(Cow::Borrowed(b"a-b")));
+    assert_eq!(parse_der_printablestring(&bytes), Ok((empty, expected)));
+    let bytes = hex!("13 03 61 2a 62");
+    assert_eq!(
+        parse_der_printablestring(&bytes),
+        Err(Err::Error(error_position!(
+            &bytes[2..],
+            ErrorKind::Custom(ParseError {
+                kind: BerError::StringInvalidCharset,
+                offset: 2,
+                path: vec![],
+            })
+        )))
+    );
+    let bytes = hex!("12 04 31 32 20 33");
+    let expected = DerObject::from_obj(BerObjectContent::NumericString(Cow::Borrowed(b"12 3")));
+    assert_eq!(parse_der_numericstring(&bytes), Ok((empty, expected)));
+    let bytes = hex!("12 03 31 32 2e");
+    parse_der_numericstring(&bytes).err().expect("expected error");
+    let bytes = hex!("16 02 61 e9");
+    parse_der_ia5string(&bytes).err().expect("expected error");
+    let bytes = hex!("1a 02 61 0a");
+    parse_der_visiblestring(&bytes).err().expect("expected error");
+    let bytes = hex!("19 03 1b 61 0d");
+    parse_der_graphicstring(&bytes).err().expect("expected error");
+    let bytes = hex!("07 02 61 62");
+    let expected = DerObject::from_obj(BerObjectContent::ObjectDescriptor(Cow::Borrowed(b"ab")));
+    assert_eq!(parse_der_objectdescriptor(&bytes), Ok((empty, expected)));
+    let bytes = hex!("1c 04 00 00 00 61");
+    let expected = DerObject::from_obj(BerObjectContent::UniversalString(Cow::Borrowed(b"\x00\x00\x00a")));
+    assert_eq!(parse_der_universalstring(&bytes), Ok((empty, expected)));
+    let bytes = hex!("1c 03 00 00 61");
+    parse_der_universalstring(&bytes).err().expect("expected error");
+    // constructed form is not allowed
+    let bytes = hex!("3a 03 04 01 61");
+    parse_der_visiblestring(&bytes).err().expect("expected error");
 }
 
 #[test]
 fn test_der_generalstring() {
     let empty = &b""[..];
     let bytes = [0x1b, 0x04, 0x63, 0x69, 0x66, 0x73];
-    let expected = DerObject::from_obj(BerObjectContent::GeneralString(b"cifs"));
+    let expected = DerObject::from_obj(BerObjectContent::GeneralString(Cow::Borrowed(b"cifs")));
     assert_eq!(parse_der_generalstring(&bytes), Ok((empty, expected)));
 }
 
@@ -285,7 +534,7 @@ fn test_der_contextspecific() {
         class: 2,
         structured: 1,
         tag: BerTag(0),
-        content: BerObjectContent::Unknown(BerTag(0), &bytes[2..]),
+        content: BerObjectContent::Unknown(BerTag(0), Cow::Borrowed(&bytes[2..])),
     };
     assert_eq!(parse_der(&bytes), Ok((empty, expected)));
 }
@@ -318,7 +567,7 @@ fn test_der_explicit() {
 fn test_der_implicit() {
     let empty = &b""[..];
     let bytes = [0x81, 0x04, 0x70, 0x61, 0x73, 0x73];
-    let pass = DerObject::from_obj(BerObjectContent::IA5String(b"pass"));
+    let pass = DerObject::from_obj(BerObjectContent::IA5String(Cow::Borrowed(b"pass")));
     let expected = DerObject {
         class: 2,
         structured: 0,
@@ -328,8 +577,8 @@ fn test_der_implicit() {
     fn der_read_ia5string_content(
         i: &[u8],
         _tag: BerTag,
-        len: usize,
-    ) -> IResult<&[u8], BerObjectContent, u32> {
+        len: BerSize,
+    ) -> BerResult<BerObjectContent> {
         ber_read_element_content_as(i, DerTag::Ia5String, len, false, 0)
     }
     assert_eq!(
@@ -347,7 +596,7 @@ fn test_der_implicit() {
 fn test_der_implicit_long_tag() {
     let empty = &b""[..];
     let bytes = [0x5f, 0x52, 0x04, 0x70, 0x61, 0x73, 0x73];
-    let pass = DerObject::from_obj(BerObjectContent::IA5String(b"pass"));
+    let pass = DerObject::from_obj(BerObjectContent::IA5String(Cow::Borrowed(b"pass")));
     let expected = DerObject {
         class: 1,
         structured: 0,
@@ -357,8 +606,8 @@ fn test_der_implicit_long_tag() {
     fn der_read_ia5string_content(
         i: &[u8],
         _tag: BerTag,
-        len: usize,
-    ) -> IResult<&[u8], BerObjectContent, u32> {
+        len: BerSize,
+    ) -> BerResult<BerObjectContent> {
         ber_read_element_content_as(i, DerTag::Ia5String, len, false, 0)
     }
     assert_eq!(
@@ -376,7 +625,7 @@ fn test_der_implicit_long_tag() {
 fn test_der_optional() {
     let empty = &b""[..];
     let bytes1 = [
-        0x30, 0x0a, 0x0a, 0x03, 0x00, 0x00, 0x01, 0x02, 0x03, 0x01, 0x00, 0x01,
+        0x30, 0x08, 0x0a, 0x01, 0x01, 0x02, 0x03, 0x01, 0x00, 0x01,
     ];
     let bytes2 = [0x30, 0x05, 0x02, 0x03, 0x01, 0x00, 0x01];
     let expected1 = DerObject::from_seq(vec![
@@ -390,10 +639,10 @@ fn test_der_optional() {
         DerObject::from_obj(BerObjectContent::ContextSpecific(BerTag(0), None)),
         DerObject::from_int_slice(b"\x01\x00\x01"),
     ]);
-    fn parse_optional_enum(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parse_optional_enum(i: &[u8]) -> DerResult {
         parse_der_optional!(i, parse_der_enum)
     }
-    fn parser(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parser(i: &[u8]) -> DerResult {
         parse_der_sequence_defined!(i, parse_optional_enum, parse_der_integer)
     };
     assert_eq!(parser(&bytes1), Ok((empty, expected1)));
@@ -412,16 +661,16 @@ fn test_der_seq_dn() {
     ];
     let expected = DerObject::from_seq(vec![
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 6]))), // countryName
-            DerObject::from_obj(BerObjectContent::PrintableString(b"FR")),
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 6]).unwrap())), // countryName
+            DerObject::from_obj(BerObjectContent::PrintableString(Cow::Borrowed(b"FR"))),
         ])]),
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 8]))), // stateOrProvinceName
-            DerObject::from_obj(BerObjectContent::UTF8String(b"Some-State")),
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 8]).unwrap())), // stateOrProvinceName
+            DerObject::from_obj(BerObjectContent::UTF8String(Cow::Borrowed(b"Some-State"))),
         ])]),
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 10]))), // organizationName
-            DerObject::from_obj(BerObjectContent::UTF8String(b"Internet Widgits Pty Ltd")),
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 10]).unwrap())), // organizationName
+            DerObject::from_obj(BerObjectContent::UTF8String(Cow::Borrowed(b"Internet Widgits Pty Ltd"))),
         ])]),
     ]);
     assert_eq!(parse_der(&bytes), Ok((empty, expected)));
@@ -439,35 +688,35 @@ fn test_der_seq_dn_defined() {
     ];
     let expected = DerObject::from_seq(vec![
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 6]))), // countryName
-            DerObject::from_obj(BerObjectContent::PrintableString(b"FR")),
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 6]).unwrap())), // countryName
+            DerObject::from_obj(BerObjectContent::PrintableString(Cow::Borrowed(b"FR"))),
         ])]),
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 8]))), // stateOrProvinceName
-            DerObject::from_obj(BerObjectContent::UTF8String(b"Some-State")),
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 8]).unwrap())), // stateOrProvinceName
+            DerObject::from_obj(BerObjectContent::UTF8String(Cow::Borrowed(b"Some-State"))),
         ])]),
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 10]))), // organizationName
-            DerObject::from_obj(BerObjectContent::UTF8String(b"Internet Widgits Pty Ltd")),
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 10]).unwrap())), // organizationName
+            DerObject::from_obj(BerObjectContent::UTF8String(Cow::Borrowed(b"Internet Widgits Pty Ltd"))),
         ])]),
     ]);
     #[inline]
-    fn parse_directory_string(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parse_directory_string(i: &[u8]) -> DerResult {
         alt!(
             i,
             parse_der_utf8string | parse_der_printablestring | parse_der_ia5string
         )
     }
     #[inline]
-    fn parse_attr_type_and_value(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parse_attr_type_and_value(i: &[u8]) -> DerResult {
         parse_der_sequence_defined!(i, parse_der_oid, parse_directory_string)
     };
     #[inline]
-    fn parse_rdn(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parse_rdn(i: &[u8]) -> DerResult {
         parse_der_set_defined!(i, parse_attr_type_and_value)
     }
     #[inline]
-    fn parse_name(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn parse_name(i: &[u8]) -> DerResult {
         parse_der_sequence_defined!(i, parse_rdn, parse_rdn, parse_rdn)
     }
     assert_eq!(parse_name(&bytes), Ok((empty, expected)));
@@ -475,7 +724,7 @@ fn test_der_seq_dn_defined() {
 
 #[test]
 fn test_der_defined_seq_macros() {
-    fn localparse_seq(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn localparse_seq(i: &[u8]) -> DerResult {
         parse_der_sequence_defined_m! {
             i,
             parse_der_integer >>
@@ -495,7 +744,7 @@ fn test_der_defined_seq_macros() {
 
 #[test]
 fn test_der_defined_set_macros() {
-    fn localparse_set(i: &[u8]) -> IResult<&[u8], DerObject> {
+    fn localparse_set(i: &[u8]) -> DerResult {
         parse_der_set_defined_m! {
             i,
             parse_der_integer >>
@@ -517,31 +766,139 @@ fn test_der_defined_set_macros() {
 fn test_parse_u32() {
     let empty = &b""[..];
     assert_eq!(parse_der_u32(&[0x02, 0x01, 0x01]),Ok((empty,1)));
-    assert_eq!(parse_der_u32(&[0x02, 0x01, 0xff]),Ok((empty,255)));
+    assert_eq!(parse_der_u32(&[0x02, 0x02, 0x00, 0xff]),Ok((empty,255)));
     assert_eq!(parse_der_u32(&[0x02, 0x02, 0x01, 0x23]),Ok((empty,0x123)));
-    assert_eq!(parse_der_u32(&[0x02, 0x02, 0xff, 0xff]),Ok((empty,0xffff)));
+    assert_eq!(parse_der_u32(&[0x02, 0x03, 0x00, 0xff, 0xff]),Ok((empty,0xffff)));
     assert_eq!(parse_der_u32(&[0x02, 0x03, 0x01, 0x23, 0x45]),Ok((empty,0x12345)));
-    assert_eq!(parse_der_u32(&[0x02, 0x03, 0xff, 0xff, 0xff]),Ok((empty,0xffffff)));
+    assert_eq!(parse_der_u32(&[0x02, 0x04, 0x00, 0xff, 0xff, 0xff]),Ok((empty,0xffffff)));
     assert_eq!(parse_der_u32(&[0x02, 0x04, 0x01, 0x23, 0x45, 0x67]),Ok((empty,0x1234567)));
-    assert_eq!(parse_der_u32(&[0x02, 0x04, 0xff, 0xff, 0xff, 0xff]),Ok((empty,0xffffffff)));
+    assert_eq!(parse_der_u32(&[0x02, 0x05, 0x00, 0xff, 0xff, 0xff, 0xff]),Ok((empty,0xffffffff)));
     let s = &[0x02, 0x05, 0x01, 0x23, 0x45, 0x67, 0x89];
-    assert_eq!(parse_der_u32(s),Err(Err::Error(error_position!(&s[2..],ErrorKind::Custom(BER_INTEGER_TOO_LARGE)))));
+    assert_eq!(parse_der_u32(s),Err(Err::Error(error_position!(&s[2..],ErrorKind::Custom(ParseError{ kind: BerError::IntegerTooLarge, offset: 2, path: vec![] })))));
     let s = &[0x01, 0x01, 0xff];
-    assert_eq!(parse_der_u32(s),Err(Err::Error(error_position!(&s[2..], ErrorKind::Custom(BER_TAG_ERROR)))));
+    assert_eq!(parse_der_u32(s),Err(Err::Error(error_position!(&s[2..], ErrorKind::Custom(ParseError{ kind: BerError::UnexpectedTag{ expected: Some(BerTag::Integer), actual: BerTag::Boolean }, offset: 2, path: vec![] })))));
 }
 
 #[test]
 fn test_parse_u64() {
     let empty = &b""[..];
     assert_eq!(parse_der_u64(&[0x02, 0x01, 0x01]),Ok((empty,1)));
-    assert_eq!(parse_der_u64(&[0x02, 0x01, 0xff]),Ok((empty,255)));
+    assert_eq!(parse_der_u64(&[0x02, 0x02, 0x00, 0xff]),Ok((empty,255)));
     assert_eq!(parse_der_u64(&[0x02, 0x02, 0x01, 0x23]),Ok((empty,0x123)));
-    assert_eq!(parse_der_u64(&[0x02, 0x02, 0xff, 0xff]),Ok((empty,0xffff)));
+    assert_eq!(parse_der_u64(&[0x02, 0x03, 0x00, 0xff, 0xff]),Ok((empty,0xffff)));
     assert_eq!(parse_der_u64(&[0x02, 0x03, 0x01, 0x23, 0x45]),Ok((empty,0x12345)));
-    assert_eq!(parse_der_u64(&[0x02, 0x03, 0xff, 0xff, 0xff]),Ok((empty,0xffffff)));
+    assert_eq!(parse_der_u64(&[0x02, 0x04, 0x00, 0xff, 0xff, 0xff]),Ok((empty,0xffffff)));
     assert_eq!(parse_der_u64(&[0x02, 0x04, 0x01, 0x23, 0x45, 0x67]),Ok((empty,0x1234567)));
-    assert_eq!(parse_der_u64(&[0x02, 0x04, 0xff, 0xff, 0xff, 0xff]),Ok((empty,0xffffffff)));
+    assert_eq!(parse_der_u64(&[0x02, 0x05, 0x00, 0xff, 0xff, 0xff, 0xff]),Ok((empty,0xffffffff)));
     assert_eq!(parse_der_u64(&[0x02, 0x05, 0x01, 0x23, 0x45, 0x67, 0x89]),Ok((empty,0x123456789)));
     let s = &[0x01, 0x01, 0xff];
-    assert_eq!(parse_der_u64(s),Err(Err::Error(error_position!(&s[2..], ErrorKind::Custom(BER_TAG_ERROR)))));
+    assert_eq!(parse_der_u64(s),Err(Err::Error(error_position!(&s[2..], ErrorKind::Custom(ParseError{ kind: BerError::UnexpectedTag{ expected: Some(BerTag::Integer), actual: BerTag::Boolean }, offset: 2, path: vec![] })))));
+}
+
+#[test]
+fn test_parse_i32() {
+    let empty = &b""[..];
+    assert_eq!(parse_der_i32(&[0x02, 0x01, 0x01]), Ok((empty, 1)));
+    assert_eq!(parse_der_i32(&[0x02, 0x01, 0xff]), Ok((empty, -1)));
+    assert_eq!(parse_der_i32(&[0x02, 0x02, 0x00, 0xff]), Ok((empty, 255)));
+    assert_eq!(parse_der_i32(&[0x02, 0x02, 0xff, 0x7f]), Ok((empty, -129)));
+    assert_eq!(parse_der_i32(&[0x02, 0x04, 0x80, 0x00, 0x00, 0x00]), Ok((empty, i32::MIN)));
+    let s = &[0x02, 0x05, 0x00, 0x80, 0x00, 0x00, 0x00];
+    assert_eq!(parse_der_i32(s), Err(Err::Error(error_position!(&s[2..], ErrorKind::Custom(ParseError{ kind: BerError::IntegerTooLarge, offset: 2, path: vec![] })))));
+}
+
+#[test]
+fn test_parse_i64() {
+    let empty = &b""[..];
+    assert_eq!(parse_der_i64(&[0x02, 0x01, 0x80]), Ok((empty, -128)));
+    assert_eq!(parse_der_i64(&[0x02, 0x05, 0x00, 0x80, 0x00, 0x00, 0x00]), Ok((empty, 0x8000_0000)));
+    assert_eq!(parse_der_i64(&[0x02, 0x05, 0xff, 0x7f, 0xff, 0xff, 0xff]), Ok((empty, -0x8000_0001)));
+    let s = &[0x01, 0x01, 0xff];
+    assert_eq!(parse_der_i64(s), Err(Err::Error(error_position!(&s[2..], ErrorKind::Custom(ParseError{ kind: BerError::UnexpectedTag{ expected: Some(BerTag::Integer), actual: BerTag::Boolean }, offset: 2, path: vec![] })))));
+}
+
+#[test]
+fn test_parse_unsigned_negative() {
+    let s = &[0x02, 0x01, 0xff];
+    assert_eq!(parse_der_u32(s), Err(Err::Error(error_position!(&s[2..], ErrorKind::Custom(ParseError{ kind: BerError::IntegerNegative, offset: 2, path: vec![] })))));
+    assert_eq!(parse_der_u64(s), Err(Err::Error(error_position!(&s[2..], ErrorKind::Custom(ParseError{ kind: BerError::IntegerNegative, offset: 2, path: vec![] })))));
+}
+
+#[test]
+fn test_validate_der() {
+    let bytes = hex!(
+        "30 80
+            01 01 01
+            02 02 00 01
+            03 02 01 01
+            17 0f 39 39 31 32 33 31 32 33 35 39 2d 30 31 33 30
+            24 80 04 01 41 00 00
+            31 06 02 01 02 02 01 01
+            04 81 01 41
+         00 00"
+    );
+    let violations = validate_der(&bytes);
+    let found: Vec<_> = violations.iter().map(|v| (v.kind, v.offset)).collect();
+    assert_eq!(
+        found,
+        vec![
+            (DerViolationKind::IndefiniteLength, 0),
+            (DerViolationKind::NonCanonicalBoolean, 2),
+            (DerViolationKind::NonMinimalInteger, 5),
+            (DerViolationKind::BitStringPadding, 9),
+            (DerViolationKind::NonCanonicalTime, 13),
+            (DerViolationKind::IndefiniteLength, 30),
+            (DerViolationKind::ConstructedString, 30),
+            (DerViolationKind::UnsortedSet, 42),
+            (DerViolationKind::NonMinimalLength, 45),
+        ]
+    );
+    assert_eq!(violations[3].clause(), "11.2.1");
+    assert_eq!(
+        violations[1].to_string(),
+        "offset 2: boolean value is not 0x00 or 0xff (X.690 11.1)"
+    );
+    // valid DER
+    let bytes = hex!("30 0c 31 0a 06 03 55 04 03 06 03 55 04 0a");
+    assert_eq!(validate_der(&bytes), vec![]);
+    assert!(parse_der(&bytes).is_ok());
+    assert!(validate_der(&hex!("30 08 31 06 02 01 01 02 01 02")).is_empty());
+    // invalid BER: validation stops
+    let violations = validate_der(&hex!("30 06 02 02 00 01 02 05 01"));
+    let found: Vec<_> = violations.iter().map(|v| (v.kind, v.offset)).collect();
+    assert_eq!(
+        found,
+        vec![
+            (DerViolationKind::NonMinimalInteger, 2),
+            (DerViolationKind::Malformed(BerError::InvalidLength), 6),
+        ]
+    );
+}
+
+#[test]
+fn test_der_parser_options() {
+    let options = ParserOptions {
+        max_elements: 2,
+        ..Default::default()
+    };
+    assert!(parse_der_with_options(&hex!("30 06 02 01 01 02 01 02"), &options).is_ok());
+    assert!(parse_der_with_options(&hex!("30 09 02 01 01 02 01 02 02 01 03"), &options).is_err());
+    // DER constraints are still verified
+    assert!(parse_der_with_options(&hex!("30 80 02 01 01 00 00"), &options).is_err());
+    assert!(parse_der_with_options(&hex!("01 01 01"), &options).is_err());
+    // depth
+    let bytes = hex!("30 05 30 03 02 01 01");
+    let options = ParserOptions { max_depth: 1, ..Default::default() };
+    assert!(parse_der_with_options(&bytes, &options).is_err());
+    let options = ParserOptions { max_depth: 2, ..Default::default() };
+    assert!(parse_der_with_options(&bytes, &options).is_ok());
+    let options = ParserOptions { max_depth: MAX_RECURSION + 1, ..Default::default() };
+    match parse_der_with_options(&bytes, &options) {
+        Err(Err::Error(Context::Code(_, ErrorKind::Custom(e)))) => assert_eq!(e.kind, BerError::Unsupported),
+        e => panic!("unexpected result {:?}", e),
+    }
+    // private tags are decoded as universal types by parse_der, so their content is checked
+    let options = ParserOptions { max_elements: 1, ..Default::default() };
+    assert!(parse_der_with_options(&hex!("f0 06 02 01 01 02 01 02"), &options).is_err());
+    assert!(parse_der_with_options(&hex!("a0 06 02 01 01 02 01 02"), &options).is_ok());
 }
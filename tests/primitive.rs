@@ -8,46 +8,55 @@ use der_parser::*;
 use der_parser::ber::*;
 use der_parser::der::*;
 use der_parser::error::*;
-use der_parser::oid::Oid;
+use der_parser::oid::{Oid, RelativeOid};
 use nom::{Context, Err, ErrorKind, Needed};
+use std::borrow::Cow;
 
 #[test]
 fn test_flat_take() {
     let empty = &b""[..];
     assert_eq!(parse_ber_bool(&[0x01, 0x01, 0xff]), Ok((empty, BerObject::from_obj(BerObjectContent::Boolean(true)))));
     assert_eq!(parse_ber_bool(&[0x01, 0x01, 0x00]), Ok((empty, BerObject::from_obj(BerObjectContent::Boolean(false)))));
-    assert_eq!(ber_read_element_content_as(&[0xff], BerTag::Boolean, 0x01, false, 0), Ok((empty, BerObjectContent::Boolean(true))));
-    assert_eq!(ber_read_element_content_as(&[0x00], BerTag::Boolean, 0x01, false, 0), Ok((empty, BerObjectContent::Boolean(false))));
+    assert_eq!(ber_read_element_content_as(&[0xff], BerTag::Boolean, BerSize::Definite(1), false, 0), Ok((empty, BerObjectContent::Boolean(true))));
+    assert_eq!(ber_read_element_content_as(&[0x00], BerTag::Boolean, BerSize::Definite(1), false, 0), Ok((empty, BerObjectContent::Boolean(false))));
 }
 
 #[test]
 fn test_oid() {
     let empty = &b""[..];
-    assert_eq!(parse_der(&[0x06, 0x06, 42, 129, 122, 1, 16, 9]), Ok((empty, BerObject::from_obj(BerObjectContent::OID(Oid::from(&[1,2,250,1,16,9]))))));
+    assert_eq!(parse_der(&[0x06, 0x06, 42, 129, 122, 1, 16, 9]), Ok((empty, BerObject::from_obj(BerObjectContent::OID(Oid::from(&[1,2,250,1,16,9]).unwrap())))));
     // Dubuisson 433
-    assert_eq!(parse_der(&[0x06, 0x05, 129, 122, 1, 16, 9]), Ok((empty, BerObject::from_obj(BerObjectContent::OID(Oid::from(&[250,1,16,9]))))));
+    assert_eq!(parse_der(&[0x06, 0x05, 129, 122, 1, 16, 9]), Ok((empty, BerObject::from_obj(BerObjectContent::OID(Oid::from(&[2,170,1,16,9]).unwrap())))));
+    // UUID-based OID, with a 128-bits arc
+    let bytes = hex!("06 14 69 83 f0 9d a7 eb cf de e0 c7 a1 a7 b2 c0 94 8c c8 f9 d7 76");
+    let (_, obj) = parse_der(&bytes).expect("parsing failed");
+    let oid = obj.as_oid().expect("not an OID");
+    assert_eq!(oid.to_string(), "2.25.329800735698586629295641978511506172918");
+    assert!(oid.iter().is_none());
+    // truncated subidentifier
+    assert!(parse_der(&[0x06, 0x02, 42, 129]).is_err());
 }
 
 #[test]
 fn test_rel_oid() {
     let empty = &b""[..];
-    assert_eq!(parse_der(&[0x0d, 0x04, 0xc2, 0x7b, 0x03, 0x02]), Ok((empty, BerObject::from_obj(BerObjectContent::RelativeOID(Oid::from(&[8571,3,2]))))));
+    assert_eq!(parse_der(&[0x0d, 0x04, 0xc2, 0x7b, 0x03, 0x02]), Ok((empty, BerObject::from_obj(BerObjectContent::RelativeOID(RelativeOid::from(&[8571,3,2]))))));
 }
 
 #[test]
 fn test_unknown_tag() {
-    let bytes = hex!("1d 01 00");
+    let bytes = hex!("1f 25 01 00");
     let res = parse_ber(&bytes).expect("parsing failed");
     assert!(res.0.is_empty());
     assert_eq!(
         res.1,
-        BerObject::from_obj(BerObjectContent::Unknown(BerTag(0x1d), &bytes[2..]))
+        BerObject::from_obj(BerObjectContent::Unknown(BerTag(0x25), Cow::Borrowed(&bytes[3..])))
     );
     let res = parse_der(&bytes).expect("parsing failed");
     assert!(res.0.is_empty());
     assert_eq!(
         res.1,
-        BerObject::from_obj(BerObjectContent::Unknown(BerTag(0x1d), &bytes[2..]))
+        BerObject::from_obj(BerObjectContent::Unknown(BerTag(0x25), Cow::Borrowed(&bytes[3..])))
     );
 }
 
@@ -62,7 +71,7 @@ fn test_unknown_context_specific() {
             class: 2,
             structured: 0,
             tag: BerTag(0),
-            content: BerObjectContent::Unknown(BerTag(0x0), &bytes[2..])
+            content: BerObjectContent::Unknown(BerTag(0x0), Cow::Borrowed(&bytes[2..]))
         }
     );
 }
@@ -78,7 +87,7 @@ fn test_unknown_long_tag() {
             class: 2,
             structured: 0,
             tag: BerTag(0x22),
-            content: BerObjectContent::Unknown(BerTag(0x22), &bytes[3..])
+            content: BerObjectContent::Unknown(BerTag(0x22), Cow::Borrowed(&bytes[3..]))
         }
     );
 }
@@ -94,7 +103,7 @@ fn test_unknown_longer_tag() {
             class: 2,
             structured: 0,
             tag: BerTag(0x1122),
-            content: BerObjectContent::Unknown(BerTag(0x1122), &bytes[4..])
+            content: BerObjectContent::Unknown(BerTag(0x1122), Cow::Borrowed(&bytes[4..]))
         }
     );
 }
@@ -143,7 +152,7 @@ fn test_invalid_length() {
     // get errorkind
     match res {
         Err::Error(Context::Code(_,code)) => {
-            assert_eq!(code, ErrorKind::Custom(BER_INVALID_LENGTH));
+            assert_eq!(code, BerError::InvalidLength.into_error_kind());
         },
         _ => assert!(false),
     }
@@ -159,7 +168,7 @@ fn test_invalid_param() {
         class: 8,
         structured: 0,
         tag: BerTag(2),
-        len: 1,
+        len: BerSize::Definite(1),
     };
     der_read_element_content(&bytes, hdr).err().expect("expected erreur");
 }